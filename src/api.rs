@@ -1,59 +1,477 @@
-#![allow(dead_code)]
-
+use crate::models::{
+    BrowseEntry, Completion, Connections, DbFile, DbStatus, DebugFacilities, DeviceConfig,
+    DeviceIdCheck, DeviceStats, DiscoveryEntry, Event, FileVersion, FolderConfig, FolderErrors,
+    FolderStats, GuiConfig, Ignores, LdapConfig, LocalChanged, LogEntry, LogList, Need, Options,
+    PendingDevice, PendingFolder, Ping, RestartRequired, SystemStatus, Version,
+};
 use anyhow::{Context, Result};
+use reqwest::{Method, StatusCode};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::WebPkiSupportedAlgorithms;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Syncthing answered a request with an error status.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    /// Syncthing's explanation from the response body, if it gave one.
+    pub message: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "API error: {}", self.status)?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+/// Requests a client has in flight at once unless configured otherwise.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 8;
+
+/// Files per request when `db_need_all` pages through `/rest/db/need`.
+const NEED_PAGE_SIZE: u64 = 10_000;
+
+/// Delay before the first retry; doubled for each further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Keys in `/rest/config` whose values are secrets.
+pub const SECRET_KEYS: &[&str] = &["apiKey", "password", "bindPassword", "encryptionPassword"];
+pub const REDACTED: &str = "<redacted>";
+
+/// Connection settings beyond the host and API key.
+#[derive(Debug, Clone, Default)]
+pub struct ClientOptions {
+    /// Accept invalid or self-signed TLS certificates.
+    pub insecure: bool,
+    /// PEM file with an additional trusted certificate, such as Syncthing's
+    /// own `https-cert.pem`.
+    pub ca_cert: Option<PathBuf>,
+    /// Expected SHA-256 fingerprint (hex) of the server certificate. When set,
+    /// the fingerprint is checked during the handshake instead of the
+    /// certificate chain (unless `insecure` turns checking off altogether).
+    pub pinned_cert: Option<String>,
+    /// Give up on a request after this long (no limit when `None`).
+    pub timeout: Option<Duration>,
+    /// How often to retry connection failures and 5xx responses.
+    pub retries: u32,
+    /// Directory for caching the folder and device lists (no caching when
+    /// `None`). Any successful write through the client clears it.
+    pub cache_dir: Option<PathBuf>,
+    /// How long cached lists stay fresh.
+    pub cache_ttl: Duration,
+    /// Log requests to stderr: 1 for method, URL, status and timing, 2 to
+    /// add request and response bodies.
+    pub verbosity: u8,
+    /// Print POST/PATCH/DELETE requests to stderr instead of sending them.
+    pub dry_run: bool,
+    /// Most requests in flight at once, across all clones of the client
+    /// (`DEFAULT_MAX_CONNECTIONS` when `None`).
+    pub max_connections: Option<usize>,
+}
 
+#[derive(Clone)]
 pub struct Client {
     http: reqwest::Client,
     api_key: String,
+    /// The host as given, e.g. `https://nas:8384` or `unix:///run/syncthing/api.sock`.
     base_url: String,
+    /// Prefix for request URLs; differs from `base_url` for Unix sockets.
+    url_base: String,
+    timeout: Option<Duration>,
+    retries: u32,
+    cache_dir: Option<PathBuf>,
+    cache_ttl: Duration,
+    verbosity: u8,
+    dry_run: bool,
+    /// Shared by clones, so fanned-out requests queue here instead of
+    /// opening ever more connections.
+    permits: Arc<Semaphore>,
+    max_connections: usize,
+}
+
+#[cfg(unix)]
+fn unix_socket(builder: reqwest::ClientBuilder, path: &str) -> Result<reqwest::ClientBuilder> {
+    Ok(builder.unix_socket(PathBuf::from(path)))
+}
+
+#[cfg(not(unix))]
+fn unix_socket(_builder: reqwest::ClientBuilder, path: &str) -> Result<reqwest::ClientBuilder> {
+    anyhow::bail!("Unix sockets are not supported on this platform ({})", path)
+}
+
+/// Hex SHA-256 of a DER certificate, as stored for pinning.
+pub fn cert_fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Accepts exactly the server certificate with the pinned fingerprint. It runs
+/// during the TLS handshake, so nothing (least of all the API key) is sent to
+/// a server that doesn't present it.
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: String,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let actual = cert_fingerprint(end_entity);
+        if actual.eq_ignore_ascii_case(&self.fingerprint) {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(format!(
+                "certificate does not match the pinned fingerprint (expected {}, got {})",
+                self.fingerprint, actual
+            )))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// TLS settings that trust only the certificate with `fingerprint`.
+fn pinned_tls(fingerprint: &str) -> Result<rustls::ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = PinnedCertVerifier {
+        fingerprint: fingerprint.to_string(),
+        algorithms: provider.signature_verification_algorithms,
+    };
+    Ok(rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth())
+}
+
+/// Create `dir` readable only by the current user, since folder configs can
+/// hold encryption passwords.
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+/// Parse the cache file at `path` if it was written less than `ttl` ago.
+fn read_fresh<T: DeserializeOwned>(path: &Path, ttl: Duration) -> Option<T> {
+    let age = std::fs::metadata(path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    if age >= ttl {
+        return None;
+    }
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
 }
 
 impl Client {
     pub fn new(api_key: &str, base_url: &str) -> Result<Self> {
-        let http = reqwest::Client::builder()
-            .danger_accept_invalid_certs(true) // Syncthing uses self-signed certs
-            .build()?;
+        Self::with_options(api_key, base_url, &ClientOptions::default())
+    }
+
+    pub fn with_options(api_key: &str, base_url: &str, options: &ClientOptions) -> Result<Self> {
+        let mut builder = reqwest::Client::builder().tls_info(true);
+        if let Some(timeout) = options.timeout {
+            builder = builder.timeout(timeout);
+        }
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let mut url_base = base_url.clone();
+        if let Some(socket) = base_url.strip_prefix("unix://") {
+            builder = unix_socket(builder, socket)?;
+            // The host part is ignored when connecting over a socket
+            url_base = "http://localhost".to_string();
+        }
+        // --insecure turns off every check, the pin included (`config pin`
+        // relies on that to replace a pin)
+        match options.pinned_cert.as_ref().filter(|_| !options.insecure) {
+            Some(_) if !base_url.starts_with("https://") => anyhow::bail!(
+                "A certificate is pinned for {} but the connection is not using TLS",
+                base_url
+            ),
+            // Only the pinned certificate counts, whatever the CAs say
+            Some(fingerprint) => builder = builder.use_preconfigured_tls(pinned_tls(fingerprint)?),
+            None => {
+                builder = builder.danger_accept_invalid_certs(options.insecure);
+                if let Some(path) = &options.ca_cert {
+                    let pem = std::fs::read(path).with_context(|| {
+                        format!("Failed to read CA certificate {}", path.display())
+                    })?;
+                    let cert = reqwest::Certificate::from_pem(&pem)
+                        .with_context(|| format!("Invalid CA certificate {}", path.display()))?;
+                    builder = builder.add_root_certificate(cert);
+                }
+            }
+        }
+        let max_connections = options
+            .max_connections
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS)
+            .max(1);
         Ok(Self {
-            http,
+            http: builder.build()?,
             api_key: api_key.to_string(),
-            base_url: base_url.trim_end_matches('/').to_string(),
+            base_url,
+            url_base,
+            timeout: options.timeout,
+            retries: options.retries,
+            cache_dir: options.cache_dir.clone(),
+            cache_ttl: options.cache_ttl,
+            verbosity: options.verbosity,
+            dry_run: options.dry_run,
+            permits: Arc::new(Semaphore::new(max_connections)),
+            max_connections,
         })
     }
 
-    async fn get(&self, endpoint: &str) -> Result<Value> {
-        let url = format!("{}{}", self.base_url, endpoint);
+    /// SHA-256 fingerprint of the certificate the server presents, or `None`
+    /// for plain HTTP. Used to pin a certificate on first use.
+    pub async fn peer_fingerprint(&self) -> Result<Option<String>> {
+        let url = format!("{}/rest/noauth/health", self.url_base);
         let resp = self
             .http
             .get(&url)
-            .header("X-API-Key", &self.api_key)
             .send()
             .await
             .context("Failed to send request")?;
+        Ok(resp
+            .extensions()
+            .get::<reqwest::tls::TlsInfo>()
+            .and_then(|info| info.peer_certificate())
+            .map(cert_fingerprint))
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// How many requests may be in flight at once; also a sensible width
+    /// for commands that fan out.
+    pub fn max_connections(&self) -> usize {
+        self.max_connections
+    }
+
+    /// Whether writes are only printed, not sent.
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Send a request, retrying with exponential backoff when it could not
+    /// connect or, for GETs only, when it failed in flight or got a 5xx.
+    /// Other methods are not repeated once they may have reached Syncthing.
+    async fn execute(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<(StatusCode, String)> {
+        let url = self.url(endpoint, query)?;
+        let idempotent = method == Method::GET;
+        if self.verbosity >= 2
+            && let Some(b) = body
+        {
+            eprintln!("> {}", loggable(&b.to_string()));
+        }
+        let mut attempt = 0;
+        loop {
+            let mut req = self
+                .http
+                .request(method.clone(), url.clone())
+                .header("X-API-Key", &self.api_key);
+            if let Some(b) = body {
+                req = req.json(b);
+            }
+
+            let permit = self.permits.acquire().await?;
+            let start = Instant::now();
+            let result = req.send().await;
+            if self.verbosity >= 1 {
+                let outcome = match &result {
+                    Ok(resp) => resp.status().to_string(),
+                    Err(e) => format!("failed: {:#}", e),
+                };
+                eprintln!(
+                    "{} {} -> {} ({} ms)",
+                    method,
+                    url,
+                    outcome,
+                    start.elapsed().as_millis()
+                );
+            }
+            let retryable = match &result {
+                Ok(resp) => idempotent && resp.status().is_server_error(),
+                Err(e) => idempotent || e.is_connect(),
+            };
+            if !retryable || attempt >= self.retries {
+                let resp = result.context("Failed to send request")?;
+                let status = resp.status();
+                let text = resp.text().await.context("Failed to read response")?;
+                if self.verbosity >= 2 {
+                    eprintln!("< {}", loggable(&text));
+                }
+                return Ok((status, text));
+            }
+            // Let other requests go ahead while this one backs off
+            drop(permit);
+            tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// `url_base` joined with `endpoint`, with `query` percent-encoded.
+    fn url(&self, endpoint: &str, query: &[(&str, &str)]) -> Result<reqwest::Url> {
+        let mut url = reqwest::Url::parse(&format!("{}{}", self.url_base, endpoint))
+            .context("Invalid URL")?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Ok(url)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.get_query(endpoint, &[]).await
+    }
 
-        if !resp.status().is_success() {
-            anyhow::bail!("API error: {}", resp.status());
+    async fn get_query<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T> {
+        let (status, text) = self.execute(Method::GET, endpoint, query, None).await?;
+
+        if !status.is_success() {
+            return Err(ApiError {
+                status,
+                message: String::new(),
+            }
+            .into());
         }
 
-        resp.json().await.context("Failed to parse response")
+        serde_json::from_str(&text).context("Failed to parse response")
     }
 
     async fn post(&self, endpoint: &str, body: Option<&Value>) -> Result<Value> {
-        let url = format!("{}{}", self.base_url, endpoint);
-        let mut req = self.http.post(&url).header("X-API-Key", &self.api_key);
+        self.post_query(endpoint, &[], body).await
+    }
+
+    async fn post_query(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<Value> {
+        self.send(Method::POST, endpoint, query, body).await
+    }
+
+    async fn put(&self, endpoint: &str, body: &Value) -> Result<Value> {
+        self.send(Method::PUT, endpoint, &[], Some(body)).await
+    }
+
+    async fn patch(&self, endpoint: &str, body: &Value) -> Result<Value> {
+        self.send(Method::PATCH, endpoint, &[], Some(body)).await
+    }
+
+    async fn delete(&self, endpoint: &str) -> Result<Value> {
+        self.send(Method::DELETE, endpoint, &[], None).await
+    }
 
-        if let Some(b) = body {
-            req = req.json(b);
+    /// GET `endpoint`, answering from the on-disk cache entry `name` while it
+    /// is younger than the TTL. Cache failures fall back to the API.
+    async fn get_cached<T: DeserializeOwned>(&self, name: &str, endpoint: &str) -> Result<T> {
+        let Some(dir) = &self.cache_dir else {
+            return self.get(endpoint).await;
+        };
+        let path = dir.join(format!("{}.json", name));
+        if let Some(value) = read_fresh(&path, self.cache_ttl) {
+            return Ok(value);
         }
 
-        let resp = req.send().await.context("Failed to send request")?;
+        let value: Value = self.get(endpoint).await?;
+        if create_private_dir(dir).is_ok() {
+            let _ = std::fs::write(&path, value.to_string());
+        }
+        serde_json::from_value(value).context("Failed to parse response")
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        endpoint: &str,
+        query: &[(&str, &str)],
+        body: Option<&Value>,
+    ) -> Result<Value> {
+        if self.dry_run {
+            eprintln!("Would send {} {}", method, self.url(endpoint, query)?);
+            if let Some(b) = body {
+                eprintln!("{}", serde_json::to_string_pretty(b)?);
+            }
+            // An empty object deserializes into the defaults of any response
+            return Ok(Value::Object(Default::default()));
+        }
+        let (status, text) = self.execute(method, endpoint, query, body).await?;
+        if status.is_success()
+            && let Some(dir) = &self.cache_dir
+        {
+            // The write may have changed anything we cached
+            let _ = std::fs::remove_dir_all(dir);
+        }
 
-        if !resp.status().is_success() {
-            anyhow::bail!("API error: {}", resp.status());
+        if !status.is_success() {
+            // Syncthing explains rejected writes (e.g. config validation) in the body
+            return Err(ApiError {
+                status,
+                message: text.trim().to_string(),
+            }
+            .into());
         }
 
-        // Some POST endpoints return empty response
-        let text = resp.text().await?;
+        // Some endpoints return an empty response
         if text.is_empty() {
             Ok(Value::Null)
         } else {
@@ -62,32 +480,91 @@ impl Client {
     }
 
     // System endpoints
-    pub async fn status(&self) -> Result<Value> {
+    pub async fn status(&self) -> Result<SystemStatus> {
         self.get("/rest/system/status").await
     }
 
-    pub async fn version(&self) -> Result<Value> {
+    pub async fn version(&self) -> Result<Version> {
         self.get("/rest/system/version").await
     }
 
-    pub async fn connections(&self) -> Result<Value> {
+    pub async fn ping(&self) -> Result<Ping> {
+        self.get("/rest/system/ping").await
+    }
+
+    pub async fn connections(&self) -> Result<Connections> {
         self.get("/rest/system/connections").await
     }
 
-    pub async fn errors(&self) -> Result<Value> {
-        self.get("/rest/system/error").await
+    /// Addresses each device was found at by local and global discovery.
+    pub async fn discovery(&self) -> Result<HashMap<String, DiscoveryEntry>> {
+        self.get("/rest/system/discovery").await
+    }
+
+    pub async fn debug(&self) -> Result<DebugFacilities> {
+        self.get("/rest/system/debug").await
+    }
+
+    /// Turn debug logging on for `enable` and off for `disable`.
+    pub async fn set_debug(&self, enable: &[String], disable: &[String]) -> Result<()> {
+        let (enable, disable) = (enable.join(","), disable.join(","));
+        let mut query = Vec::new();
+        if !enable.is_empty() {
+            query.push(("enable", enable.as_str()));
+        }
+        if !disable.is_empty() {
+            query.push(("disable", disable.as_str()));
+        }
+        self.post_query("/rest/system/debug", &query, None).await?;
+        Ok(())
+    }
+
+    pub async fn errors(&self) -> Result<Vec<LogEntry>> {
+        let list: LogList = self.get("/rest/system/error").await?;
+        Ok(list.errors.unwrap_or_default())
+    }
+
+    /// Have Syncthing validate a device ID and return its canonical form.
+    pub async fn check_device_id(&self, id: &str) -> Result<String> {
+        let check: DeviceIdCheck = self.get_query("/rest/svc/deviceid", &[("id", id)]).await?;
+        match (check.id, check.error) {
+            (Some(id), None) => Ok(id),
+            (_, error) => anyhow::bail!(
+                "Invalid device ID: {}",
+                error.unwrap_or_else(|| "rejected by Syncthing".to_string())
+            ),
+        }
+    }
+
+    /// Where the config, database, log file and certificates live, by name
+    /// (e.g. `config`, `database`, `logFile`, `certFile`).
+    pub async fn paths(&self) -> Result<BTreeMap<String, String>> {
+        self.get("/rest/system/paths").await
     }
 
-    pub async fn clear_errors(&self) -> Result<Value> {
-        self.post("/rest/system/error/clear", None).await
+    /// Recent log lines, or only those logged after the RFC 3339 time `since`.
+    pub async fn log(&self, since: Option<&str>) -> Result<Vec<LogEntry>> {
+        let query: &[(&str, &str)] = match since {
+            Some(since) => &[("since", since)],
+            None => &[],
+        };
+        let list: LogList = self.get_query("/rest/system/log", query).await?;
+        Ok(list.messages.unwrap_or_default())
     }
 
-    pub async fn restart(&self) -> Result<Value> {
-        self.post("/rest/system/restart", None).await
+    pub async fn clear_errors(&self) -> Result<()> {
+        self.post("/rest/system/error/clear", None).await?;
+        Ok(())
     }
 
-    pub async fn shutdown(&self) -> Result<Value> {
-        self.post("/rest/system/shutdown", None).await
+    pub async fn restart(&self) -> Result<()> {
+        self.post("/rest/system/restart", None).await?;
+        Ok(())
+    }
+
+    pub async fn shutdown(&self) -> Result<()> {
+        self.post("/rest/system/shutdown", None).await?;
+        Ok(())
     }
 
     // Config endpoints
@@ -95,85 +572,454 @@ impl Client {
         self.get("/rest/config").await
     }
 
-    pub async fn config_folders(&self) -> Result<Value> {
+    /// Merge a fragment into the full configuration.
+    pub async fn patch_config(&self, patch: &Value) -> Result<()> {
+        self.patch("/rest/config", patch).await?;
+        Ok(())
+    }
+
+    /// Whether saved config changes only take effect after a restart.
+    pub async fn restart_required(&self) -> Result<bool> {
+        let result: RestartRequired = self.get("/rest/config/restart-required").await?;
+        Ok(result.requires_restart)
+    }
+
+    pub async fn gui(&self) -> Result<GuiConfig> {
+        self.get("/rest/config/gui").await
+    }
+
+    pub async fn patch_gui(&self, patch: &Value) -> Result<()> {
+        self.patch("/rest/config/gui", patch).await?;
+        Ok(())
+    }
+
+    pub async fn ldap(&self) -> Result<LdapConfig> {
+        self.get("/rest/config/ldap").await
+    }
+
+    pub async fn patch_ldap(&self, patch: &Value) -> Result<()> {
+        self.patch("/rest/config/ldap", patch).await?;
+        Ok(())
+    }
+
+    pub async fn options(&self) -> Result<Options> {
+        self.get("/rest/config/options").await
+    }
+
+    pub async fn patch_options(&self, patch: &Value) -> Result<()> {
+        self.patch("/rest/config/options", patch).await?;
+        Ok(())
+    }
+
+    /// The template new folders are created from.
+    pub async fn defaults_folder(&self) -> Result<Value> {
+        self.get("/rest/config/defaults/folder").await
+    }
+
+    pub async fn patch_defaults_folder(&self, patch: &Value) -> Result<()> {
+        self.patch("/rest/config/defaults/folder", patch).await?;
+        Ok(())
+    }
+
+    /// The template new devices are created from.
+    pub async fn defaults_device(&self) -> Result<Value> {
+        self.get("/rest/config/defaults/device").await
+    }
+
+    pub async fn patch_defaults_device(&self, patch: &Value) -> Result<()> {
+        self.patch("/rest/config/defaults/device", patch).await?;
+        Ok(())
+    }
+
+    pub async fn config_folders(&self) -> Result<Vec<FolderConfig>> {
+        self.get_cached("folders", "/rest/config/folders").await
+    }
+
+    pub async fn config_devices(&self) -> Result<Vec<DeviceConfig>> {
+        self.get_cached("devices", "/rest/config/devices").await
+    }
+
+    /// `config_folders` straight from Syncthing, never from the cache. For
+    /// changes that write back a list built from the current one (such as a
+    /// folder's `devices`), which must not start from a stale copy.
+    pub async fn config_folders_fresh(&self) -> Result<Vec<FolderConfig>> {
         self.get("/rest/config/folders").await
     }
 
-    pub async fn config_devices(&self) -> Result<Value> {
-        self.get("/rest/config/devices").await
+    /// Add a folder. Fields missing from `folder` are taken from the
+    /// configured folder defaults.
+    pub async fn add_folder(&self, folder: &Value) -> Result<()> {
+        self.post("/rest/config/folders", Some(folder)).await?;
+        Ok(())
+    }
+
+    /// One folder's config straight from Syncthing, including the fields
+    /// `FolderConfig` doesn't model.
+    pub async fn folder_config(&self, id: &str) -> Result<Value> {
+        self.get(&format!("/rest/config/folders/{}", encode(id)))
+            .await
+    }
+
+    /// Replace a folder's whole config. Unlike a PATCH, which merges maps
+    /// such as `versioning.params` into the old ones, keys left out are gone.
+    pub async fn replace_folder(&self, id: &str, folder: &Value) -> Result<()> {
+        self.put(&format!("/rest/config/folders/{}", encode(id)), folder)
+            .await?;
+        Ok(())
+    }
+
+    /// Apply a partial update to a folder's config.
+    pub async fn patch_folder(&self, id: &str, patch: &Value) -> Result<()> {
+        self.patch(&format!("/rest/config/folders/{}", encode(id)), patch)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_folder(&self, id: &str) -> Result<()> {
+        self.delete(&format!("/rest/config/folders/{}", encode(id)))
+            .await?;
+        Ok(())
+    }
+
+    /// Add a device. Fields missing from `device` are taken from the
+    /// configured device defaults.
+    pub async fn add_device(&self, device: &Value) -> Result<()> {
+        self.post("/rest/config/devices", Some(device)).await?;
+        Ok(())
+    }
+
+    /// Apply a partial update to a device's config.
+    pub async fn patch_device(&self, id: &str, patch: &Value) -> Result<()> {
+        self.patch(&format!("/rest/config/devices/{}", encode(id)), patch)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_device(&self, id: &str) -> Result<()> {
+        self.delete(&format!("/rest/config/devices/{}", encode(id)))
+            .await?;
+        Ok(())
     }
 
     // Database endpoints
-    pub async fn db_status(&self, folder: &str) -> Result<Value> {
-        self.get(&format!("/rest/db/status?folder={}", folder))
+    pub async fn db_status(&self, folder: &str) -> Result<DbStatus> {
+        self.get_query("/rest/db/status", &[("folder", folder)])
             .await
     }
 
-    pub async fn db_completion(&self) -> Result<Value> {
+    pub async fn db_completion(&self) -> Result<Completion> {
         self.get("/rest/db/completion").await
     }
 
-    pub async fn db_need(&self, folder: &str) -> Result<Value> {
-        self.get(&format!("/rest/db/need?folder={}", folder)).await
+    /// How far `device` is with `folder`, as this node knows it.
+    pub async fn db_completion_for(&self, folder: &str, device: &str) -> Result<Completion> {
+        self.get_query(
+            "/rest/db/completion",
+            &[("folder", folder), ("device", device)],
+        )
+        .await
+    }
+
+    /// The first page of the files `folder` still needs, with Syncthing's
+    /// default page size. Use `db_need_all` for the complete list.
+    pub async fn db_need(&self, folder: &str) -> Result<Need> {
+        self.get_query("/rest/db/need", &[("folder", folder)]).await
+    }
+
+    /// Page `page` (from 1) of the files `folder` needs, `perpage` files per
+    /// page across the in-progress, queued and remaining lists.
+    pub async fn db_need_page(&self, folder: &str, page: u64, perpage: u64) -> Result<Need> {
+        let (page, perpage) = (page.to_string(), perpage.to_string());
+        self.get_query(
+            "/rest/db/need",
+            &[("folder", folder), ("page", &page), ("perpage", &perpage)],
+        )
+        .await
+    }
+
+    /// Every file `folder` needs, fetched page by page until a page comes
+    /// back short.
+    pub async fn db_need_all(&self, folder: &str) -> Result<Need> {
+        self.db_need_paged(folder, NEED_PAGE_SIZE).await
+    }
+
+    async fn db_need_paged(&self, folder: &str, perpage: u64) -> Result<Need> {
+        let mut all = Need::default();
+        for page in 1.. {
+            let need = self.db_need_page(folder, page, perpage).await?;
+            let count = need.files().count() as u64;
+            all.progress.extend(need.progress);
+            all.queued.extend(need.queued);
+            all.rest.extend(need.rest);
+            if count < perpage {
+                break;
+            }
+        }
+        all.page = 1;
+        all.perpage = all.files().count() as u64;
+        Ok(all)
+    }
+
+    /// List the global tree of a folder below `prefix`, `levels` deep
+    /// (unlimited when `None`).
+    pub async fn db_browse(
+        &self,
+        folder: &str,
+        prefix: Option<&str>,
+        levels: Option<u32>,
+    ) -> Result<Vec<BrowseEntry>> {
+        let levels = levels.map(|l| l.to_string());
+        let mut query = vec![("folder", folder)];
+        if let Some(p) = prefix {
+            query.push(("prefix", p));
+        }
+        if let Some(l) = &levels {
+            query.push(("levels", l));
+        }
+        self.get_query("/rest/db/browse", &query).await
+    }
+
+    pub async fn db_file(&self, folder: &str, file: &str) -> Result<DbFile> {
+        self.get_query("/rest/db/file", &[("folder", folder), ("file", file)])
+            .await
+    }
+
+    /// Files changed locally in a receive-only folder.
+    pub async fn db_local_changed(&self, folder: &str) -> Result<LocalChanged> {
+        self.get_query("/rest/db/localchanged", &[("folder", folder)])
+            .await
+    }
+
+    /// Discard local changes in a receive-only folder.
+    pub async fn db_revert(&self, folder: &str) -> Result<()> {
+        self.post_query("/rest/db/revert", &[("folder", folder)], None)
+            .await?;
+        Ok(())
+    }
+
+    /// Make a send-only folder's local state the global one, overwriting
+    /// remote changes.
+    pub async fn db_override(&self, folder: &str) -> Result<()> {
+        self.post_query("/rest/db/override", &[("folder", folder)], None)
+            .await?;
+        Ok(())
     }
 
-    pub async fn db_scan(&self, folder: &str) -> Result<Value> {
-        self.post(&format!("/rest/db/scan?folder={}", folder), None)
+    pub async fn db_ignores(&self, folder: &str) -> Result<Ignores> {
+        self.get_query("/rest/db/ignores", &[("folder", folder)])
             .await
     }
 
-    pub async fn db_scan_all(&self) -> Result<Value> {
-        self.post("/rest/db/scan", None).await
+    /// Replace the folder's `.stignore` with `patterns`.
+    pub async fn set_db_ignores(&self, folder: &str, patterns: &[String]) -> Result<()> {
+        let body = serde_json::json!({ "ignore": patterns });
+        self.post_query("/rest/db/ignores", &[("folder", folder)], Some(&body))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn db_scan(&self, folder: &str) -> Result<()> {
+        self.db_scan_with(folder, &[], None).await
+    }
+
+    /// Rescan only the paths `subs` inside `folder` (all of it when empty)
+    /// and, with `next`, postpone the folder's next regular scan by that many
+    /// seconds.
+    pub async fn db_scan_with(
+        &self,
+        folder: &str,
+        subs: &[String],
+        next: Option<u64>,
+    ) -> Result<()> {
+        let next = next.map(|n| n.to_string());
+        let mut query = vec![("folder", folder)];
+        for sub in subs {
+            query.push(("sub", sub));
+        }
+        if let Some(next) = &next {
+            query.push(("next", next));
+        }
+        self.post_query("/rest/db/scan", &query, None).await?;
+        Ok(())
+    }
+
+    /// Move `file` to the front of `folder`'s download queue. Returns the
+    /// first page of the queue afterwards.
+    pub async fn db_prio(&self, folder: &str, file: &str) -> Result<Need> {
+        let need = self
+            .post_query("/rest/db/prio", &[("folder", folder), ("file", file)], None)
+            .await?;
+        serde_json::from_value(need).context("Failed to parse response")
+    }
+
+    pub async fn db_scan_all(&self) -> Result<()> {
+        self.post("/rest/db/scan", None).await?;
+        Ok(())
     }
 
     // Stats endpoints
-    pub async fn stats_device(&self) -> Result<Value> {
+    pub async fn stats_device(&self) -> Result<HashMap<String, DeviceStats>> {
         self.get("/rest/stats/device").await
     }
 
-    pub async fn stats_folder(&self) -> Result<Value> {
+    pub async fn stats_folder(&self) -> Result<HashMap<String, FolderStats>> {
         self.get("/rest/stats/folder").await
     }
 
     // Cluster endpoints
-    pub async fn pending_devices(&self) -> Result<Value> {
+    pub async fn pending_devices(&self) -> Result<HashMap<String, PendingDevice>> {
         self.get("/rest/cluster/pending/devices").await
     }
 
-    pub async fn pending_folders(&self) -> Result<Value> {
+    pub async fn pending_folders(&self) -> Result<HashMap<String, PendingFolder>> {
         self.get("/rest/cluster/pending/folders").await
     }
 
     // Folder endpoints
-    pub async fn folder_errors(&self, folder: &str) -> Result<Value> {
-        self.get(&format!("/rest/folder/errors?folder={}", folder))
+    pub async fn folder_errors(&self, folder: &str) -> Result<FolderErrors> {
+        self.get_query("/rest/folder/errors", &[("folder", folder)])
+            .await
+    }
+
+    /// Archived file versions, keyed by path.
+    pub async fn folder_versions(&self, folder: &str) -> Result<HashMap<String, Vec<FileVersion>>> {
+        self.get_query("/rest/folder/versions", &[("folder", folder)])
             .await
     }
 
+    /// Restore archived versions, given as path -> version time. Returns
+    /// per-path errors; an empty map means every restore succeeded.
+    pub async fn restore_versions(
+        &self,
+        folder: &str,
+        versions: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>> {
+        let body = serde_json::to_value(versions)?;
+        let result = self
+            .post_query("/rest/folder/versions", &[("folder", folder)], Some(&body))
+            .await?;
+        if result.is_null() {
+            return Ok(HashMap::new());
+        }
+        Ok(serde_json::from_value(result)?)
+    }
+
     // Events
-    pub async fn events(&self, since: Option<u64>, limit: Option<u32>) -> Result<Value> {
-        let mut url = "/rest/events".to_string();
-        let mut params = Vec::new();
-        if let Some(s) = since {
-            params.push(format!("since={}", s));
+    /// Fetch events after `since`, optionally only of the given `types`
+    /// (e.g. `ItemFinished`). Blocks until at least one event is available
+    /// or the server-side timeout expires.
+    pub async fn events(
+        &self,
+        since: Option<u64>,
+        limit: Option<u32>,
+        types: &[String],
+    ) -> Result<Vec<Event>> {
+        let since = since.map(|s| s.to_string());
+        let limit = limit.map(|l| l.to_string());
+        let types = types.join(",");
+        // Have the long poll return before the request would time out
+        let timeout = self
+            .timeout
+            .map(|t| t.as_secs().saturating_sub(1).max(1).to_string());
+        let mut query = Vec::new();
+        if let Some(s) = &since {
+            query.push(("since", s.as_str()));
         }
-        if let Some(l) = limit {
-            params.push(format!("limit={}", l));
+        if let Some(l) = &limit {
+            query.push(("limit", l.as_str()));
+        }
+        if !types.is_empty() {
+            query.push(("events", types.as_str()));
+        }
+        if let Some(t) = &timeout {
+            query.push(("timeout", t.as_str()));
+        }
+        self.get_query("/rest/events", &query).await
+    }
+}
+
+/// Replace every non-empty secret value with a placeholder.
+pub fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if SECRET_KEYS.contains(&key.as_str())
+                    && value.as_str().is_some_and(|s| !s.is_empty())
+                {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// A request or response body as `-vv` prints it, with secrets redacted
+/// (e.g. the API key and password hash in `/rest/config/gui`).
+fn loggable(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value.to_string()
         }
-        if !params.is_empty() {
-            url.push('?');
-            url.push_str(&params.join("&"));
+        Err(_) => body.trim_end().to_string(),
+    }
+}
+
+/// Percent-encode an ID for use as a path segment (folder IDs may contain
+/// spaces, `/`, `#`, ...). Query values go through `url` instead.
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
         }
-        self.get(&url).await
     }
+    encoded
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{header, method, path};
+    use wiremock::matchers::{body_json, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
+    #[test]
+    fn test_redact() {
+        let mut cfg = serde_json::json!({
+            "gui": {"apiKey": "secret", "password": "", "user": "admin"},
+            "folders": [{"devices": [{"deviceID": "A", "encryptionPassword": "pw"}]}]
+        });
+        redact(&mut cfg);
+        assert_eq!(cfg["gui"]["apiKey"], REDACTED);
+        assert_eq!(cfg["gui"]["password"], "");
+        assert_eq!(cfg["gui"]["user"], "admin");
+        assert_eq!(
+            cfg["folders"][0]["devices"][0]["encryptionPassword"],
+            REDACTED
+        );
+    }
+
+    #[test]
+    fn test_loggable_redacts_secrets() {
+        let gui = r#"{"address":"127.0.0.1:8384","apiKey":"s3cr3t","user":"admin"}"#;
+        let logged = loggable(gui);
+        assert!(!logged.contains("s3cr3t"));
+        assert!(logged.contains("admin"));
+        assert_eq!(loggable("not json\n"), "not json");
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("a b&c/ü"), "a%20b%26c%2F%C3%BC");
+    }
+
     #[tokio::test]
     async fn test_status() {
         let mock_server = MockServer::start().await;
@@ -192,8 +1038,8 @@ mod tests {
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
         let result = client.status().await.unwrap();
 
-        assert_eq!(result["uptime"], 3600);
-        assert_eq!(result["alloc"], 12345678);
+        assert_eq!(result.uptime, 3600);
+        assert_eq!(result.alloc, 12345678);
     }
 
     #[tokio::test]
@@ -212,7 +1058,26 @@ mod tests {
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
         let result = client.version().await.unwrap();
 
-        assert_eq!(result["version"], "v1.23.0");
+        assert_eq!(result.version, "v1.23.0");
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/system/ping"))
+            .and(header("X-API-Key", "test-key"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"ping": "pong"})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.ping().await.unwrap();
+
+        assert_eq!(result.ping, "pong");
     }
 
     #[tokio::test]
@@ -231,9 +1096,9 @@ mod tests {
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
         let result = client.config_folders().await.unwrap();
 
-        let folders = result.as_array().unwrap();
-        assert_eq!(folders.len(), 2);
-        assert_eq!(folders[0]["label"], "Documents");
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].label, "Documents");
+        assert!(result[1].paused);
     }
 
     #[tokio::test]
@@ -252,9 +1117,9 @@ mod tests {
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
         let result = client.config_devices().await.unwrap();
 
-        let devices = result.as_array().unwrap();
-        assert_eq!(devices.len(), 2);
-        assert_eq!(devices[0]["name"], "Laptop");
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].name, "Laptop");
+        assert_eq!(result[1].device_id, "DEF456");
     }
 
     #[tokio::test]
@@ -274,8 +1139,8 @@ mod tests {
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
         let result = client.db_completion().await.unwrap();
 
-        assert_eq!(result["completion"], 100.0);
-        assert_eq!(result["needBytes"], 0);
+        assert_eq!(result.completion, 100.0);
+        assert_eq!(result.need_bytes, 0);
     }
 
     #[tokio::test]
@@ -295,9 +1160,26 @@ mod tests {
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
         let result = client.errors().await.unwrap();
 
-        let errors = result["errors"].as_array().unwrap();
-        assert_eq!(errors.len(), 1);
-        assert_eq!(errors[0]["message"], "Test error");
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].message, "Test error");
+    }
+
+    #[tokio::test]
+    async fn test_errors_null() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/system/error"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"errors": null})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.errors().await.unwrap();
+
+        assert!(result.is_empty());
     }
 
     #[tokio::test]
@@ -311,26 +1193,750 @@ mod tests {
             .await;
 
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
-        let result = client.db_scan_all().await.unwrap();
+        let result = client.db_scan_all().await;
 
-        assert_eq!(result, Value::Null);
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
-    async fn test_api_error() {
+    async fn test_patch_folder() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/folders/docs"))
+            .and(body_json(serde_json::json!({"paused": true})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client
+            .patch_folder("docs", &serde_json::json!({"paused": true}))
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_add_folder() {
         let mock_server = MockServer::start().await;
+        let folder = serde_json::json!({"id": "docs", "path": "/data/docs"});
 
+        Mock::given(method("POST"))
+            .and(path("/rest/config/folders"))
+            .and(body_json(folder.clone()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        assert!(client.add_folder(&folder).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_patch_device() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/devices/ABC123"))
+            .and(body_json(serde_json::json!({"name": "nas"})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client
+            .patch_device("ABC123", &serde_json::json!({"name": "nas"}))
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_max_connections() {
+        let mock_server = MockServer::start().await;
         Mock::given(method("GET"))
-            .and(path("/rest/system/status"))
-            .respond_with(ResponseTemplate::new(401))
+            .and(path("/rest/system/ping"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"ping": "pong"}))
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .expect(4)
             .mount(&mock_server)
             .await;
 
-        let client = Client::new("bad-key", &mock_server.uri()).unwrap();
-        let result = client.status().await;
+        let options = ClientOptions {
+            max_connections: Some(2),
+            ..Default::default()
+        };
+        let client = Client::with_options("test-key", &mock_server.uri(), &options).unwrap();
+        let start = Instant::now();
+        let pings = (0..4).map(|_| {
+            let client = client.clone();
+            async move { client.ping().await }
+        });
+        for ping in futures::future::join_all(pings).await {
+            ping.unwrap();
+        }
+        // Two rounds of two
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("401"));
+    #[tokio::test]
+    async fn test_db_need_all_pages() {
+        let mock_server = MockServer::start().await;
+        let file = |name: &str| serde_json::json!({"name": name, "size": 1});
+        let pages = [
+            serde_json::json!({"progress": [file("a")], "queued": [file("b")], "rest": []}),
+            serde_json::json!({"progress": [], "queued": [], "rest": [file("c"), file("d")]}),
+            serde_json::json!({"progress": [], "queued": [], "rest": [file("e")]}),
+        ];
+        for (i, body) in pages.into_iter().enumerate() {
+            Mock::given(method("GET"))
+                .and(path("/rest/db/need"))
+                .and(query_param("page", (i + 1).to_string()))
+                .and(query_param("perpage", "2"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(body))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let need = client.db_need_paged("docs", 2).await.unwrap();
+        let names: Vec<&str> = need.files().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c", "d", "e"]);
+        assert_eq!(need.queued.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_sends_no_writes() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let options = ClientOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let client = Client::with_options("test-key", &mock_server.uri(), &options).unwrap();
+        client.remove_folder("docs").await.unwrap();
+        let need = client.db_prio("docs", "a.txt").await.unwrap();
+        assert!(need.queued.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_options() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/config/options"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "maxSendKbps": 1000,
+                "maxRecvKbps": 0,
+                "relaysEnabled": true
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.options().await.unwrap();
+
+        assert_eq!(result.max_send_kbps, 1000);
+        assert_eq!(result.max_recv_kbps, 0);
+        assert!(result.relays_enabled);
+    }
+
+    #[tokio::test]
+    async fn test_restart_required() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/config/restart-required"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"requiresRestart": true})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        assert!(client.restart_required().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_discovery() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/system/discovery"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "DEVICE-A": {"addresses": ["tcp://192.168.1.5:22000", "quic://192.168.1.5:22000"]}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let discovery = client.discovery().await.unwrap();
+        assert_eq!(discovery["DEVICE-A"].addresses.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_set_debug() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/rest/system/debug"))
+            .and(query_param("enable", "model,connections"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        client
+            .set_debug(&["model".to_string(), "connections".to_string()], &[])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_log_since() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/system/log"))
+            .and(query_param("since", "2024-01-01T10:00:00+01:00"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "messages": [{"when": "2024-01-01T10:00:01+01:00", "message": "Ready", "level": 2}]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let log = client.log(Some("2024-01-01T10:00:00+01:00")).await.unwrap();
+        assert_eq!(log[0].message, "Ready");
+    }
+
+    #[tokio::test]
+    async fn test_check_device_id() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/svc/deviceid"))
+            .and(query_param("id", "bogus"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(
+                    serde_json::json!({"error": "device ID invalid: incorrect length"}),
+                ),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let err = client.check_device_id("bogus").await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid device ID: device ID invalid: incorrect length"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_db_scan_with_subdirs() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/rest/db/scan"))
+            .and(query_param("folder", "docs"))
+            .and(query_param("sub", "photos/2024"))
+            .and(query_param("next", "300"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        client
+            .db_scan_with("docs", &["photos/2024".to_string()], Some(300))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_gui() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/config/gui"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "enabled": true,
+                "address": "127.0.0.1:8384",
+                "user": "admin",
+                "useTLS": true
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.gui().await.unwrap();
+
+        assert_eq!(result.address, "127.0.0.1:8384");
+        assert_eq!(result.user, "admin");
+        assert!(result.use_tls);
+    }
+
+    #[tokio::test]
+    async fn test_patch_options() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/options"))
+            .and(body_json(serde_json::json!({"maxRecvKbps": 5000})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client
+            .patch_options(&serde_json::json!({"maxRecvKbps": 5000}))
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_remove_folder() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/rest/config/folders/docs"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        assert!(client.remove_folder("docs").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_folder_id_is_encoded() {
+        let mock_server = MockServer::start().await;
+        let id = "a&b #c+d/e";
+
+        Mock::given(method("GET"))
+            .and(path("/rest/db/status"))
+            .and(query_param("folder", id))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/rest/db/revert"))
+            .and(query_param("folder", id))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/rest/config/folders/a%26b%20%23c%2Bd%2Fe"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        client.db_status(id).await.unwrap();
+        client.db_revert(id).await.unwrap();
+        client.remove_folder(id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_db_ignores() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/db/ignores"))
+            .and(query_param("folder", "docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "ignore": ["*.tmp", "#include more"],
+                "expanded": ["*.tmp", "build"],
+                "error": null
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/rest/db/ignores"))
+            .and(query_param("folder", "docs"))
+            .and(body_json(serde_json::json!({"ignore": ["*.bak"]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let ignores = client.db_ignores("docs").await.unwrap();
+        assert_eq!(ignores.ignore.unwrap(), vec!["*.tmp", "#include more"]);
+        assert_eq!(ignores.expanded.unwrap().len(), 2);
+
+        let result = client.set_db_ignores("docs", &["*.bak".to_string()]).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_db_browse() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/db/browse"))
+            .and(query_param("folder", "docs"))
+            .and(query_param("levels", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {
+                    "name": "notes",
+                    "type": "FILE_INFO_TYPE_DIRECTORY",
+                    "children": [
+                        {"name": "todo.txt", "type": "FILE_INFO_TYPE_FILE", "size": 12}
+                    ]
+                },
+                {"name": "readme.md", "type": "FILE_INFO_TYPE_FILE", "size": 100}
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.db_browse("docs", None, Some(1)).await.unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert!(result[0].is_dir());
+        assert_eq!(result[0].children[0].size, 12);
+    }
+
+    #[tokio::test]
+    async fn test_db_file() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/db/file"))
+            .and(query_param("folder", "docs"))
+            .and(query_param("file", "my notes/a.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "availability": [{"id": "DEV1", "fromTemporary": false}],
+                "global": {"name": "my notes/a.txt", "size": 10, "version": ["ABCDEFG:3"]},
+                "local": {"name": "my notes/a.txt", "size": 8, "version": ["ABCDEFG:2"]}
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.db_file("docs", "my notes/a.txt").await.unwrap();
+
+        assert_eq!(result.global.size, 10);
+        assert_eq!(result.local.version, vec!["ABCDEFG:2"]);
+        assert_eq!(result.availability.unwrap()[0].id, "DEV1");
+    }
+
+    #[tokio::test]
+    async fn test_db_local_changed() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/db/localchanged"))
+            .and(query_param("folder", "photos"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "files": [{"name": "edited.jpg", "size": 2048, "modified": "2024-01-01T00:00:00Z"}],
+                "page": 1,
+                "perpage": 100
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.db_local_changed("photos").await.unwrap();
+
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.files[0].name, "edited.jpg");
+    }
+
+    #[tokio::test]
+    async fn test_folder_versions() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/folder/versions"))
+            .and(query_param("folder", "docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "report.pdf": [
+                    {"versionTime": "2024-01-02T00:00:00Z", "modTime": "2024-01-01T00:00:00Z", "size": 1234}
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let result = client.folder_versions("docs").await.unwrap();
+
+        assert_eq!(result["report.pdf"][0].size, 1234);
+    }
+
+    #[tokio::test]
+    async fn test_restore_versions() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/rest/folder/versions"))
+            .and(query_param("folder", "docs"))
+            .and(body_json(
+                serde_json::json!({"report.pdf": "2024-01-02T00:00:00Z"}),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let versions =
+            HashMap::from([("report.pdf".to_string(), "2024-01-02T00:00:00Z".to_string())]);
+        let errors = client.restore_versions("docs", &versions).await.unwrap();
+
+        assert!(errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_events_with_types() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/events"))
+            .and(query_param("since", "5"))
+            .and(query_param("events", "ItemFinished,FolderSummary"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"id": 6, "globalID": 60, "type": "ItemFinished", "time": "2024-01-01T00:00:00Z", "data": {"item": "a.txt"}}
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let types = vec!["ItemFinished".to_string(), "FolderSummary".to_string()];
+        let result = client.events(Some(5), None, &types).await.unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].event_type, "ItemFinished");
+        assert_eq!(result[0].data["item"], "a.txt");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("api.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let n = stream.read(&mut buf).await.unwrap();
+            assert!(String::from_utf8_lossy(&buf[..n]).starts_with("GET /rest/system/ping "));
+            let body = r#"{"ping":"pong"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let host = format!("unix://{}", socket.display());
+        let client = Client::new("test-key", &host).unwrap();
+
+        assert_eq!(client.base_url(), host);
+        assert_eq!(client.ping().await.unwrap().ping, "pong");
+    }
+
+    #[test]
+    fn test_cert_fingerprint() {
+        assert_eq!(
+            cert_fingerprint(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_pinned_cert_requires_tls() {
+        let options = ClientOptions {
+            pinned_cert: Some("00".to_string()),
+            ..Default::default()
+        };
+        let err = Client::with_options("test-key", "http://localhost:8384", &options)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("not using TLS"));
+
+        // --insecure drops the pin, so `config pin` can replace it
+        let insecure = ClientOptions {
+            insecure: true,
+            ..options
+        };
+        assert!(Client::with_options("test-key", "http://localhost:8384", &insecure).is_ok());
+    }
+
+    #[test]
+    fn test_pinned_cert_verifier() {
+        let verifier = PinnedCertVerifier {
+            fingerprint: cert_fingerprint(b"abc").to_uppercase(),
+            algorithms: rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        };
+        let name = ServerName::try_from("localhost").unwrap();
+        let verify = |der: &[u8]| {
+            verifier.verify_server_cert(
+                &CertificateDer::from(der.to_vec()),
+                &[],
+                &name,
+                &[],
+                UnixTime::now(),
+            )
+        };
+        assert!(verify(b"abc").is_ok());
+        let err = verify(b"abd").unwrap_err();
+        assert!(err.to_string().contains("pinned fingerprint"));
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/system/ping"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/system/ping"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"ping": "pong"})),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let options = ClientOptions {
+            retries: 1,
+            ..Default::default()
+        };
+        let client = Client::with_options("test-key", &mock_server.uri(), &options).unwrap();
+
+        assert_eq!(client.ping().await.unwrap().ping, "pong");
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_posts() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/rest/system/restart"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let options = ClientOptions {
+            retries: 3,
+            ..Default::default()
+        };
+        let client = Client::with_options("test-key", &mock_server.uri(), &options).unwrap();
+
+        assert!(client.restart().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cache_until_write() {
+        let mock_server = MockServer::start().await;
+        let cache = tempfile::tempdir().unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!([{"id": "docs", "label": "Docs"}])),
+            )
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/db/scan"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let options = ClientOptions {
+            cache_dir: Some(cache.path().join("nas")),
+            cache_ttl: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let client = Client::with_options("test-key", &mock_server.uri(), &options).unwrap();
+
+        // The second call is served from the cache, the third follows a write
+        assert_eq!(client.config_folders().await.unwrap()[0].id, "docs");
+        assert_eq!(client.config_folders().await.unwrap()[0].id, "docs");
+        client.db_scan("docs").await.unwrap();
+        assert_eq!(client.config_folders().await.unwrap()[0].id, "docs");
+    }
+
+    #[tokio::test]
+    async fn test_api_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/rest/system/status"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("bad-key", &mock_server.uri()).unwrap();
+        let result = client.status().await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("401"));
+    }
+
+    #[tokio::test]
+    async fn test_patch_config_error_message() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("invalid listen address\n"))
+            .mount(&mock_server)
+            .await;
+
+        let client = Client::new("test-key", &mock_server.uri()).unwrap();
+        let err = client
+            .patch_config(&serde_json::json!({"options": {"listenAddresses": ["bogus"]}}))
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "API error: 400 Bad Request: invalid listen address"
+        );
     }
 
     #[tokio::test]
@@ -346,6 +1952,6 @@ mod tests {
         let client = Client::new("test-key", &mock_server.uri()).unwrap();
         let result = client.pending_devices().await.unwrap();
 
-        assert!(result.as_object().unwrap().is_empty());
+        assert!(result.is_empty());
     }
 }
@@ -0,0 +1,604 @@
+//! `apply -f cluster.yaml`: make the live config match a declarative
+//! manifest of devices, folders and options. Only what the manifest sets is
+//! changed; with `--prune`, folders and devices missing from a section the
+//! manifest has are removed as well.
+//!
+//! ```yaml
+//! devices:
+//!   - deviceID: MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD
+//!     name: nas
+//! folders:
+//!   - id: docs
+//!     label: Documents
+//!     path: ~/Documents
+//!     devices: [nas]
+//!     versioning: {type: simple}
+//! options:
+//!   relaysEnabled: false
+//! ```
+
+use crate::output::{ApplyReport, FieldChange, Output, PlannedChange, short_id};
+use crate::prompt::confirm_or_abort;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use syncthing::api::Client;
+use syncthing::device_id;
+use syncthing::models::{DeviceConfig, find_device};
+
+/// A manifest file. It is YAML, so JSON works as well.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// Entries in `/rest/config/devices` form; `deviceID` is required.
+    devices: Option<Vec<Map<String, Value>>>,
+    /// Entries in `/rest/config/folders` form; `id` is required, and
+    /// `devices` may list device names or IDs.
+    folders: Option<Vec<Map<String, Value>>>,
+    /// Keys of `/rest/config/options`.
+    options: Map<String, Value>,
+}
+
+impl Manifest {
+    /// Read a manifest from `path`, or stdin for `-`.
+    pub fn load(path: &str) -> Result<Self> {
+        let text = if path == "-" {
+            std::io::read_to_string(std::io::stdin()).context("Failed to read stdin")?
+        } else {
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?
+        };
+        serde_yaml::from_str(&text).with_context(|| format!("Invalid manifest {}", path))
+    }
+}
+
+/// The config request that carries out a planned change.
+#[derive(Debug, PartialEq)]
+enum Request {
+    AddDevice(Value),
+    PatchDevice(String, Value),
+    RemoveDevice(String),
+    AddFolder(Value),
+    PatchFolder(String, Value),
+    RemoveFolder(String),
+    PatchOptions(Value),
+}
+
+struct Step {
+    change: PlannedChange,
+    request: Request,
+}
+
+pub async fn run(client: &Client, out: &Output, path: &str, prune: bool, yes: bool) -> Result<()> {
+    let manifest = Manifest::load(path)?;
+    let live = client.config().await?;
+    let my_id = client.status().await?.my_id;
+    let steps = plan(&manifest, &live, &my_id, prune)?;
+
+    let mut report = ApplyReport {
+        changes: Vec::new(),
+        applied: false,
+    };
+    if steps.is_empty() {
+        return out.emit(&report);
+    }
+    let (changes, requests): (Vec<_>, Vec<_>) =
+        steps.into_iter().map(|s| (s.change, s.request)).unzip();
+    report.changes = changes;
+
+    for line in report.plan_lines() {
+        out.info(line);
+    }
+    if !(yes || client.dry_run()) {
+        confirm_or_abort("Apply these changes?", false)?;
+        // Answering may have taken a while; patches carry whole lists (such
+        // as a folder's devices), so don't send a plan made for an older config
+        let live = client.config().await?;
+        let replanned: Vec<Request> = plan(&manifest, &live, &my_id, prune)?
+            .into_iter()
+            .map(|s| s.request)
+            .collect();
+        if replanned != requests {
+            bail!(
+                "Syncthing's configuration changed while waiting for confirmation; \
+                 run apply again to see the new plan"
+            );
+        }
+    }
+
+    for (change, request) in report.changes.iter().zip(&requests) {
+        send(client, request).await.with_context(|| {
+            format!("Failed to {} {} {}", change.action, change.kind, change.id)
+        })?;
+    }
+    report.applied = true;
+    out.emit(&report)
+}
+
+async fn send(client: &Client, request: &Request) -> Result<()> {
+    match request {
+        Request::AddDevice(device) => client.add_device(device).await,
+        Request::PatchDevice(id, patch) => client.patch_device(id, patch).await,
+        Request::RemoveDevice(id) => client.remove_device(id).await,
+        Request::AddFolder(folder) => client.add_folder(folder).await,
+        Request::PatchFolder(id, patch) => client.patch_folder(id, patch).await,
+        Request::RemoveFolder(id) => client.remove_folder(id).await,
+        Request::PatchOptions(patch) => client.patch_options(patch).await,
+    }
+}
+
+/// The steps that take the `live` config (`/rest/config`) to the manifest:
+/// devices first so folders can be shared with them, then folders and
+/// options, then removals.
+fn plan(manifest: &Manifest, live: &Value, my_id: &str, prune: bool) -> Result<Vec<Step>> {
+    let live_devices: Vec<DeviceConfig> = match &live["devices"] {
+        Value::Null => Vec::new(),
+        devices => serde_json::from_value(devices.clone())
+            .context("Unexpected device list in the config")?,
+    };
+    let wanted_devices = manifest
+        .devices
+        .iter()
+        .flatten()
+        .map(device_entry)
+        .collect::<Result<Vec<_>>>()?;
+    let manifest_devices: Vec<DeviceConfig> = wanted_devices
+        .iter()
+        .map(|entry| serde_json::from_value(Value::Object(entry.clone())))
+        .collect::<Result<_, _>>()?;
+    let names: HashMap<&str, &str> = live_devices
+        .iter()
+        .chain(&manifest_devices)
+        .map(|d| (d.device_id.as_str(), d.display_name()))
+        .collect();
+
+    let mut steps = Vec::new();
+    let mut removals = Vec::new();
+
+    for (entry, device) in wanted_devices.iter().zip(&manifest_devices) {
+        let id = &device.device_id;
+        let name = names[id.as_str()].to_string();
+        match find(&live["devices"], "deviceID", id) {
+            None => steps.push(Step {
+                change: added("device", id, name, entry, &["deviceID"]),
+                request: Request::AddDevice(Value::Object(entry.clone())),
+            }),
+            Some(current) => {
+                let fields = changed_fields(current, entry);
+                if !fields.is_empty() {
+                    steps.push(Step {
+                        request: Request::PatchDevice(id.clone(), patch_of(entry, &fields)),
+                        change: updated("device", id, name, fields),
+                    });
+                }
+            }
+        }
+    }
+    if prune && manifest.devices.is_some() {
+        for device in &live_devices {
+            if device.device_id != my_id
+                && !manifest_devices
+                    .iter()
+                    .any(|d| d.device_id == device.device_id)
+            {
+                removals.push(Step {
+                    change: removed("device", &device.device_id, device.display_name()),
+                    request: Request::RemoveDevice(device.device_id.clone()),
+                });
+            }
+        }
+    }
+
+    let wanted_folders: Vec<&Map<String, Value>> = manifest.folders.iter().flatten().collect();
+    for entry in &wanted_folders {
+        let id = entry
+            .get("id")
+            .and_then(Value::as_str)
+            .context("Every folder in the manifest needs an id")?;
+        let shared = match entry.get("devices") {
+            Some(devices) => Some(folder_devices(
+                id,
+                devices,
+                &manifest_devices,
+                &live_devices,
+                my_id,
+            )?),
+            None => None,
+        };
+        let mut settings = (*entry).clone();
+        settings.remove("devices");
+
+        match find(&live["folders"], "id", id) {
+            None => {
+                let name = label_of(&settings, id);
+                let mut change = added("folder", id, name, &settings, &["id"]);
+                if let Some(shared) = &shared {
+                    change.fields.push(FieldChange {
+                        field: "devices".to_string(),
+                        from: Value::Null,
+                        to: device_names(shared, &names),
+                    });
+                    let shared: Vec<Value> =
+                        shared.iter().map(|d| json!({ "deviceID": d })).collect();
+                    settings.insert("devices".to_string(), Value::Array(shared));
+                }
+                steps.push(Step {
+                    change,
+                    request: Request::AddFolder(Value::Object(settings)),
+                });
+            }
+            Some(current) => {
+                let name = label_of(&settings, current["label"].as_str().unwrap_or(id));
+                let mut fields = changed_fields(current, &settings);
+                let mut patch = patch_of(&settings, &fields);
+                if let Some(shared) = shared {
+                    let live_shared: Vec<&Value> = current["devices"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                    let mut have: Vec<String> = live_shared
+                        .iter()
+                        .filter_map(|d| d["deviceID"].as_str())
+                        .filter(|id| *id != my_id)
+                        .map(String::from)
+                        .collect();
+                    have.sort();
+                    let mut want = shared.clone();
+                    want.sort();
+                    if have != want {
+                        fields.push(FieldChange {
+                            field: "devices".to_string(),
+                            from: device_names(&have, &names),
+                            to: device_names(&shared, &names),
+                        });
+                        // Keep what is configured per device, such as an
+                        // encryption password, for devices that stay
+                        let devices: Vec<Value> = live_shared
+                            .iter()
+                            .filter(|d| d["deviceID"] == my_id)
+                            .map(|d| (*d).clone())
+                            .chain(shared.iter().map(|id| {
+                                live_shared
+                                    .iter()
+                                    .find(|d| d["deviceID"] == id.as_str())
+                                    .map_or_else(|| json!({ "deviceID": id }), |d| (*d).clone())
+                            }))
+                            .collect();
+                        patch["devices"] = Value::Array(devices);
+                    }
+                }
+                if !fields.is_empty() {
+                    steps.push(Step {
+                        change: updated("folder", id, name, fields),
+                        request: Request::PatchFolder(id.to_string(), patch),
+                    });
+                }
+            }
+        }
+    }
+    if prune && manifest.folders.is_some() {
+        for folder in live["folders"].as_array().into_iter().flatten() {
+            let id = folder["id"].as_str().unwrap_or_default();
+            if !wanted_folders.iter().any(|f| f["id"] == id) {
+                let label = folder["label"].as_str().unwrap_or(id);
+                // Folders go before the devices they may be shared with
+                removals.insert(
+                    0,
+                    Step {
+                        change: removed("folder", id, label),
+                        request: Request::RemoveFolder(id.to_string()),
+                    },
+                );
+            }
+        }
+    }
+
+    let fields = changed_fields(&live["options"], &manifest.options);
+    if !fields.is_empty() {
+        steps.push(Step {
+            request: Request::PatchOptions(patch_of(&manifest.options, &fields)),
+            change: updated("options", "", String::new(), fields),
+        });
+    }
+
+    steps.extend(removals);
+    Ok(steps)
+}
+
+/// A manifest device with its ID in canonical form.
+fn device_entry(entry: &Map<String, Value>) -> Result<Map<String, Value>> {
+    let Some(id) = entry.get("deviceID").and_then(Value::as_str) else {
+        bail!("Every device in the manifest needs a deviceID");
+    };
+    let mut entry = entry.clone();
+    entry.insert("deviceID".to_string(), json!(device_id::normalize(id)?));
+    Ok(entry)
+}
+
+/// The IDs of the devices a manifest folder lists by name, ID or
+/// `{deviceID: ...}`, without this device (Syncthing adds it itself).
+fn folder_devices(
+    folder: &str,
+    devices: &Value,
+    manifest_devices: &[DeviceConfig],
+    live_devices: &[DeviceConfig],
+    my_id: &str,
+) -> Result<Vec<String>> {
+    let Some(devices) = devices.as_array() else {
+        bail!("The devices of folder {} must be a list", folder);
+    };
+    let mut ids = Vec::new();
+    for device in devices {
+        let query = device
+            .as_str()
+            .or_else(|| device["deviceID"].as_str())
+            .with_context(|| format!("Unreadable device in folder {}", folder))?;
+        let id = find_device(manifest_devices, query)
+            .or_else(|_| find_device(live_devices, query))
+            .map(|d| d.device_id.clone())
+            .or_else(|_| device_id::normalize(query))
+            .with_context(|| format!("Unknown device '{}' in folder {}", query, folder))?;
+        if id != my_id && !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
+/// The entry in the `list` array whose `key` is `value`.
+fn find<'a>(list: &'a Value, key: &str, value: &str) -> Option<&'a Value> {
+    list.as_array()?.iter().find(|item| item[key] == value)
+}
+
+fn label_of(settings: &Map<String, Value>, default: &str) -> String {
+    settings
+        .get("label")
+        .and_then(Value::as_str)
+        .unwrap_or(default)
+        .to_string()
+}
+
+fn device_names(ids: &[String], names: &HashMap<&str, &str>) -> Value {
+    ids.iter()
+        .map(|id| {
+            names
+                .get(id.as_str())
+                .filter(|name| *name != id)
+                .map_or_else(|| short_id(id).to_string(), |name| name.to_string())
+        })
+        .collect()
+}
+
+/// The settings of `desired` that differ from `live`. Objects such as
+/// `versioning` are compared key by key, so a manifest only has to name the
+/// keys it cares about.
+fn changed_fields(live: &Value, desired: &Map<String, Value>) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    collect_changes("", live, desired, &mut fields);
+    fields
+}
+
+fn collect_changes(
+    prefix: &str,
+    live: &Value,
+    desired: &Map<String, Value>,
+    fields: &mut Vec<FieldChange>,
+) {
+    for (key, want) in desired {
+        let field = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        let have = live.get(key).unwrap_or(&Value::Null);
+        match want {
+            Value::Object(want) if have.is_object() => collect_changes(&field, have, want, fields),
+            want if !same(have, want) => fields.push(FieldChange {
+                field,
+                from: have.clone(),
+                to: want.clone(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Equal, counting `3600` and `3600.0` as the same number.
+fn same(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// A PATCH body with the top-level keys of `desired` that `fields` touch.
+/// Nested objects are sent as the manifest has them; Syncthing merges them.
+fn patch_of(desired: &Map<String, Value>, fields: &[FieldChange]) -> Value {
+    let mut patch = Map::new();
+    for field in fields {
+        let key = field.field.split('.').next().unwrap_or_default();
+        if let Some(value) = desired.get(key) {
+            patch.insert(key.to_string(), value.clone());
+        }
+    }
+    Value::Object(patch)
+}
+
+fn added(
+    kind: &'static str,
+    id: &str,
+    name: String,
+    entry: &Map<String, Value>,
+    skip: &[&str],
+) -> PlannedChange {
+    PlannedChange {
+        action: "add",
+        kind,
+        id: id.to_string(),
+        name,
+        fields: entry
+            .iter()
+            .filter(|(key, _)| !skip.contains(&key.as_str()))
+            .map(|(key, value)| FieldChange {
+                field: key.clone(),
+                from: Value::Null,
+                to: value.clone(),
+            })
+            .collect(),
+    }
+}
+
+fn updated(kind: &'static str, id: &str, name: String, fields: Vec<FieldChange>) -> PlannedChange {
+    PlannedChange {
+        action: "update",
+        kind,
+        id: id.to_string(),
+        name,
+        fields,
+    }
+}
+
+fn removed(kind: &'static str, id: &str, name: &str) -> PlannedChange {
+    PlannedChange {
+        action: "remove",
+        kind,
+        id: id.to_string(),
+        name: name.to_string(),
+        fields: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINE: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+    const NAS: &str = "P56IOI7-MZJNU2Y-IQGDREY-DM2MGTI-MGL3BXN-PQ6W5BM-TBBZ4TJ-XZWICQ2";
+
+    fn live() -> Value {
+        json!({
+            "devices": [
+                {"deviceID": MINE, "name": "laptop"},
+                {"deviceID": NAS, "name": "nas", "paused": false}
+            ],
+            "folders": [
+                {"id": "docs", "label": "Docs", "path": "/docs", "rescanIntervalS": 3600,
+                 "versioning": {"type": "", "params": {}},
+                 "devices": [{"deviceID": MINE}, {"deviceID": NAS, "encryptionPassword": "pw"}]},
+                {"id": "old", "label": "Old", "path": "/old", "devices": []}
+            ],
+            "options": {"relaysEnabled": true, "natEnabled": true}
+        })
+    }
+
+    fn parse(yaml: &str) -> Manifest {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_plan_matching_config_is_empty() {
+        let manifest = parse(
+            "
+devices:
+  - deviceID: p56ioi7-mzjnu2y-iqgdrey-dm2mgti-mgl3bxn-pq6w5bm-tbbz4tj-xzwicq2
+    name: nas
+folders:
+  - id: docs
+    label: Docs
+    rescanIntervalS: 3600.0
+    devices: [nas]
+options:
+  natEnabled: true
+",
+        );
+        assert!(plan(&manifest, &live(), MINE, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_plan() {
+        let manifest = parse(
+            "
+folders:
+  - id: docs
+    label: Documents
+    versioning: {type: simple}
+    devices: []
+  - id: photos
+    path: /photos
+    devices: [nas]
+options:
+  relaysEnabled: false
+",
+        );
+        let steps = plan(&manifest, &live(), MINE, true).unwrap();
+        let requests: Vec<&Request> = steps.iter().map(|s| &s.request).collect();
+        assert_eq!(
+            requests,
+            [
+                &Request::PatchFolder(
+                    "docs".to_string(),
+                    json!({
+                        "label": "Documents",
+                        "versioning": {"type": "simple"},
+                        "devices": [{"deviceID": MINE}]
+                    })
+                ),
+                &Request::AddFolder(json!({
+                    "id": "photos",
+                    "path": "/photos",
+                    "devices": [{"deviceID": NAS}]
+                })),
+                &Request::PatchOptions(json!({"relaysEnabled": false})),
+                &Request::RemoveFolder("old".to_string()),
+            ]
+        );
+
+        let fields: Vec<String> = steps[0]
+            .change
+            .fields
+            .iter()
+            .map(|f| format!("{}: {} -> {}", f.field, f.from, f.to))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "label: \"Docs\" -> \"Documents\"",
+                "versioning.type: \"\" -> \"simple\"",
+                "devices: [\"nas\"] -> []",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_devices() {
+        let manifest = parse(&format!(
+            "
+devices:
+  - deviceID: {}
+    name: backup
+",
+            NAS
+        ));
+        let steps = plan(&manifest, &live(), MINE, false).unwrap();
+        assert_eq!(steps.len(), 1);
+        assert_eq!(
+            steps[0].request,
+            Request::PatchDevice(NAS.to_string(), json!({"name": "backup"}))
+        );
+
+        // Pruning never removes this device
+        let steps = plan(&parse("devices: []"), &live(), MINE, true).unwrap();
+        assert_eq!(
+            steps.iter().map(|s| &s.request).collect::<Vec<_>>(),
+            [&Request::RemoveDevice(NAS.to_string())]
+        );
+    }
+
+    #[test]
+    fn test_plan_rejects_unknown_device() {
+        let manifest = parse("folders: [{id: docs, devices: [phone]}]");
+        let error = plan(&manifest, &live(), MINE, false).err().unwrap();
+        assert_eq!(error.to_string(), "Unknown device 'phone' in folder docs");
+    }
+}
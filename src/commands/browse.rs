@@ -0,0 +1,23 @@
+use crate::output::{BrowseReport, Output};
+use anyhow::Result;
+use syncthing::api::Client;
+use syncthing::models::find_folder;
+
+pub async fn browse(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    prefix: Option<&str>,
+    levels: Option<u32>,
+) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let entries = client.db_browse(&folder.id, prefix, levels).await?;
+
+    let report = BrowseReport {
+        folder: folder.id.clone(),
+        prefix: prefix.map(str::to_string),
+        entries,
+    };
+    out.emit(&report)
+}
@@ -0,0 +1,214 @@
+use super::disk_space_warning;
+use crate::exit;
+use crate::output::{CheckReport, Output, format_bytes, format_duration_since};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use syncthing::api::Client;
+
+/// Check every unpaused folder against the thresholds and return the exit
+/// code: 0 when in sync, 6 when out of sync, otherwise the code for the
+/// error (3 when the API is unreachable, 4 when it answered with an error).
+pub async fn run(
+    client: &Client,
+    out: &Output,
+    max_need_bytes: u64,
+    max_age: Option<Duration>,
+) -> Result<i32> {
+    let report = match check(client, max_need_bytes, max_age).await {
+        Ok(report) => report,
+        Err(e) => failed(&e),
+    };
+    out.emit(&report)?;
+    Ok(report.exit_code)
+}
+
+/// Report for when the check itself failed: `unreachable` when Syncthing
+/// could not be reached (or rejected the API key), `error` otherwise.
+pub fn failed(error: &anyhow::Error) -> CheckReport {
+    let exit_code = exit::code_for(error);
+    let status = if exit_code == exit::CONNECTION {
+        "unreachable"
+    } else {
+        "error"
+    };
+    CheckReport {
+        status: status.to_string(),
+        exit_code,
+        problems: vec![format!("{:#}", error)],
+        warnings: Vec::new(),
+        folders: 0,
+    }
+}
+
+async fn check(
+    client: &Client,
+    max_need_bytes: u64,
+    max_age: Option<Duration>,
+) -> Result<CheckReport> {
+    let folders = client.config_folders().await?;
+    let stats = client.stats_folder().await?;
+    let now = Utc::now();
+
+    let mut problems = Vec::new();
+    let mut warnings = Vec::new();
+    let mut checked = 0;
+    for folder in folders.iter().filter(|f| !f.paused) {
+        checked += 1;
+        let name = folder.display_name();
+        let status = client.db_status(&folder.id).await?;
+
+        if status.state == "error" {
+            problems.push(format!("{} is in error state", name));
+        }
+        if status.errors > 0 {
+            problems.push(format!("{} has {} sync errors", name, status.errors));
+        }
+        if let Some(warning) = disk_space_warning(folder, &status.error) {
+            warnings.push(format!("{} is low on disk space ({})", name, warning));
+        }
+        if status.need_bytes > max_need_bytes {
+            problems.push(format!(
+                "{} needs {} files ({})",
+                name,
+                status.need_files,
+                format_bytes(status.need_bytes)
+            ));
+        }
+
+        if let Some(max_age) = max_age {
+            let last_scan = stats.get(&folder.id).map(|s| s.last_scan.as_str());
+            let scanned_at = last_scan.and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+            match scanned_at {
+                Some(at)
+                    if (now - at.with_timezone(&Utc)).to_std().unwrap_or_default() > max_age =>
+                {
+                    problems.push(format!(
+                        "{} last scanned {}",
+                        name,
+                        format_duration_since(last_scan.unwrap_or_default())
+                    ));
+                }
+                Some(_) => {}
+                None => problems.push(format!("{} has never been scanned", name)),
+            }
+        }
+    }
+
+    let (status, exit_code) = if problems.is_empty() {
+        ("ok", exit::OK)
+    } else {
+        ("out-of-sync", exit::THRESHOLD)
+    };
+    Ok(CheckReport {
+        status: status.to_string(),
+        exit_code,
+        problems,
+        warnings,
+        folders: checked,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_folder(server: &MockServer, need_bytes: u64) {
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"id": "docs", "label": "Docs"}])),
+            )
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/stats/folder"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "docs": {"lastScan": Utc::now().to_rfc3339()}
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/db/status"))
+            .and(query_param("folder", "docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "state": "idle",
+                "needFiles": 1,
+                "needBytes": need_bytes
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_check_thresholds() {
+        let server = MockServer::start().await;
+        mock_folder(&server, 2048).await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let report = check(&client, 0, None).await.unwrap();
+        assert_eq!(report.exit_code, exit::THRESHOLD);
+        assert_eq!(report.problems, vec!["Docs needs 1 files (2.0 KB)"]);
+
+        let report = check(&client, 4096, Some(Duration::from_secs(3600)))
+            .await
+            .unwrap();
+        assert_eq!(report.exit_code, exit::OK);
+        assert_eq!(report.folders, 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_api_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": "docs"}])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/stats/folder"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/db/status"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        let options = syncthing::api::ClientOptions {
+            retries: 0,
+            ..Default::default()
+        };
+        let client = Client::with_options("key", &server.uri(), &options).unwrap();
+
+        let Err(e) = check(&client, 0, None).await else {
+            panic!("check should fail on a 500");
+        };
+        assert_eq!(failed(&e).status, "error");
+        let out = Output {
+            json: true,
+            ..Default::default()
+        };
+        assert_eq!(run(&client, &out, 0, None).await.unwrap(), exit::API);
+    }
+
+    #[tokio::test]
+    async fn test_check_unreachable() {
+        let client = Client::new("key", "http://127.0.0.1:1").unwrap();
+        let code = run(
+            &client,
+            &Output {
+                json: true,
+                ..Default::default()
+            },
+            0,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(code, exit::CONNECTION);
+    }
+}
@@ -0,0 +1,52 @@
+use crate::output::{ClusterMember, Output};
+use anyhow::{Result, bail};
+use syncthing::api::Client;
+use syncthing::config;
+use tokio::task::JoinSet;
+
+/// Query every configured profile concurrently and summarize each instance.
+pub async fn status(out: &Output) -> Result<()> {
+    let cfg = config::load_config()?;
+    if cfg.profiles.is_empty() {
+        bail!("No profiles configured (see 'syncthing config profile add')");
+    }
+
+    let mut tasks = JoinSet::new();
+    for (name, profile) in &cfg.profiles {
+        let options = cfg.client_options(&profile.host, Some(profile));
+        let (name, profile) = (name.clone(), profile.clone());
+        tasks.spawn(async move {
+            let mut member = ClusterMember {
+                profile: name,
+                host: profile.host.clone(),
+                ..Default::default()
+            };
+            let result = match Client::with_options(&profile.api_key, &profile.host, &options) {
+                Ok(client) => fill(&client, &mut member).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                member.error = Some(format!("{:#}", e));
+            }
+            member
+        });
+    }
+
+    let mut members = tasks.join_all().await;
+    members.sort_by(|a, b| a.profile.cmp(&b.profile));
+    out.emit(&members)
+}
+
+async fn fill(client: &Client, member: &mut ClusterMember) -> Result<()> {
+    let status = client.status().await?;
+    let version = client.version().await?;
+    let completion = client.db_completion().await?;
+    let errors = client.errors().await?;
+
+    member.version = version.version;
+    member.uptime = status.uptime;
+    member.completion = completion.completion;
+    member.need_bytes = completion.need_bytes;
+    member.errors = errors.len();
+    Ok(())
+}
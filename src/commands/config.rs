@@ -0,0 +1,364 @@
+use crate::output::{ActionResult, ConfigReport, LdapReport, Output, ProfileSummary};
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value, json};
+use std::path::PathBuf;
+use syncthing::api::{Client, redact};
+use syncthing::config::{self, Profile};
+use syncthing::models::NotFound;
+
+pub fn show(out: &Output, profile: Option<&str>) -> Result<()> {
+    let cfg = config::load_config()?;
+    let report = match cfg.profile(profile)? {
+        Some((name, profile)) => ConfigReport {
+            host: profile.host.clone(),
+            api_key: Some(profile.api_key.clone()),
+            profile: Some(name.to_string()),
+            keyring: false,
+        },
+        None => ConfigReport {
+            // Where commands would connect, including a GUI address from config.xml
+            host: config::detect(&cfg).map_or_else(|_| cfg.host().to_string(), |d| d.host),
+            api_key: cfg.api_key,
+            profile: None,
+            keyring: cfg.keyring,
+        },
+    };
+    out.emit(&report)
+}
+
+/// Save the API key and/or host. With `keyring`, the key goes to the OS
+/// credential store and is removed from the config file.
+pub fn set(
+    out: &Output,
+    api_key: Option<String>,
+    host: Option<String>,
+    keyring: bool,
+) -> Result<()> {
+    let mut cfg = config::load_config()?;
+    match api_key {
+        Some(key) if keyring => {
+            config::store_keyring_api_key(&key)?;
+            cfg.api_key = None;
+            cfg.keyring = true;
+        }
+        Some(key) => {
+            cfg.api_key = Some(key);
+            cfg.keyring = false;
+        }
+        None if keyring => bail!("--keyring needs --api-key"),
+        None => {}
+    }
+    if let Some(h) = host {
+        cfg.host = Some(h);
+    }
+    config::save_config(&cfg)?;
+    out.info("Configuration saved");
+    Ok(())
+}
+
+pub fn profile_list(out: &Output) -> Result<()> {
+    let cfg = config::load_config()?;
+    let profiles: Vec<ProfileSummary> = cfg
+        .profiles
+        .iter()
+        .map(|(name, profile)| ProfileSummary {
+            name: name.clone(),
+            host: profile.host.clone(),
+            default: cfg.default_profile.as_deref() == Some(name),
+        })
+        .collect();
+    out.emit(&profiles)
+}
+
+/// Add a profile, or replace one with the same name. `insecure` skips TLS
+/// certificate verification for it; `cacert` trusts an extra certificate.
+pub fn profile_add(
+    out: &Output,
+    name: &str,
+    host: &str,
+    api_key: &str,
+    insecure: bool,
+    cacert: Option<&str>,
+) -> Result<()> {
+    let mut cfg = config::load_config()?;
+    let replaced = cfg
+        .profiles
+        .insert(
+            name.to_string(),
+            Profile {
+                host: config::normalize_host(host),
+                api_key: api_key.to_string(),
+                insecure,
+                cacert: cacert.map(str::to_string),
+            },
+        )
+        .is_some();
+    config::save_config(&cfg)?;
+    let verb = if replaced { "Updated" } else { "Added" };
+    out.emit(&ActionResult::new(format!("{} profile: {}", verb, name)))
+}
+
+pub fn profile_remove(out: &Output, name: &str) -> Result<()> {
+    let mut cfg = config::load_config()?;
+    if cfg.profiles.remove(name).is_none() {
+        return Err(NotFound(format!("No profile named '{}'", name)).into());
+    }
+    if cfg.default_profile.as_deref() == Some(name) {
+        cfg.default_profile = None;
+    }
+    config::save_config(&cfg)?;
+    out.emit(&ActionResult::new(format!("Removed profile: {}", name)))
+}
+
+/// Make `name` the default profile for commands run without `--host`.
+pub fn profile_use(out: &Output, name: &str) -> Result<()> {
+    let mut cfg = config::load_config()?;
+    if !cfg.profiles.contains_key(name) {
+        return Err(NotFound(format!("No profile named '{}'", name)).into());
+    }
+    cfg.default_profile = Some(name.to_string());
+    config::save_config(&cfg)?;
+    out.emit(&ActionResult::new(format!("Using profile: {}", name)))
+}
+
+/// Download the full Syncthing configuration, with secrets redacted unless
+/// `include_secrets` is set, to `output` or stdout.
+pub async fn export(
+    client: &Client,
+    out: &Output,
+    output: Option<&str>,
+    include_secrets: bool,
+) -> Result<()> {
+    let mut cfg = client.config().await?;
+    if !include_secrets {
+        redact(&mut cfg);
+    }
+    let json = serde_json::to_string_pretty(&cfg)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, json + "\n")
+                .with_context(|| format!("Failed to write {}", path))?;
+            out.emit(&ActionResult::new(format!("Exported config to {}", path)))
+        }
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+/// PATCH a JSON fragment into `/rest/config`, read from `fragment` or, when
+/// that is `None` or `-`, from stdin.
+pub async fn patch(client: &Client, out: &Output, fragment: Option<&str>) -> Result<()> {
+    let text = match fragment {
+        Some(text) if text != "-" => text.to_string(),
+        _ => std::io::read_to_string(std::io::stdin()).context("Failed to read stdin")?,
+    };
+    let patch: Value = serde_json::from_str(&text).context("Invalid JSON fragment")?;
+    if !patch.is_object() {
+        bail!("The config patch must be a JSON object");
+    }
+    client.patch_config(&patch).await?;
+    out.emit(&ActionResult::new("Config updated"))
+}
+
+/// Trust a CA certificate (default: Syncthing's own `https-cert.pem`) for
+/// the selected profile, or for connections without a profile.
+pub fn trust_cert(out: &Output, profile: Option<&str>, path: Option<&str>) -> Result<()> {
+    let path = path
+        .map(PathBuf::from)
+        .unwrap_or_else(config::syncthing_cert_path);
+    let path = std::fs::canonicalize(&path)
+        .with_context(|| format!("Cannot read certificate {}", path.display()))?;
+    let path = path.display().to_string();
+
+    let mut cfg = config::load_config()?;
+    let name = cfg.profile(profile)?.map(|(name, _)| name.to_string());
+    match &name {
+        Some(name) => {
+            if let Some(profile) = cfg.profiles.get_mut(name) {
+                profile.cacert = Some(path.clone());
+            }
+        }
+        None => cfg.cacert = Some(path.clone()),
+    }
+    config::save_config(&cfg)?;
+
+    let target = name.map_or_else(String::new, |n| format!(" for profile {}", n));
+    out.emit(&ActionResult::new(format!("Trusting {}{}", path, target)))
+}
+
+/// Pin the certificate the server presents right now (trust on first use),
+/// or forget the pin with `remove`.
+pub async fn pin(client: &Client, out: &Output, remove: bool) -> Result<()> {
+    let mut cfg = config::load_config()?;
+    let host = client.base_url().to_string();
+
+    if remove {
+        if cfg.pins.remove(&host).is_none() {
+            bail!("No certificate pinned for {}", host);
+        }
+        config::save_config(&cfg)?;
+        return out.emit(&ActionResult::new(format!(
+            "Unpinned certificate of {}",
+            host
+        )));
+    }
+
+    let Some(fingerprint) = client.peer_fingerprint().await? else {
+        bail!(
+            "{} does not use HTTPS; there is no certificate to pin",
+            host
+        );
+    };
+    cfg.pins.insert(host.clone(), fingerprint.clone());
+    config::save_config(&cfg)?;
+    out.emit(&ActionResult::new(format!(
+        "Pinned certificate of {} (SHA-256 {})",
+        host, fingerprint
+    )))
+}
+
+/// Show the LDAP settings and whether the GUI uses them.
+pub async fn ldap_show(client: &Client, out: &Output) -> Result<()> {
+    let ldap = client.ldap().await?;
+    let gui = client.gui().await?;
+    out.emit(&LdapReport {
+        address: ldap.address,
+        bind_dn: ldap.bind_dn,
+        transport: ldap.transport,
+        insecure_skip_verify: ldap.insecure_skip_verify,
+        search_base_dn: ldap.search_base_dn,
+        search_filter: ldap.search_filter,
+        gui_auth: gui.auth_mode == "ldap",
+    })
+}
+
+/// New LDAP settings for `config ldap set`; `None` keeps the current value.
+#[derive(Default)]
+pub struct LdapChange {
+    pub address: Option<String>,
+    pub bind_dn: Option<String>,
+    /// `plain`, `tls` or `starttls`.
+    pub transport: Option<&'static str>,
+    pub insecure_skip_verify: Option<bool>,
+    pub search_base_dn: Option<String>,
+    pub search_filter: Option<String>,
+    /// Switch the GUI between LDAP and its own user and password.
+    pub gui_auth: Option<bool>,
+}
+
+pub async fn ldap_set(client: &Client, out: &Output, change: LdapChange) -> Result<()> {
+    let mut patch = Map::new();
+    let fields = [
+        ("address", change.address.map(Value::from)),
+        ("bindDN", change.bind_dn.map(Value::from)),
+        ("transport", change.transport.map(Value::from)),
+        (
+            "insecureSkipVerify",
+            change.insecure_skip_verify.map(Value::from),
+        ),
+        ("searchBaseDN", change.search_base_dn.map(Value::from)),
+        ("searchFilter", change.search_filter.map(Value::from)),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            patch.insert(key.to_string(), value);
+        }
+    }
+    if patch.is_empty() && change.gui_auth.is_none() {
+        bail!("Nothing to change; pass --address, --bind-dn, --transport or another setting");
+    }
+
+    if !patch.is_empty() {
+        client.patch_ldap(&patch.into()).await?;
+    }
+    // Only after the server is configured, so the GUI isn't pointed at an
+    // empty LDAP setup
+    if let Some(ldap) = change.gui_auth {
+        let mode = if ldap { "ldap" } else { "static" };
+        client.patch_gui(&json!({ "authMode": mode })).await?;
+    }
+    ldap_show(client, out).await
+}
+
+/// Report whether Syncthing needs a restart to apply config changes, and
+/// restart it when `restart` is set and it does.
+pub async fn restart_required(client: &Client, out: &Output, restart: bool) -> Result<()> {
+    let required = client.restart_required().await?;
+    let message = match (required, restart) {
+        (false, _) => "No restart required",
+        (true, false) => "Restart required to apply config changes",
+        (true, true) => {
+            client.restart().await?;
+            "Restart required; Syncthing restart initiated"
+        }
+    };
+    out.emit(&ActionResult::new(message))
+}
+
+/// Print a note on stderr when a config change is waiting for a restart.
+/// Failures are ignored: the change itself already succeeded.
+pub async fn warn_if_restart_required(client: &Client, out: &Output) {
+    if let Ok(true) = client.restart_required().await {
+        out.info(
+            "Note: Syncthing must be restarted for this change to take effect \
+             (run 'syncthing config restart-required --restart')",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_ldap_set_then_gui_auth() {
+        let server = MockServer::start().await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/ldap"))
+            .and(body_json(
+                json!({"address": "ldap:636", "transport": "tls"}),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/gui"))
+            .and(body_json(json!({"authMode": "ldap"})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        for endpoint in ["/rest/config/ldap", "/rest/config/gui"] {
+            Mock::given(method("GET"))
+                .and(path(endpoint))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+                .mount(&server)
+                .await;
+        }
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let change = LdapChange {
+            address: Some("ldap:636".to_string()),
+            transport: Some("tls"),
+            gui_auth: Some(true),
+            ..Default::default()
+        };
+        let out = Output {
+            json: true,
+            ..Default::default()
+        };
+        ldap_set(&client, &out, change).await.unwrap();
+        assert!(
+            ldap_set(&client, &out, LdapChange::default())
+                .await
+                .is_err()
+        );
+    }
+}
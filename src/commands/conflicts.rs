@@ -0,0 +1,127 @@
+use crate::output::{ConflictFile, ConflictGroup, ConflictsReport, Output};
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use std::collections::BTreeMap;
+use syncthing::api::Client;
+use syncthing::models::{BrowseEntry, find_folder};
+
+const MARKER: &str = ".sync-conflict-";
+
+/// List `*.sync-conflict-*` files in a folder, grouped by the file they conflict with.
+pub async fn conflicts(client: &Client, out: &Output, query: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let tree = client.db_browse(&folder.id, None, None).await?;
+
+    let mut files = Vec::new();
+    collect_files(&tree, "", &mut files);
+
+    let mut groups: BTreeMap<String, Vec<ConflictFile>> = BTreeMap::new();
+    for (path, entry) in files {
+        let Some(conflict) = parse_conflict_name(&path) else {
+            continue;
+        };
+        groups
+            .entry(conflict.original)
+            .or_default()
+            .push(ConflictFile {
+                path,
+                time: conflict.time,
+                device: conflict.device,
+                size: entry.size,
+            });
+    }
+
+    let report = ConflictsReport {
+        folder: folder.id.clone(),
+        groups: groups
+            .into_iter()
+            .map(|(original, mut conflicts)| {
+                conflicts.sort_by(|a, b| b.time.cmp(&a.time));
+                ConflictGroup {
+                    original,
+                    conflicts,
+                }
+            })
+            .collect(),
+    };
+    out.emit(&report)
+}
+
+fn collect_files<'a>(
+    entries: &'a [BrowseEntry],
+    dir: &str,
+    files: &mut Vec<(String, &'a BrowseEntry)>,
+) {
+    for entry in entries {
+        let path = if dir.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", dir, entry.name)
+        };
+        if entry.is_dir() {
+            collect_files(&entry.children, &path, files);
+        } else {
+            files.push((path, entry));
+        }
+    }
+}
+
+struct ConflictName {
+    original: String,
+    /// RFC 3339 time of the conflict, when the name's timestamp is valid.
+    time: Option<String>,
+    /// Short ID of the device whose change lost.
+    device: String,
+}
+
+/// Parse `dir/name.sync-conflict-20240131-154502-ABCDEFG.ext`.
+fn parse_conflict_name(path: &str) -> Option<ConflictName> {
+    let start = path.rfind(MARKER)?;
+    let base = &path[..start];
+    let rest = &path[start + MARKER.len()..];
+
+    // 20240131-154502-ABCDEFG[.ext]
+    let (stamp, rest) = rest.split_at_checked(15)?;
+    let rest = rest.strip_prefix('-')?;
+    let (device, ext) = match rest.find('.') {
+        Some(dot) => (&rest[..dot], &rest[dot..]),
+        None => (rest, ""),
+    };
+
+    let time = NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S")
+        .ok()
+        .and_then(|t| Local.from_local_datetime(&t).single())
+        .map(|t| t.to_rfc3339());
+
+    Some(ConflictName {
+        original: format!("{}{}", base, ext),
+        time,
+        device: device.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conflict_name() {
+        let c =
+            parse_conflict_name("notes/todo.sync-conflict-20240131-154502-ABCDEFG.txt").unwrap();
+        assert_eq!(c.original, "notes/todo.txt");
+        assert_eq!(c.device, "ABCDEFG");
+        assert!(c.time.unwrap().starts_with("2024-01-31T15:45:02"));
+    }
+
+    #[test]
+    fn test_parse_conflict_name_without_extension() {
+        let c = parse_conflict_name("Makefile.sync-conflict-20240131-154502-ABCDEFG").unwrap();
+        assert_eq!(c.original, "Makefile");
+    }
+
+    #[test]
+    fn test_parse_regular_file() {
+        assert!(parse_conflict_name("notes/todo.txt").is_none());
+    }
+}
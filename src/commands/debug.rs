@@ -0,0 +1,43 @@
+use crate::output::{ActionResult, DebugFacility, Output};
+use anyhow::{Result, bail};
+use syncthing::api::Client;
+
+/// Every debug facility, whether it is enabled, and what it logs.
+pub async fn list(client: &Client, out: &Output) -> Result<()> {
+    let debug = client.debug().await?;
+    let enabled = debug.enabled.unwrap_or_default();
+    let mut facilities: Vec<DebugFacility> = debug
+        .facilities
+        .into_iter()
+        .map(|(name, description)| DebugFacility {
+            enabled: enabled.contains(&name),
+            name,
+            description,
+        })
+        .collect();
+    facilities.sort_by(|a, b| a.name.cmp(&b.name));
+    out.emit(&facilities)
+}
+
+/// Turn debug logging for `facilities` on or off.
+pub async fn set(client: &Client, out: &Output, facilities: &[String], on: bool) -> Result<()> {
+    // Syncthing silently ignores unknown names, so catch typos here
+    let known = client.debug().await?.facilities;
+    if let Some(unknown) = facilities.iter().find(|f| !known.contains_key(*f)) {
+        bail!(
+            "Unknown debug facility '{}' (see 'syncthing debug list')",
+            unknown
+        );
+    }
+
+    if on {
+        client.set_debug(facilities, &[]).await?;
+    } else {
+        client.set_debug(&[], facilities).await?;
+    }
+    out.emit(&ActionResult::new(format!(
+        "Debug logging {} for {}",
+        if on { "enabled" } else { "disabled" },
+        facilities.join(", ")
+    )))
+}
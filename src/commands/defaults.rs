@@ -0,0 +1,119 @@
+//! `defaults`: the templates Syncthing fills in new folders and devices
+//! from, whether they are added here, in the GUI, or auto-accepted.
+
+use super::options::{Kind, parse_assignment};
+use crate::output::{DefaultsReport, Output};
+use anyhow::Result;
+use serde_json::{Map, Value, json};
+use syncthing::api::Client;
+
+/// Keys `defaults folder` shows and accepts.
+const FOLDER_KEYS: &[(&str, Kind)] = &[
+    ("path", Kind::Text),
+    (
+        "type",
+        Kind::Choice(&["sendreceive", "sendonly", "receiveonly", "receiveencrypted"]),
+    ),
+    (
+        "versioning",
+        Kind::Choice(&["none", "simple", "trashcan", "staggered", "external"]),
+    ),
+    ("rescanIntervalS", Kind::Int),
+    ("fsWatcherEnabled", Kind::Bool),
+    ("fsWatcherDelayS", Kind::Int),
+    ("ignorePerms", Kind::Bool),
+    ("minDiskFree", Kind::Size),
+    (
+        "order",
+        Kind::Choice(&[
+            "random",
+            "alphabetic",
+            "smallestFirst",
+            "largestFirst",
+            "oldestFirst",
+            "newestFirst",
+        ]),
+    ),
+];
+
+/// Keys `defaults device` shows and accepts.
+const DEVICE_KEYS: &[(&str, Kind)] = &[
+    ("addresses", Kind::List),
+    ("introducer", Kind::Bool),
+    ("autoAcceptFolders", Kind::Bool),
+    (
+        "compression",
+        Kind::Choice(&["metadata", "always", "never"]),
+    ),
+    ("allowedNetworks", Kind::List),
+    ("untrusted", Kind::Bool),
+];
+
+/// Show the folder template, after applying `assignments` if there are any.
+pub async fn folder(client: &Client, out: &Output, assignments: &[String]) -> Result<()> {
+    if !assignments.is_empty() {
+        let patch = patch(FOLDER_KEYS, assignments)?;
+        client.patch_defaults_folder(&patch).await?;
+    }
+    let defaults = client.defaults_folder().await?;
+    out.emit(&report(FOLDER_KEYS, &defaults))
+}
+
+/// Show the device template, after applying `assignments` if there are any.
+pub async fn device(client: &Client, out: &Output, assignments: &[String]) -> Result<()> {
+    if !assignments.is_empty() {
+        let patch = patch(DEVICE_KEYS, assignments)?;
+        client.patch_defaults_device(&patch).await?;
+    }
+    let defaults = client.defaults_device().await?;
+    out.emit(&report(DEVICE_KEYS, &defaults))
+}
+
+fn patch(keys: &[(&str, Kind)], assignments: &[String]) -> Result<Value> {
+    let mut patch = Map::new();
+    for assignment in assignments {
+        let (key, value) = parse_assignment(keys, assignment)?;
+        // Only the type is set here; the other versioning parameters keep
+        // whatever the template has
+        let value = if key == "versioning" {
+            let versioning_type = value.as_str().filter(|&t| t != "none").unwrap_or_default();
+            json!({ "type": versioning_type })
+        } else {
+            value
+        };
+        patch.insert(key, value);
+    }
+    Ok(patch.into())
+}
+
+/// The template's values for `keys`, in that order.
+fn report(keys: &[(&str, Kind)], defaults: &Value) -> DefaultsReport {
+    DefaultsReport(
+        keys.iter()
+            .filter_map(|(key, _)| Some((key.to_string(), defaults.get(*key)?.clone())))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_patch_versioning() {
+        let assignments = ["versioning=none".to_string(), "path=~/Sync".to_string()];
+        assert_eq!(
+            patch(FOLDER_KEYS, &assignments).unwrap(),
+            json!({"versioning": {"type": ""}, "path": "~/Sync"})
+        );
+        assert!(patch(DEVICE_KEYS, &["path=/tmp".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_report_keeps_key_order() {
+        let defaults = json!({"untrusted": false, "introducer": true, "name": ""});
+        let report = report(DEVICE_KEYS, &defaults);
+        let keys: Vec<&String> = report.0.keys().collect();
+        assert_eq!(keys, ["introducer", "untrusted"]);
+    }
+}
@@ -0,0 +1,423 @@
+use super::options::{Kind, parse_assignment};
+use crate::output::{
+    ActionResult, ConnectionInfo, DeviceIdReport, DeviceSummary, DiscoveredDevice,
+    DiscoveryMechanism, DiscoveryReport, Output,
+};
+use crate::prompt::confirm_or_abort;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, json};
+use std::cmp::Reverse;
+use std::time::{Duration, Instant};
+use syncthing::api::Client;
+use syncthing::device_id;
+use syncthing::models::find_device;
+
+/// How long to wait between the two connection samples rates are computed from.
+const RATE_SAMPLE: Duration = Duration::from_secs(1);
+
+pub async fn list(client: &Client, out: &Output, view: &DeviceView) -> Result<()> {
+    let summaries = summaries(client).await?;
+    out.emit(&view.apply(summaries, Utc::now()))
+}
+
+/// Which devices `devices` shows and in what order.
+#[derive(Default)]
+pub struct DeviceView {
+    pub connected: bool,
+    pub offline: bool,
+    /// Only devices that are offline and haven't been seen for this long.
+    pub stale: Option<Duration>,
+    /// Keep the configured order when `None`.
+    pub sort: Option<DeviceSort>,
+}
+
+#[derive(Clone, Copy)]
+pub enum DeviceSort {
+    /// Connected devices first, then most recently seen; never seen last.
+    LastSeen,
+    /// By name, case-insensitively.
+    Name,
+}
+
+impl DeviceView {
+    pub fn apply(&self, mut devices: Vec<DeviceSummary>, now: DateTime<Utc>) -> Vec<DeviceSummary> {
+        if self.connected {
+            devices.retain(|d| d.connected);
+        }
+        if self.offline {
+            devices.retain(|d| !d.connected);
+        }
+        if let Some(stale) = self.stale {
+            let cutoff = now - chrono::Duration::from_std(stale).unwrap_or(chrono::Duration::MAX);
+            devices.retain(|d| !d.connected && last_seen(d).is_none_or(|seen| seen < cutoff));
+        }
+        match self.sort {
+            Some(DeviceSort::LastSeen) => {
+                devices.sort_by_key(|d| Reverse((d.connected, last_seen(d))))
+            }
+            Some(DeviceSort::Name) => devices.sort_by_key(|d| d.name.to_lowercase()),
+            None => {}
+        }
+        devices
+    }
+}
+
+/// When a device was last seen; `None` if never (Syncthing reports the Unix
+/// epoch then).
+fn last_seen(device: &DeviceSummary) -> Option<DateTime<Utc>> {
+    let seen = DateTime::parse_from_rfc3339(device.last_seen.as_deref()?).ok()?;
+    Some(seen.with_timezone(&Utc)).filter(|t| t.timestamp() > 0)
+}
+
+async fn summaries(client: &Client) -> Result<Vec<DeviceSummary>> {
+    let devices = client.config_devices().await?;
+    let stats = client.stats_device().await?;
+
+    // Sample the byte counters twice; skip the wait when nothing is connected
+    let before = client.connections().await?;
+    let start = Instant::now();
+    let after = if before.connections.values().any(|c| c.connected) {
+        tokio::time::sleep(RATE_SAMPLE).await;
+        client.connections().await?
+    } else {
+        before.clone()
+    };
+    let elapsed = start.elapsed().as_secs_f64();
+    let rate = |now: u64, then: u64| {
+        if elapsed > 0.0 {
+            (now.saturating_sub(then) as f64 / elapsed) as u64
+        } else {
+            0
+        }
+    };
+
+    let summaries: Vec<DeviceSummary> = devices
+        .iter()
+        .map(|device| {
+            let now = after.connections.get(&device.device_id);
+            let then = before.connections.get(&device.device_id);
+            let (in_rate, out_rate) = match (now, then) {
+                (Some(now), Some(then)) => (
+                    rate(now.in_bytes_total, then.in_bytes_total),
+                    rate(now.out_bytes_total, then.out_bytes_total),
+                ),
+                _ => (0, 0),
+            };
+            DeviceSummary {
+                id: device.device_id.clone(),
+                name: device.display_name().to_string(),
+                connected: now.is_some_and(|c| c.connected),
+                last_seen: stats
+                    .get(&device.device_id)
+                    .map(|s| s.last_seen.clone())
+                    .filter(|t| !t.is_empty()),
+                in_rate,
+                out_rate,
+                in_bytes_total: now.map_or(0, |c| c.in_bytes_total),
+                out_bytes_total: now.map_or(0, |c| c.out_bytes_total),
+            }
+        })
+        .collect();
+
+    Ok(summaries)
+}
+
+/// Connection details for every remote device.
+pub async fn connections(client: &Client, out: &Output) -> Result<()> {
+    let devices = client.config_devices().await?;
+    let connections = client.connections().await?;
+    let my_id = client.status().await?.my_id;
+
+    let infos: Vec<ConnectionInfo> = devices
+        .iter()
+        .filter(|d| d.device_id != my_id)
+        .map(|device| {
+            let conn = connections
+                .connections
+                .get(&device.device_id)
+                .cloned()
+                .unwrap_or_default();
+            ConnectionInfo {
+                id: device.device_id.clone(),
+                name: device.display_name().to_string(),
+                connected: conn.connected,
+                address: conn.address,
+                connection_type: conn.connection_type,
+                crypto: conn.crypto,
+                is_local: conn.is_local,
+                compression: device.compression.clone(),
+                client_version: conn.client_version,
+                started_at: Some(conn.started_at).filter(|t| !t.is_empty()),
+            }
+        })
+        .collect();
+
+    out.emit(&infos)
+}
+
+/// Where each remote device was discovered, next to its configured and
+/// connected addresses and the health of each discovery mechanism.
+pub async fn discovery(client: &Client, out: &Output) -> Result<()> {
+    let status = client.status().await?;
+    let devices = client.config_devices().await?;
+    let mut discovered = client.discovery().await?;
+    let connections = client.connections().await?;
+
+    let mut mechanisms: Vec<DiscoveryMechanism> = status
+        .discovery_status
+        .into_iter()
+        .map(|(name, s)| DiscoveryMechanism {
+            name,
+            error: s.error,
+        })
+        .collect();
+    mechanisms.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let devices = devices
+        .into_iter()
+        .filter(|d| d.device_id != status.my_id)
+        .map(|device| DiscoveredDevice {
+            name: device.display_name().to_string(),
+            discovered: discovered
+                .remove(&device.device_id)
+                .map(|entry| entry.addresses)
+                .unwrap_or_default(),
+            connected_address: connections
+                .connections
+                .get(&device.device_id)
+                .filter(|conn| conn.connected)
+                .map(|conn| conn.address.clone()),
+            configured: device.addresses,
+            id: device.device_id,
+        })
+        .collect();
+
+    out.emit(&DiscoveryReport {
+        mechanisms,
+        devices,
+    })
+}
+
+/// Validate a device ID locally and with Syncthing, print it in canonical
+/// form, and say whether it is already configured.
+pub async fn check_id(client: &Client, out: &Output, id: &str) -> Result<()> {
+    let id = device_id::normalize(id)?;
+    let id = client.check_device_id(&id).await?;
+    let known_as = client
+        .config_devices()
+        .await?
+        .into_iter()
+        .find(|d| d.device_id == id)
+        .map(|d| d.display_name().to_string());
+    out.emit(&DeviceIdReport { id, known_as })
+}
+
+/// Pause or resume one device, or every remote device when `device` is `None`.
+pub async fn set_paused(
+    client: &Client,
+    out: &Output,
+    device: Option<&str>,
+    paused: bool,
+) -> Result<()> {
+    let devices = client.config_devices().await?;
+    let verb = if paused { "Paused" } else { "Resumed" };
+    let patch = json!({ "paused": paused });
+
+    let result = match device {
+        Some(query) => {
+            let device = find_device(&devices, query)?;
+            client.patch_device(&device.device_id, &patch).await?;
+            ActionResult::new(format!("{} device: {}", verb, device.display_name()))
+        }
+        None => {
+            // The local device is part of the device list but can't be paused
+            let my_id = client.status().await?.my_id;
+            let targets: Vec<_> = devices
+                .iter()
+                .filter(|d| d.device_id != my_id && d.paused != paused)
+                .collect();
+            for device in &targets {
+                client.patch_device(&device.device_id, &patch).await?;
+            }
+            ActionResult::new(format!("{} {} devices", verb, targets.len()))
+        }
+    };
+    out.emit(&result)
+}
+
+/// Per-device settings `device set` accepts.
+const SETTING_KEYS: &[(&str, Kind)] = &[
+    ("introducer", Kind::Bool),
+    ("autoAcceptFolders", Kind::Bool),
+    (
+        "compression",
+        Kind::Choice(&["metadata", "always", "never"]),
+    ),
+    ("allowedNetworks", Kind::List),
+    ("untrusted", Kind::Bool),
+];
+
+/// Apply `key=value` assignments to a device in a single PATCH.
+pub async fn set(client: &Client, out: &Output, query: &str, assignments: &[String]) -> Result<()> {
+    let mut patch = Map::new();
+    for assignment in assignments {
+        let (key, value) = parse_assignment(SETTING_KEYS, assignment)?;
+        patch.insert(key, value);
+    }
+    let devices = client.config_devices().await?;
+    let device = find_device(&devices, query)?;
+
+    let keys: Vec<_> = patch.keys().cloned().collect();
+    client
+        .patch_device(&device.device_id, &patch.into())
+        .await?;
+    out.emit(&ActionResult::new(format!(
+        "Updated {} of device {}",
+        keys.join(", "),
+        device.display_name()
+    )))
+}
+
+/// Remove a device, optionally dropping it from every folder it is shared with.
+pub async fn remove(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    unshare: bool,
+    yes: bool,
+) -> Result<()> {
+    let devices = client.config_devices().await?;
+    let device = find_device(&devices, query)?;
+    let folders = client.config_folders().await?;
+    let shared: Vec<_> = folders
+        .iter()
+        .filter(|f| f.devices.iter().any(|d| d.device_id == device.device_id))
+        .collect();
+
+    let mut question = format!("Remove device {}?", device.display_name());
+    if unshare && !shared.is_empty() {
+        question = format!(
+            "Remove device {} and unshare {} folders?",
+            device.display_name(),
+            shared.len()
+        );
+    }
+    confirm_or_abort(&question, yes || client.dry_run())?;
+
+    let mut unshared = 0;
+    if unshare {
+        // Whatever was shared meanwhile must survive, so start from the
+        // current lists rather than the ones the question was based on
+        let folders = client.config_folders_fresh().await?;
+        let shared = folders
+            .iter()
+            .filter(|f| f.devices.iter().any(|d| d.device_id == device.device_id));
+        for folder in shared {
+            let remaining: Vec<_> = folder
+                .devices
+                .iter()
+                .filter(|d| d.device_id != device.device_id)
+                .collect();
+            client
+                .patch_folder(&folder.id, &json!({ "devices": remaining }))
+                .await?;
+            unshared += 1;
+        }
+    }
+    client.remove_device(&device.device_id).await?;
+
+    let mut message = format!("Removed device: {}", device.display_name());
+    if unshare {
+        message.push_str(&format!(" (unshared from {} folders)", unshared));
+    } else if !shared.is_empty() {
+        message.push_str(&format!(
+            " (still listed in {} folders; use --unshare to remove it there too)",
+            shared.len()
+        ));
+    }
+    out.emit(&ActionResult::new(message))
+}
+
+pub async fn rename(client: &Client, out: &Output, query: &str, name: &str) -> Result<()> {
+    let devices = client.config_devices().await?;
+    let device = find_device(&devices, query)?;
+
+    client
+        .patch_device(&device.device_id, &json!({ "name": name }))
+        .await?;
+    out.emit(&ActionResult::new(format!(
+        "Renamed device {} to {}",
+        device.display_name(),
+        name
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(name: &str, connected: bool, last_seen: &str) -> DeviceSummary {
+        DeviceSummary {
+            id: name.to_uppercase(),
+            name: name.to_string(),
+            connected,
+            last_seen: Some(last_seen.to_string()),
+            in_rate: 0,
+            out_rate: 0,
+            in_bytes_total: 0,
+            out_bytes_total: 0,
+        }
+    }
+
+    #[test]
+    fn test_device_view() {
+        let now = DateTime::parse_from_rfc3339("2024-06-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let devices = || {
+            vec![
+                summary("nas", false, "2024-06-10T11:00:00Z"),
+                summary("Laptop", true, "2024-06-10T12:00:00Z"),
+                summary("old-phone", false, "2024-05-01T00:00:00Z"),
+                summary("ghost", false, "1970-01-01T00:00:00Z"),
+            ]
+        };
+        let names = |view: DeviceView| -> Vec<String> {
+            view.apply(devices(), now)
+                .into_iter()
+                .map(|d| d.name)
+                .collect()
+        };
+
+        assert_eq!(
+            names(DeviceView {
+                connected: true,
+                ..Default::default()
+            }),
+            ["Laptop"]
+        );
+        assert_eq!(
+            names(DeviceView {
+                stale: Some(Duration::from_secs(7 * 24 * 3600)),
+                ..Default::default()
+            }),
+            ["old-phone", "ghost"]
+        );
+        assert_eq!(
+            names(DeviceView {
+                sort: Some(DeviceSort::LastSeen),
+                ..Default::default()
+            }),
+            ["Laptop", "nas", "old-phone", "ghost"]
+        );
+        assert_eq!(
+            names(DeviceView {
+                offline: true,
+                sort: Some(DeviceSort::Name),
+                ..Default::default()
+            }),
+            ["ghost", "nas", "old-phone"]
+        );
+    }
+}
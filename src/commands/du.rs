@@ -0,0 +1,113 @@
+//! `du`: where the synced space goes, per folder and in total, for one
+//! instance or every configured profile.
+
+use crate::output::{DuReport, FolderUsage, Output};
+use anyhow::{Result, bail};
+use futures::stream::{self, StreamExt};
+use syncthing::api::Client;
+use syncthing::config;
+use tokio::task::JoinSet;
+
+pub async fn run(client: &Client, out: &Output) -> Result<()> {
+    out.emit(&DuReport::new(usage(client, None).await?))
+}
+
+/// Sum up every configured profile. Profiles that can't be reached are
+/// reported on stderr and left out of the totals.
+pub async fn all_profiles(out: &Output) -> Result<()> {
+    let cfg = config::load_config()?;
+    if cfg.profiles.is_empty() {
+        bail!("No profiles configured (see 'syncthing config profile add')");
+    }
+
+    let mut tasks = JoinSet::new();
+    for (name, profile) in &cfg.profiles {
+        let options = cfg.client_options(&profile.host, Some(profile));
+        let (name, profile) = (name.clone(), profile.clone());
+        tasks.spawn(async move {
+            let result = match Client::with_options(&profile.api_key, &profile.host, &options) {
+                Ok(client) => usage(&client, Some(&name)).await,
+                Err(e) => Err(e),
+            };
+            (name, result)
+        });
+    }
+
+    let mut folders = Vec::new();
+    for (name, result) in tasks.join_all().await {
+        match result {
+            Ok(usage) => folders.extend(usage),
+            Err(e) => out.info(format!("Skipping profile {}: {:#}", name, e)),
+        }
+    }
+    out.emit(&DuReport::new(folders))
+}
+
+/// Space used by each folder of one instance.
+async fn usage(client: &Client, profile: Option<&str>) -> Result<Vec<FolderUsage>> {
+    let folders = client.config_folders().await?;
+    let usage = stream::iter(folders)
+        .map(|folder| async move {
+            // Paused folders have no status; count them as empty
+            let status = if folder.paused {
+                None
+            } else {
+                client.db_status(&folder.id).await.ok()
+            };
+            let status = status.unwrap_or_default();
+            FolderUsage {
+                profile: profile.map(String::from),
+                id: folder.id.clone(),
+                label: folder.display_name().to_string(),
+                global_bytes: status.global_bytes,
+                local_bytes: status.local_bytes,
+                need_bytes: status.need_bytes,
+            }
+        })
+        .buffered(client.max_connections())
+        .collect()
+        .await;
+    Ok(usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_usage_sorted_with_totals() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"id": "docs", "label": "Docs"},
+                {"id": "photos", "label": "Photos"},
+                {"id": "old", "label": "Old", "paused": true}
+            ])))
+            .mount(&server)
+            .await;
+        for (id, global, local, need) in [("docs", 100, 100, 0), ("photos", 5000, 3000, 2000)] {
+            Mock::given(method("GET"))
+                .and(path("/rest/db/status"))
+                .and(query_param("folder", id))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "globalBytes": global,
+                    "localBytes": local,
+                    "needBytes": need
+                })))
+                .mount(&server)
+                .await;
+        }
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let report = DuReport::new(usage(&client, None).await.unwrap());
+        let ids: Vec<&str> = report.folders.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, ["photos", "docs", "old"]);
+        assert_eq!(report.total.global_bytes, 5100);
+        assert_eq!(report.total.local_bytes, 3100);
+        assert_eq!(report.total.need_bytes, 2000);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::output::{EventSummary, Output};
+use anyhow::Result;
+use std::time::Duration;
+use syncthing::api::Client;
+use syncthing::models::Event;
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+pub fn summarize(event: Event) -> EventSummary {
+    EventSummary {
+        id: event.id,
+        event_type: event.event_type,
+        time: event.time,
+        data: event.data,
+    }
+}
+
+pub async fn recent(client: &Client, out: &Output, limit: u32, types: &[String]) -> Result<()> {
+    let events: Vec<EventSummary> = client
+        .events(None, Some(limit), types)
+        .await?
+        .into_iter()
+        .rev()
+        .take(limit as usize)
+        .map(summarize)
+        .collect();
+    out.emit(&events)
+}
+
+/// Print the last `limit` events, then stream new ones as they arrive.
+/// With `--json`, each event is printed as one line of JSON (JSONL).
+pub async fn follow(client: &Client, out: &Output, limit: u32, types: &[String]) -> Result<()> {
+    let mut since = 0;
+    for event in client.events(None, Some(limit), types).await? {
+        since = event.id;
+        out.emit_line(&summarize(event))?;
+    }
+    stream(client, out, since, types, |event| {
+        out.emit_line(&summarize(event))
+    })
+    .await
+}
+
+/// Hand every event after `since` to `handle` as it arrives, using the
+/// events endpoint's long polling. Reconnects with backoff on errors and
+/// only returns when `handle` fails.
+pub async fn stream(
+    client: &Client,
+    out: &Output,
+    mut since: u64,
+    types: &[String],
+    mut handle: impl FnMut(Event) -> Result<()>,
+) -> Result<()> {
+    let mut delay = Duration::from_secs(1);
+    loop {
+        match client.events(Some(since), None, types).await {
+            Ok(events) => {
+                delay = Duration::from_secs(1);
+                for event in events {
+                    since = event.id;
+                    handle(event)?;
+                }
+            }
+            Err(e) => {
+                out.info(format!(
+                    "Lost connection ({:#}); retrying in {}s",
+                    e,
+                    delay.as_secs()
+                ));
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+
+                // Event IDs restart from 1 when Syncthing restarts
+                if let Ok(latest) = client.events(None, Some(1), &[]).await
+                    && let Some(event) = latest.last()
+                    && event.id < since
+                {
+                    since = 0;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,465 @@
+//! Prometheus exporter: polls the API on an interval and serves the latest
+//! snapshot at `/metrics`.
+
+use crate::output::Output;
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+use syncthing::api::Client;
+use syncthing::models::{
+    Completion, Connections, DbStatus, DeviceConfig, FolderConfig, FolderStats, SystemStatus,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+
+/// Everything one scrape is rendered from.
+struct Snapshot {
+    status: SystemStatus,
+    completion: Completion,
+    devices: Vec<DeviceConfig>,
+    connections: Connections,
+    folders: Vec<(FolderConfig, Option<DbStatus>)>,
+    folder_stats: HashMap<String, FolderStats>,
+}
+
+pub async fn run(client: Client, out: &Output, listen: &str, interval: u64) -> Result<()> {
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {}", listen))?;
+    out.info(format!("Serving metrics on http://{}/metrics", listen));
+
+    let metrics = Arc::new(RwLock::new(String::new()));
+
+    let poller = {
+        let metrics = Arc::clone(&metrics);
+        async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+            loop {
+                ticker.tick().await;
+                let rendered = match collect(&client).await {
+                    Ok(snapshot) => render_metrics(&snapshot),
+                    Err(e) => {
+                        eprintln!("Failed to poll syncthing: {:#}", e);
+                        "# TYPE syncthing_up gauge\nsyncthing_up 0\n".to_string()
+                    }
+                };
+                *metrics.write().await = rendered;
+            }
+        }
+    };
+    tokio::spawn(poller);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let metrics = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            if let Err(e) = serve(stream, &metrics).await {
+                eprintln!("Failed to serve request: {:#}", e);
+            }
+        });
+    }
+}
+
+async fn collect(client: &Client) -> Result<Snapshot> {
+    let status = client.status().await?;
+    let completion = client.db_completion().await?;
+    let devices = client.config_devices().await?;
+    let connections = client.connections().await?;
+    let folder_stats = client.stats_folder().await?;
+
+    let mut folders = Vec::new();
+    for folder in client.config_folders().await? {
+        let db_status = if folder.paused {
+            None
+        } else {
+            client.db_status(&folder.id).await.ok()
+        };
+        folders.push((folder, db_status));
+    }
+
+    Ok(Snapshot {
+        status,
+        completion,
+        devices,
+        connections,
+        folders,
+        folder_stats,
+    })
+}
+
+async fn serve(mut stream: TcpStream, metrics: &RwLock<String>) -> Result<()> {
+    // Only the request line matters; the rest of the request is ignored
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, content_type, body) = if path == "/metrics" {
+        (
+            "200 OK",
+            "text/plain; version=0.0.4",
+            metrics.read().await.clone(),
+        )
+    } else {
+        (
+            "404 Not Found",
+            "text/plain",
+            "Metrics are served at /metrics\n".to_string(),
+        )
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Name, help text and accessor for a per-folder gauge.
+type FolderGauge = (&'static str, &'static str, fn(&DbStatus) -> u64);
+
+/// Builds the Prometheus text exposition format.
+struct Metrics {
+    text: String,
+}
+
+impl Metrics {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {} {}", name, help);
+        let _ = writeln!(self.text, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+            .collect();
+        if labels.is_empty() {
+            let _ = writeln!(self.text, "{} {}", name, value);
+        } else {
+            let _ = writeln!(self.text, "{}{{{}}} {}", name, labels.join(","), value);
+        }
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn render_metrics(snapshot: &Snapshot) -> String {
+    let mut m = Metrics {
+        text: String::new(),
+    };
+
+    m.family(
+        "syncthing_up",
+        "gauge",
+        "Whether the last poll of the API succeeded.",
+    );
+    m.sample("syncthing_up", &[], 1.0);
+
+    let status = &snapshot.status;
+    m.family(
+        "syncthing_uptime_seconds",
+        "gauge",
+        "Seconds since Syncthing started.",
+    );
+    m.sample("syncthing_uptime_seconds", &[], status.uptime as f64);
+    m.family(
+        "syncthing_memory_alloc_bytes",
+        "gauge",
+        "Bytes allocated by the Go runtime.",
+    );
+    m.sample("syncthing_memory_alloc_bytes", &[], status.alloc as f64);
+    m.family(
+        "syncthing_memory_sys_bytes",
+        "gauge",
+        "Bytes obtained from the OS.",
+    );
+    m.sample("syncthing_memory_sys_bytes", &[], status.sys as f64);
+    m.family("syncthing_goroutines", "gauge", "Number of goroutines.");
+    m.sample("syncthing_goroutines", &[], status.goroutines as f64);
+
+    let completion = &snapshot.completion;
+    m.family(
+        "syncthing_completion_percent",
+        "gauge",
+        "Overall sync completion.",
+    );
+    m.sample("syncthing_completion_percent", &[], completion.completion);
+    m.family(
+        "syncthing_global_bytes",
+        "gauge",
+        "Total bytes across all folders.",
+    );
+    m.sample(
+        "syncthing_global_bytes",
+        &[],
+        completion.global_bytes as f64,
+    );
+    m.family(
+        "syncthing_need_bytes",
+        "gauge",
+        "Bytes still needed across all folders.",
+    );
+    m.sample("syncthing_need_bytes", &[], completion.need_bytes as f64);
+
+    let total = &snapshot.connections.total;
+    m.family(
+        "syncthing_in_bytes_total",
+        "counter",
+        "Bytes received from all devices.",
+    );
+    m.sample("syncthing_in_bytes_total", &[], total.in_bytes_total as f64);
+    m.family(
+        "syncthing_out_bytes_total",
+        "counter",
+        "Bytes sent to all devices.",
+    );
+    m.sample(
+        "syncthing_out_bytes_total",
+        &[],
+        total.out_bytes_total as f64,
+    );
+
+    let remote_devices: Vec<_> = snapshot
+        .devices
+        .iter()
+        .filter(|d| d.device_id != status.my_id)
+        .collect();
+    m.family(
+        "syncthing_device_connected",
+        "gauge",
+        "Whether a device is connected.",
+    );
+    for device in &remote_devices {
+        let conn = snapshot.connections.connections.get(&device.device_id);
+        let labels = [
+            ("device", device.device_id.as_str()),
+            ("name", device.display_name()),
+        ];
+        m.sample(
+            "syncthing_device_connected",
+            &labels,
+            conn.is_some_and(|c| c.connected) as u8 as f64,
+        );
+    }
+    m.family(
+        "syncthing_device_in_bytes_total",
+        "counter",
+        "Bytes received from a device.",
+    );
+    for device in &remote_devices {
+        if let Some(conn) = snapshot.connections.connections.get(&device.device_id) {
+            let labels = [
+                ("device", device.device_id.as_str()),
+                ("name", device.display_name()),
+            ];
+            m.sample(
+                "syncthing_device_in_bytes_total",
+                &labels,
+                conn.in_bytes_total as f64,
+            );
+        }
+    }
+    m.family(
+        "syncthing_device_out_bytes_total",
+        "counter",
+        "Bytes sent to a device.",
+    );
+    for device in &remote_devices {
+        if let Some(conn) = snapshot.connections.connections.get(&device.device_id) {
+            let labels = [
+                ("device", device.device_id.as_str()),
+                ("name", device.display_name()),
+            ];
+            m.sample(
+                "syncthing_device_out_bytes_total",
+                &labels,
+                conn.out_bytes_total as f64,
+            );
+        }
+    }
+
+    m.family(
+        "syncthing_folder_paused",
+        "gauge",
+        "Whether a folder is paused.",
+    );
+    for (folder, _) in &snapshot.folders {
+        let labels = [
+            ("folder", folder.id.as_str()),
+            ("label", folder.display_name()),
+        ];
+        m.sample(
+            "syncthing_folder_paused",
+            &labels,
+            folder.paused as u8 as f64,
+        );
+    }
+
+    let gauges: [FolderGauge; 6] = [
+        (
+            "syncthing_folder_global_bytes",
+            "Bytes in the global state of a folder.",
+            |s| s.global_bytes,
+        ),
+        (
+            "syncthing_folder_local_bytes",
+            "Bytes in the local state of a folder.",
+            |s| s.local_bytes,
+        ),
+        (
+            "syncthing_folder_need_bytes",
+            "Bytes a folder still needs.",
+            |s| s.need_bytes,
+        ),
+        (
+            "syncthing_folder_need_files",
+            "Files a folder still needs.",
+            |s| s.need_files,
+        ),
+        (
+            "syncthing_folder_errors",
+            "Number of sync errors in a folder.",
+            |s| s.errors,
+        ),
+        (
+            "syncthing_folder_sequence",
+            "Local sequence number of a folder.",
+            |s| s.sequence,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        m.family(name, "gauge", help);
+        for (folder, db_status) in &snapshot.folders {
+            if let Some(db_status) = db_status {
+                let labels = [
+                    ("folder", folder.id.as_str()),
+                    ("label", folder.display_name()),
+                ];
+                m.sample(name, &labels, value(db_status) as f64);
+            }
+        }
+    }
+
+    m.family(
+        "syncthing_folder_state",
+        "gauge",
+        "Current folder state (1 for the active state).",
+    );
+    for (folder, db_status) in &snapshot.folders {
+        if let Some(db_status) = db_status {
+            let labels = [
+                ("folder", folder.id.as_str()),
+                ("label", folder.display_name()),
+                ("state", db_status.state.as_str()),
+            ];
+            m.sample("syncthing_folder_state", &labels, 1.0);
+        }
+    }
+
+    m.family(
+        "syncthing_folder_last_scan_timestamp_seconds",
+        "gauge",
+        "Unix time of a folder's last scan.",
+    );
+    for (folder, _) in &snapshot.folders {
+        let last_scan = snapshot
+            .folder_stats
+            .get(&folder.id)
+            .and_then(|s| DateTime::parse_from_rfc3339(&s.last_scan).ok());
+        if let Some(last_scan) = last_scan {
+            let labels = [
+                ("folder", folder.id.as_str()),
+                ("label", folder.display_name()),
+            ];
+            m.sample(
+                "syncthing_folder_last_scan_timestamp_seconds",
+                &labels,
+                last_scan.timestamp() as f64,
+            );
+        }
+    }
+
+    m.text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syncthing::models::Connection;
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label(r#"a "b" \c"#), r#"a \"b\" \\c"#);
+    }
+
+    #[test]
+    fn test_render_metrics() {
+        let snapshot = Snapshot {
+            status: SystemStatus {
+                my_id: "SELF".to_string(),
+                uptime: 60,
+                ..Default::default()
+            },
+            completion: Completion::default(),
+            devices: vec![
+                DeviceConfig {
+                    device_id: "SELF".to_string(),
+                    ..Default::default()
+                },
+                DeviceConfig {
+                    device_id: "PEER".to_string(),
+                    name: "nas".to_string(),
+                    ..Default::default()
+                },
+            ],
+            connections: Connections {
+                connections: HashMap::from([(
+                    "PEER".to_string(),
+                    Connection {
+                        connected: true,
+                        in_bytes_total: 42,
+                        ..Default::default()
+                    },
+                )]),
+                ..Default::default()
+            },
+            folders: vec![(
+                FolderConfig {
+                    id: "docs".to_string(),
+                    label: "Documents".to_string(),
+                    ..Default::default()
+                },
+                Some(DbStatus {
+                    state: "idle".to_string(),
+                    need_bytes: 7,
+                    ..Default::default()
+                }),
+            )],
+            folder_stats: HashMap::new(),
+        };
+
+        let text = render_metrics(&snapshot);
+        assert!(text.contains("syncthing_uptime_seconds 60\n"));
+        assert!(text.contains("syncthing_device_connected{device=\"PEER\",name=\"nas\"} 1\n"));
+        assert!(!text.contains("device=\"SELF\""));
+        assert!(
+            text.contains("syncthing_folder_need_bytes{folder=\"docs\",label=\"Documents\"} 7\n")
+        );
+        assert!(text.contains("state=\"idle\"} 1\n"));
+    }
+}
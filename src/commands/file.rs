@@ -0,0 +1,98 @@
+use crate::output::{ActionResult, FileReport, Output};
+use anyhow::{Result, bail};
+use syncthing::api::Client;
+use syncthing::models::find_folder;
+
+pub async fn file(client: &Client, out: &Output, query: &str, path: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let info = client.db_file(&folder.id, path).await?;
+    let devices = client.config_devices().await?;
+
+    let available_on = info
+        .availability
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| {
+            devices
+                .iter()
+                .find(|d| d.device_id == a.id)
+                .map(|d| d.display_name().to_string())
+                .unwrap_or(a.id)
+        })
+        .collect();
+
+    let report = FileReport {
+        folder: folder.id.clone(),
+        path: path.to_string(),
+        local: info.local,
+        global: info.global,
+        available_on,
+    };
+    out.emit(&report)
+}
+
+/// Download `path` before the rest of the folder's queue.
+pub async fn prio(client: &Client, out: &Output, query: &str, path: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let need = client.db_prio(&folder.id, path).await?;
+
+    // Syncthing ignores files that aren't queued, so check it took effect
+    let path = path.trim_start_matches('/');
+    let in_progress = need.progress.iter().any(|f| f.name == path);
+    let queued = need.queued.first().is_some_and(|f| f.name == path);
+    if !in_progress && !queued {
+        bail!(
+            "{} is not waiting to be downloaded in folder {}",
+            path,
+            folder.display_name()
+        );
+    }
+    let message = if in_progress {
+        format!("{} is already downloading", path)
+    } else {
+        format!(
+            "Moved {} to the front of the queue of folder {}",
+            path,
+            folder.display_name()
+        )
+    };
+    out.emit(&ActionResult::new(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_prio() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"id": "docs", "label": "Docs"}])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/db/prio"))
+            .and(query_param("folder", "docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "progress": [{"name": "movie.mkv", "size": 4000000000u64}],
+                "queued": [{"name": "report.pdf", "size": 1200}],
+                "rest": []
+            })))
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+        let out = Output::default();
+
+        prio(&client, &out, "Docs", "report.pdf").await.unwrap();
+        prio(&client, &out, "Docs", "movie.mkv").await.unwrap();
+        assert!(prio(&client, &out, "Docs", "done.txt").await.is_err());
+    }
+}
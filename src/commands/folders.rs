@@ -0,0 +1,1459 @@
+use super::disk_space_warning;
+use super::options::{Kind, parse_assignment};
+use crate::output::{
+    ActionResult, DeviceLag, FolderPathReport, FolderStatsRow, FolderStatusReport, FolderSummary,
+    LagReport, LastFileInfo, LocalChangedReport, NeedReport, NeededFile, Output, VersioningReport,
+    format_bytes, short_id,
+};
+use crate::prompt::confirm_or_abort;
+use anyhow::{Context, Result, bail};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde_json::{Map, Value, json};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use syncthing::api::Client;
+use syncthing::models::{FolderDevice, Need, NotFound, find_device, find_folder};
+
+pub async fn list(client: &Client, out: &Output, view: &FolderView) -> Result<()> {
+    out.emit(&view.apply(summaries(client).await?))
+}
+
+/// Which folders `folders` shows and in what order. Without any filter flag
+/// set every folder is shown; with several, folders matching any of them.
+#[derive(Default)]
+pub struct FolderView {
+    pub paused: bool,
+    pub syncing: bool,
+    pub errors: bool,
+    pub idle: bool,
+    /// Keep the configured order when `None`.
+    pub sort: Option<FolderSort>,
+}
+
+#[derive(Clone, Copy)]
+pub enum FolderSort {
+    /// Most bytes still needed first.
+    Need,
+    /// Largest folders (global size) first.
+    Size,
+    /// By label, case-insensitively.
+    Name,
+}
+
+impl FolderView {
+    pub fn apply(&self, mut folders: Vec<FolderSummary>) -> Vec<FolderSummary> {
+        if self.paused || self.syncing || self.errors || self.idle {
+            folders.retain(|f| self.matches(f));
+        }
+        match self.sort {
+            Some(FolderSort::Need) => folders.sort_by_key(|f| std::cmp::Reverse(f.need_bytes)),
+            Some(FolderSort::Size) => folders.sort_by_key(|f| std::cmp::Reverse(f.global_bytes)),
+            Some(FolderSort::Name) => folders.sort_by_key(|f| f.label.to_lowercase()),
+            None => {}
+        }
+        folders
+    }
+
+    fn matches(&self, folder: &FolderSummary) -> bool {
+        let state = folder.state.as_deref().unwrap_or_default();
+        (self.paused && folder.paused)
+            // Includes sync-preparing and sync-waiting
+            || (self.syncing && state.starts_with("sync"))
+            || (self.errors && (folder.errors > 0 || state == "error"))
+            || (self.idle && state == "idle")
+    }
+}
+
+pub async fn summaries(client: &Client) -> Result<Vec<FolderSummary>> {
+    let folders = client.config_folders().await?;
+
+    // Fetch statuses concurrently, keeping the configured folder order
+    let summaries = stream::iter(folders)
+        .map(|folder| async move {
+            let mut summary = FolderSummary {
+                id: folder.id.clone(),
+                label: folder.display_name().to_string(),
+                paused: folder.paused,
+                state: None,
+                need_files: 0,
+                need_bytes: 0,
+                global_bytes: 0,
+                errors: 0,
+                disk_warning: None,
+            };
+
+            // Get sync status for this folder
+            if !folder.paused
+                && let Ok(status) = client.db_status(&folder.id).await
+            {
+                summary.state = Some(status.state);
+                summary.need_files = status.need_files;
+                summary.need_bytes = status.need_bytes;
+                summary.global_bytes = status.global_bytes;
+                summary.errors = status.errors;
+                summary.disk_warning = disk_space_warning(&folder, &status.error);
+            }
+
+            summary
+        })
+        .buffered(client.max_connections())
+        .collect()
+        .await;
+
+    Ok(summaries)
+}
+
+/// Last scan and last received file per folder.
+pub async fn stats(client: &Client, out: &Output) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let mut stats = client.stats_folder().await?;
+
+    let rows: Vec<FolderStatsRow> = folders
+        .iter()
+        .map(|folder| {
+            let stats = stats.remove(&folder.id).unwrap_or_default();
+            FolderStatsRow {
+                id: folder.id.clone(),
+                label: folder.display_name().to_string(),
+                last_scan: known_time(stats.last_scan),
+                last_file: known_time(stats.last_file.at)
+                    .filter(|_| !stats.last_file.filename.is_empty())
+                    .map(|at| LastFileInfo {
+                        path: stats.last_file.filename,
+                        at,
+                        deleted: stats.last_file.deleted,
+                    }),
+            }
+        })
+        .collect();
+
+    out.emit(&rows)
+}
+
+/// `None` for an empty timestamp or Go's zero time, which Syncthing reports
+/// for things that never happened.
+fn known_time(timestamp: String) -> Option<String> {
+    Some(timestamp).filter(|t| !t.is_empty() && !t.starts_with("0001-"))
+}
+
+/// Sync status of one folder, by ID, label or path.
+pub async fn info(client: &Client, out: &Output, query: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let status = client.db_status(&folder.id).await?;
+    out.emit(&FolderStatusReport {
+        id: folder.id.clone(),
+        label: folder.display_name().to_string(),
+        status,
+    })
+}
+
+/// Pause or resume one folder (by ID or label), or every folder when `folder` is `None`.
+pub async fn set_paused(
+    client: &Client,
+    out: &Output,
+    folder: Option<&str>,
+    paused: bool,
+) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let verb = if paused { "Paused" } else { "Resumed" };
+    let patch = json!({ "paused": paused });
+
+    let result = match folder {
+        Some(query) => {
+            let folder = find_folder(&folders, query)?;
+            client.patch_folder(&folder.id, &patch).await?;
+            ActionResult::new(format!("{} folder: {}", verb, folder.display_name()))
+        }
+        None => {
+            let targets: Vec<_> = folders.iter().filter(|f| f.paused != paused).collect();
+            for folder in &targets {
+                client.patch_folder(&folder.id, &patch).await?;
+            }
+            ActionResult::new(format!("{} {} folders", verb, targets.len()))
+        }
+    };
+    out.emit(&result)
+}
+
+/// Create a folder from a pending offer, shared back with the offering device.
+/// A folder that is already configured is shared with the device instead,
+/// keeping its path and settings.
+pub async fn accept(
+    client: &Client,
+    out: &Output,
+    folder_id: &str,
+    from: Option<&str>,
+    path: Option<&str>,
+) -> Result<()> {
+    let pending = client.pending_folders().await?;
+    let Some(offer) = pending.get(folder_id) else {
+        return Err(NotFound(format!("No pending offer for folder '{}'", folder_id)).into());
+    };
+
+    let device_id = match from {
+        Some(query) => {
+            let devices = client.config_devices().await?;
+            let device = find_device(&devices, query)?;
+            if !offer.offered_by.contains_key(&device.device_id) {
+                bail!(
+                    "Folder '{}' is not offered by {}",
+                    folder_id,
+                    device.display_name()
+                );
+            }
+            device.device_id.clone()
+        }
+        None => {
+            let mut offering: Vec<_> = offer.offered_by.keys().collect();
+            if offering.is_empty() {
+                return Err(
+                    NotFound(format!("No device is offering folder '{}'", folder_id)).into(),
+                );
+            }
+            if offering.len() > 1 {
+                offering.sort();
+                bail!(
+                    "Folder '{}' is offered by several devices ({}); pick one with --from",
+                    folder_id,
+                    offering
+                        .iter()
+                        .map(|id| short_id(id))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            offering[0].clone()
+        }
+    };
+
+    // Posting a folder that exists would replace its whole config
+    let folders = client.config_folders_fresh().await?;
+    if let Some(existing) = folders.iter().find(|f| f.id == folder_id) {
+        if let Some(path) = path
+            && Path::new(path) != Path::new(&existing.path)
+        {
+            bail!(
+                "Folder {} already exists at {}; accept it without --path",
+                existing.display_name(),
+                existing.path
+            );
+        }
+        let mut devices = existing.devices.clone();
+        if !devices.iter().any(|d| d.device_id == device_id) {
+            devices.push(FolderDevice {
+                device_id: device_id.clone(),
+                ..Default::default()
+            });
+            client
+                .patch_folder(&existing.id, &json!({ "devices": devices }))
+                .await?;
+        }
+        return out.emit(&ActionResult::new(format!(
+            "Shared existing folder {} with {}",
+            existing.display_name(),
+            short_id(&device_id)
+        )));
+    }
+
+    let Some(path) = path else {
+        bail!(
+            "Folder '{}' is new; give a --path to store it at",
+            folder_id
+        );
+    };
+    let details = &offer.offered_by[&device_id];
+    let mut folder = json!({
+        "id": folder_id,
+        "label": details.label,
+        "path": path,
+        "devices": [{ "deviceID": device_id }],
+    });
+    if details.receive_encrypted {
+        folder["type"] = json!("receiveencrypted");
+    }
+
+    client.add_folder(&folder).await?;
+
+    let label = if details.label.is_empty() {
+        folder_id
+    } else {
+        &details.label
+    };
+    out.emit(&ActionResult::new(format!(
+        "Accepted folder {} from {} at {}",
+        label,
+        short_id(&device_id),
+        path
+    )))
+}
+
+/// Arguments for [`add`].
+pub struct NewFolder {
+    pub id: String,
+    pub path: String,
+    pub label: Option<String>,
+    pub folder_type: &'static str,
+    /// Devices to share with, as IDs, short IDs, or names.
+    pub share: Vec<String>,
+}
+
+pub async fn add(client: &Client, out: &Output, folder: NewFolder) -> Result<()> {
+    let existing = client.config_folders().await?;
+    if existing.iter().any(|f| f.id == folder.id) {
+        bail!("Folder '{}' already exists", folder.id);
+    }
+
+    let devices = client.config_devices().await?;
+    let shared_with = folder
+        .share
+        .iter()
+        .map(|query| find_device(&devices, query).map(|d| json!({ "deviceID": d.device_id })))
+        .collect::<Result<Vec<_>>>()?;
+
+    let label = folder.label.as_deref().unwrap_or(&folder.id);
+    let config = json!({
+        "id": folder.id,
+        "label": label,
+        "path": folder.path,
+        "type": folder.folder_type,
+        "devices": shared_with,
+    });
+    client.add_folder(&config).await?;
+
+    out.emit(&ActionResult::new(format!(
+        "Added folder {} at {} (shared with {} devices)",
+        label,
+        folder.path,
+        shared_with.len()
+    )))
+}
+
+pub async fn remove(client: &Client, out: &Output, query: &str, yes: bool) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+
+    let question = format!(
+        "Remove folder {} ({}) from the config? Files on disk are kept.",
+        folder.display_name(),
+        folder.path
+    );
+    confirm_or_abort(&question, yes || client.dry_run())?;
+
+    client.remove_folder(&folder.id).await?;
+    out.emit(&ActionResult::new(format!(
+        "Removed folder: {}",
+        folder.display_name()
+    )))
+}
+
+/// Add (`share == true`) or remove devices from a folder's device list.
+pub async fn share(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    device_queries: &[String],
+    share: bool,
+) -> Result<()> {
+    let folders = client.config_folders_fresh().await?;
+    let folder = find_folder(&folders, query)?;
+    let devices = client.config_devices().await?;
+    let targets = device_queries
+        .iter()
+        .map(|q| find_device(&devices, q))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut folder_devices = folder.devices.clone();
+    let mut changed = Vec::new();
+    for device in targets {
+        let position = folder_devices
+            .iter()
+            .position(|d| d.device_id == device.device_id);
+        match (share, position) {
+            (true, None) => folder_devices.push(FolderDevice {
+                device_id: device.device_id.clone(),
+                ..Default::default()
+            }),
+            (false, Some(i)) => {
+                folder_devices.remove(i);
+            }
+            _ => continue,
+        }
+        changed.push(device.display_name());
+    }
+
+    let message = if changed.is_empty() {
+        format!("Folder {} unchanged", folder.display_name())
+    } else {
+        client
+            .patch_folder(&folder.id, &json!({ "devices": folder_devices }))
+            .await?;
+        let (verb, preposition) = if share {
+            ("Shared", "with")
+        } else {
+            ("Unshared", "from")
+        };
+        format!(
+            "{} folder {} {} {}",
+            verb,
+            folder.display_name(),
+            preposition,
+            changed.join(", ")
+        )
+    };
+    out.emit(&ActionResult::new(message))
+}
+
+/// Set the password the folder is encrypted with when sent to `device`, so
+/// that device stores it without being able to read it. An empty password
+/// makes the device trusted again.
+pub async fn set_password(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    device_query: &str,
+    password: &str,
+) -> Result<()> {
+    let folders = client.config_folders_fresh().await?;
+    let folder = find_folder(&folders, query)?;
+    let devices = client.config_devices().await?;
+    let device = find_device(&devices, device_query)?;
+
+    let mut folder_devices = folder.devices.clone();
+    let Some(entry) = folder_devices
+        .iter_mut()
+        .find(|d| d.device_id == device.device_id)
+    else {
+        bail!(
+            "Folder {} is not shared with {} (see 'folder share')",
+            folder.display_name(),
+            device.display_name()
+        );
+    };
+    entry.encryption_password = password.to_string();
+    client
+        .patch_folder(&folder.id, &json!({ "devices": folder_devices }))
+        .await?;
+
+    let message = if password.is_empty() {
+        format!(
+            "Cleared encryption password of folder {} for {}",
+            folder.display_name(),
+            device.display_name()
+        )
+    } else {
+        format!(
+            "Set encryption password of folder {} for {}",
+            folder.display_name(),
+            device.display_name()
+        )
+    };
+    out.emit(&ActionResult::new(message))
+}
+
+/// List files changed locally in a receive-only folder.
+pub async fn local_changed(client: &Client, out: &Output, query: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let changed = client.db_local_changed(&folder.id).await?;
+
+    let report = LocalChangedReport {
+        folder: folder.id.clone(),
+        files: changed.files,
+    };
+    out.emit(&report)
+}
+
+/// Files the folder still has to pull, in download order or, with `top`,
+/// the largest first.
+pub async fn need(client: &Client, out: &Output, query: &str, top: Option<usize>) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let need = client.db_need_all(&folder.id).await?;
+    out.emit(&need_report(&folder.id, need, top))
+}
+
+fn need_report(folder: &str, need: Need, top: Option<usize>) -> NeedReport {
+    let queues = [
+        ("downloading", need.progress),
+        ("queued", need.queued),
+        ("", need.rest),
+    ];
+    let mut files: Vec<NeededFile> = queues
+        .into_iter()
+        .flat_map(|(queue, files)| {
+            files
+                .into_iter()
+                .map(move |file| NeededFile { queue, file })
+        })
+        .collect();
+    let total_files = files.len();
+    let total_bytes = files.iter().map(|f| f.file.size).sum();
+    if let Some(top) = top {
+        files.sort_by_key(|f| std::cmp::Reverse(f.file.size));
+        files.truncate(top);
+    }
+    NeedReport {
+        folder: folder.to_string(),
+        files,
+        total_files,
+        total_bytes,
+    }
+}
+
+/// How far each device sharing the folder is from having all of it, the
+/// furthest behind first.
+pub async fn lag(client: &Client, out: &Output, query: &str) -> Result<()> {
+    out.emit(&lag_report(client, query).await?)
+}
+
+async fn lag_report(client: &Client, query: &str) -> Result<LagReport> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let devices = client.config_devices().await?;
+    let connections = client.connections().await?.connections;
+    let my_id = client.status().await?.my_id;
+
+    let shared: Vec<&str> = folder
+        .devices
+        .iter()
+        .map(|d| d.device_id.as_str())
+        .filter(|&id| id != my_id)
+        .collect();
+    let (devices, connections) = (&devices, &connections);
+    let mut lags: Vec<DeviceLag> = stream::iter(shared)
+        .map(|id| async move {
+            let completion = client.db_completion_for(&folder.id, id).await?;
+            Ok::<_, anyhow::Error>(DeviceLag {
+                id: id.to_string(),
+                name: devices.iter().find(|d| d.device_id == id).map_or_else(
+                    || short_id(id).to_string(),
+                    |d| d.display_name().to_string(),
+                ),
+                connected: connections.get(id).is_some_and(|c| c.connected),
+                completion: completion.completion,
+                need_bytes: completion.need_bytes,
+                need_items: completion.need_items,
+            })
+        })
+        .buffered(client.max_connections())
+        .try_collect()
+        .await?;
+    lags.sort_by(|a, b| {
+        (b.need_bytes, b.need_items)
+            .cmp(&(a.need_bytes, a.need_items))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(LagReport {
+        folder: folder.display_name().to_string(),
+        devices: lags,
+    })
+}
+
+/// Open the folder's directory in the local file manager. With `--quiet`
+/// the path is only printed, e.g. for `cd "$(syncthing -q open docs)"`.
+pub async fn open(client: &Client, out: &Output, query: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let path = folder.local_path();
+    if !path.is_dir() {
+        bail!(
+            "{} does not exist on this machine; is Syncthing running elsewhere?",
+            path.display()
+        );
+    }
+
+    if !out.quiet {
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(windows) {
+            "explorer"
+        } else {
+            "xdg-open"
+        };
+        Command::new(opener)
+            .arg(&path)
+            .spawn()
+            .with_context(|| format!("Failed to run {}", opener))?;
+    }
+    out.emit(&FolderPathReport {
+        folder: folder.id.clone(),
+        path: path.display().to_string(),
+    })
+}
+
+/// Switch a folder between send-receive, send-only and receive-only, warning
+/// about what happens to changes that are still pending.
+pub async fn set_type(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    new_type: &str,
+    yes: bool,
+) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let old_type = folder.folder_type.as_str();
+    if old_type == new_type {
+        return out.emit(&ActionResult::new(format!(
+            "Folder {} is already {}",
+            folder.display_name(),
+            new_type
+        )));
+    }
+    if old_type == "receiveencrypted" || new_type == "receiveencrypted" {
+        bail!("Folders can't be converted to or from receiveencrypted; re-add the folder instead");
+    }
+
+    // Changes that the new type would start sending or overwriting
+    let mut pending = Vec::new();
+    if old_type == "receiveonly" {
+        let changed = client.db_local_changed(&folder.id).await?.files.len();
+        if changed > 0 {
+            pending.push(format!(
+                "{} local changes will be sent to other devices (use 'folder revert' first to discard them)",
+                changed
+            ));
+        }
+    }
+    if old_type == "sendonly" {
+        let need = client.db_need_all(&folder.id).await?.files().count();
+        if need > 0 {
+            pending.push(format!(
+                "{} remote changes will be pulled, replacing the local versions",
+                need
+            ));
+        }
+    }
+    match new_type {
+        "receiveonly" => out.info(
+            "Note: local changes will no longer be sent to other devices; they can be reverted with 'folder revert'"
+        ),
+        "sendonly" => out.info(
+            "Note: changes made on other devices will no longer be applied here; 'folder override' pushes the local state"
+        ),
+        _ => {}
+    }
+    for warning in &pending {
+        out.info(format!("Warning: {}", warning));
+    }
+
+    let question = format!(
+        "Change folder {} from {} to {}?",
+        folder.display_name(),
+        old_type,
+        new_type
+    );
+    if !pending.is_empty() {
+        confirm_or_abort(&question, yes || client.dry_run())?;
+    }
+
+    client
+        .patch_folder(&folder.id, &json!({ "type": new_type }))
+        .await?;
+    out.emit(&ActionResult::new(format!(
+        "Folder {} is now {}",
+        folder.display_name(),
+        new_type
+    )))
+}
+
+/// Per-folder tuning keys `folder set` accepts.
+const TUNING_KEYS: &[(&str, Kind)] = &[
+    ("rescanIntervalS", Kind::Int),
+    ("fsWatcherEnabled", Kind::Bool),
+    ("fsWatcherDelayS", Kind::Int),
+    (
+        "order",
+        Kind::Choice(&[
+            "random",
+            "alphabetic",
+            "smallestFirst",
+            "largestFirst",
+            "oldestFirst",
+            "newestFirst",
+        ]),
+    ),
+    ("copiers", Kind::Int),
+    ("hashers", Kind::Int),
+    ("ignorePerms", Kind::Bool),
+    ("minDiskFree", Kind::Size),
+];
+
+/// Apply `key=value` tuning assignments to a folder in a single PATCH.
+pub async fn set(client: &Client, out: &Output, query: &str, assignments: &[String]) -> Result<()> {
+    let mut patch = Map::new();
+    for assignment in assignments {
+        let (key, value) = parse_assignment(TUNING_KEYS, assignment)?;
+        patch.insert(key, value);
+    }
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+
+    let keys: Vec<_> = patch.keys().cloned().collect();
+    client.patch_folder(&folder.id, &patch.into()).await?;
+    out.emit(&ActionResult::new(format!(
+        "Updated {} of folder {}",
+        keys.join(", "),
+        folder.display_name()
+    )))
+}
+
+/// New versioning settings for `folder versioning`.
+pub struct VersioningChange {
+    /// `simple`, `trashcan`, `staggered`, `external`, or empty for none.
+    pub versioning_type: &'static str,
+    pub keep: Option<u32>,
+    pub cleanout_days: Option<u32>,
+    pub max_age: Option<Duration>,
+    pub command: Option<String>,
+    pub path: Option<String>,
+    pub cleanup_interval: Option<Duration>,
+}
+
+/// Show a folder's versioning settings, after replacing them with `change`
+/// if given.
+pub async fn versioning(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    change: Option<VersioningChange>,
+) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let mut folder = find_folder(&folders, query)?.clone();
+
+    if let Some(change) = change {
+        let mut config = client.folder_config(&folder.id).await?;
+        config["versioning"] = versioning_block(&config["versioning"], &change)?;
+        client.replace_folder(&folder.id, &config).await?;
+        let folders = client.config_folders().await?;
+        folder = find_folder(&folders, &folder.id)?.clone();
+    }
+
+    let name = folder.display_name().to_string();
+    let versioning = folder.versioning;
+    out.emit(&VersioningReport {
+        folder: name,
+        versioning_type: Some(versioning.versioning_type).filter(|t| !t.is_empty()),
+        params: versioning.params.into_iter().collect(),
+        cleanup_interval_s: versioning.cleanup_interval_s,
+        path: Some(versioning.fs_path).filter(|p| !p.is_empty()),
+    })
+}
+
+/// The `versioning` block after `change`, rejecting options that don't apply
+/// to the chosen type. `params` is replaced as a whole, so options of the
+/// previous type don't linger; other fields of `current` are kept.
+fn versioning_block(current: &Value, change: &VersioningChange) -> Result<Value> {
+    let t = change.versioning_type;
+    let allowed = |option: &str, types: &[&str]| {
+        if types.contains(&t) {
+            Ok(())
+        } else if t.is_empty() {
+            Err(anyhow::anyhow!("{} needs a versioning type", option))
+        } else {
+            Err(anyhow::anyhow!(
+                "{} doesn't apply to {} versioning",
+                option,
+                t
+            ))
+        }
+    };
+
+    let mut params = Map::new();
+    if let Some(keep) = change.keep {
+        allowed("--keep", &["simple"])?;
+        params.insert("keep".to_string(), json!(keep.to_string()));
+    }
+    if let Some(days) = change.cleanout_days {
+        allowed("--cleanout-days", &["simple", "trashcan"])?;
+        params.insert("cleanoutDays".to_string(), json!(days.to_string()));
+    }
+    if let Some(max_age) = change.max_age {
+        allowed("--max-age", &["staggered"])?;
+        params.insert("maxAge".to_string(), json!(max_age.as_secs().to_string()));
+    }
+    match &change.command {
+        Some(command) => {
+            allowed("--command", &["external"])?;
+            params.insert("command".to_string(), json!(command));
+        }
+        None if t == "external" => bail!("External versioning needs --command"),
+        None => {}
+    }
+
+    let mut versioning = current.as_object().cloned().unwrap_or_default();
+    versioning.insert("type".to_string(), json!(t));
+    versioning.insert("params".to_string(), params.into());
+    if let Some(path) = &change.path {
+        allowed("--path", &["simple", "trashcan", "staggered"])?;
+        versioning.insert("fsPath".to_string(), json!(path));
+    }
+    if let Some(interval) = change.cleanup_interval {
+        allowed("--cleanup-interval", &["simple", "trashcan", "staggered"])?;
+        versioning.insert("cleanupIntervalS".to_string(), json!(interval.as_secs()));
+    }
+    Ok(versioning.into())
+}
+
+/// Revert a receive-only folder to the global state, discarding local changes.
+pub async fn revert(client: &Client, out: &Output, query: &str, yes: bool) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    if folder.folder_type != "receiveonly" {
+        bail!(
+            "Folder {} is {}; only receive-only folders can be reverted",
+            folder.display_name(),
+            folder.folder_type
+        );
+    }
+
+    let changed = client.db_local_changed(&folder.id).await?.files;
+    if changed.is_empty() {
+        return out.emit(&ActionResult::new(format!(
+            "Folder {} has no local changes to revert",
+            folder.display_name()
+        )));
+    }
+
+    let question = format!(
+        "Discard {} local changes in folder {}?",
+        changed.len(),
+        folder.display_name()
+    );
+    confirm_or_abort(&question, yes || client.dry_run())?;
+
+    client.db_revert(&folder.id).await?;
+    out.emit(&ActionResult::new(format!(
+        "Reverted {} local changes in folder {}",
+        changed.len(),
+        folder.display_name()
+    )))
+}
+
+/// Override remote changes in a send-only folder with the local state.
+pub async fn override_changes(client: &Client, out: &Output, query: &str, yes: bool) -> Result<()> {
+    const PREVIEW: usize = 10;
+
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    if folder.folder_type != "sendonly" {
+        bail!(
+            "Folder {} is {}; only send-only folders can be overridden",
+            folder.display_name(),
+            folder.folder_type
+        );
+    }
+
+    let need = client.db_need_all(&folder.id).await?;
+    let affected: Vec<_> = need.files().collect();
+    if affected.is_empty() {
+        return out.emit(&ActionResult::new(format!(
+            "Folder {} has no remote changes to override",
+            folder.display_name()
+        )));
+    }
+
+    let total: u64 = affected.iter().map(|f| f.size).sum();
+    out.info(format!(
+        "{} remote changes ({}) will be overwritten:",
+        affected.len(),
+        format_bytes(total)
+    ));
+    for file in affected.iter().take(PREVIEW) {
+        out.info(format!("  {}", file.name));
+    }
+    if affected.len() > PREVIEW {
+        out.info(format!("  ... and {} more", affected.len() - PREVIEW));
+    }
+
+    let question = format!(
+        "Override remote changes in folder {}?",
+        folder.display_name()
+    );
+    confirm_or_abort(&question, yes || client.dry_run())?;
+
+    client.db_override(&folder.id).await?;
+    out.emit(&ActionResult::new(format!(
+        "Overrode {} remote changes in folder {}",
+        affected.len(),
+        folder.display_name()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_known_time() {
+        assert_eq!(known_time("0001-01-01T00:00:00Z".to_string()), None);
+        assert_eq!(known_time(String::new()), None);
+        assert_eq!(
+            known_time("2024-01-01T10:00:00Z".to_string()).as_deref(),
+            Some("2024-01-01T10:00:00Z")
+        );
+    }
+
+    fn summary(label: &str, state: Option<&str>, need_bytes: u64, errors: u64) -> FolderSummary {
+        FolderSummary {
+            id: label.to_lowercase(),
+            label: label.to_string(),
+            paused: state.is_none(),
+            state: state.map(String::from),
+            need_files: 0,
+            need_bytes,
+            global_bytes: need_bytes * 2,
+            errors,
+            disk_warning: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lag_sorted_by_need() {
+        let server = MockServer::start().await;
+        let get = |endpoint: &str, body: Value| {
+            Mock::given(method("GET"))
+                .and(path(endpoint.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        };
+        get(
+            "/rest/config/folders",
+            json!([{"id": "docs", "devices": [
+                {"deviceID": "ME"}, {"deviceID": "LAPTOP"}, {"deviceID": "NAS"}
+            ]}]),
+        )
+        .mount(&server)
+        .await;
+        get(
+            "/rest/config/devices",
+            json!([{"deviceID": "LAPTOP", "name": "laptop"}, {"deviceID": "NAS", "name": "nas"}]),
+        )
+        .mount(&server)
+        .await;
+        get(
+            "/rest/system/connections",
+            json!({"connections": {"NAS": {"connected": true}}}),
+        )
+        .mount(&server)
+        .await;
+        get("/rest/system/status", json!({"myID": "ME"}))
+            .mount(&server)
+            .await;
+        for (device, need) in [("LAPTOP", 0), ("NAS", 4096)] {
+            Mock::given(method("GET"))
+                .and(path("/rest/db/completion"))
+                .and(query_param("device", device))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "completion": 50.0,
+                    "needBytes": need,
+                    "needItems": need / 1024
+                })))
+                .mount(&server)
+                .await;
+        }
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let report = lag_report(&client, "docs").await.unwrap();
+        let names: Vec<&str> = report.devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["nas", "laptop"]);
+        assert!(report.devices[0].connected);
+        assert_eq!(report.devices[0].need_items, 4);
+        assert!(!report.devices[1].connected);
+    }
+
+    #[tokio::test]
+    async fn test_open_quiet_checks_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"id": "docs", "path": dir.path()},
+                {"id": "gone", "path": dir.path().join("missing")}
+            ])))
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+        let out = Output {
+            quiet: true,
+            ..Default::default()
+        };
+
+        open(&client, &out, "docs").await.unwrap();
+        assert!(open(&client, &out, "gone").await.is_err());
+    }
+
+    #[test]
+    fn test_need_report_top() {
+        use syncthing::models::NeedFile;
+        let file = |name: &str, size: u64| NeedFile {
+            name: name.to_string(),
+            size,
+            ..Default::default()
+        };
+        let need = || Need {
+            progress: vec![file("small", 10)],
+            queued: vec![file("huge", 9000)],
+            rest: vec![file("medium", 500), file("tiny", 1)],
+            ..Default::default()
+        };
+
+        let all = need_report("docs", need(), None);
+        let names: Vec<&str> = all.files.iter().map(|f| f.file.name.as_str()).collect();
+        assert_eq!(names, ["small", "huge", "medium", "tiny"]);
+        assert_eq!(all.files[0].queue, "downloading");
+
+        let top = need_report("docs", need(), Some(2));
+        let names: Vec<&str> = top.files.iter().map(|f| f.file.name.as_str()).collect();
+        assert_eq!(names, ["huge", "medium"]);
+        assert_eq!(top.files[0].queue, "queued");
+        assert_eq!(top.total_files, 4);
+        assert_eq!(top.total_bytes, 9511);
+    }
+
+    #[test]
+    fn test_folder_view() {
+        let folders = || {
+            vec![
+                summary("music", Some("idle"), 0, 0),
+                summary("Docs", Some("syncing"), 100, 0),
+                summary("photos", None, 0, 0),
+                summary("backup", Some("sync-waiting"), 500, 2),
+            ]
+        };
+        let labels = |view: FolderView| -> Vec<String> {
+            view.apply(folders()).into_iter().map(|f| f.label).collect()
+        };
+
+        assert_eq!(labels(FolderView::default()).len(), 4);
+        assert_eq!(
+            labels(FolderView {
+                syncing: true,
+                ..Default::default()
+            }),
+            ["Docs", "backup"]
+        );
+        assert_eq!(
+            labels(FolderView {
+                paused: true,
+                errors: true,
+                ..Default::default()
+            }),
+            ["photos", "backup"]
+        );
+        assert_eq!(
+            labels(FolderView {
+                sort: Some(FolderSort::Need),
+                ..Default::default()
+            })[..2],
+            ["backup", "Docs"]
+        );
+        assert_eq!(
+            labels(FolderView {
+                sort: Some(FolderSort::Name),
+                ..Default::default()
+            }),
+            ["backup", "Docs", "music", "photos"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_set_tuning() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"id": "docs", "label": "Docs"}])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/folders/docs"))
+            .and(body_json(json!({
+                "rescanIntervalS": 3600,
+                "fsWatcherEnabled": false,
+                "minDiskFree": {"value": 5.0, "unit": "%"}
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let assignments = [
+            "rescanintervals=3600",
+            "fsWatcherEnabled=off",
+            "minDiskFree=5%",
+        ]
+        .map(String::from);
+        set(
+            &client,
+            &Output {
+                json: true,
+                ..Default::default()
+            },
+            "Docs",
+            &assignments,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_set_password() {
+        const PHONE: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "docs",
+                "devices": [{"deviceID": "SELF"}, {"deviceID": PHONE}]
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/devices"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([{"deviceID": PHONE, "name": "phone"}])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/folders/docs"))
+            .and(body_json(json!({"devices": [
+                {"deviceID": "SELF", "introducedBy": "", "encryptionPassword": ""},
+                {"deviceID": PHONE, "introducedBy": "", "encryptionPassword": "s3cret"}
+            ]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+        let out = Output {
+            json: true,
+            ..Default::default()
+        };
+
+        set_password(&client, &out, "docs", "phone", "s3cret")
+            .await
+            .unwrap();
+        assert!(
+            set_password(&client, &out, "docs", "laptop", "s3cret")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_share_skips_cached_folders() {
+        const PHONE: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "docs",
+                "devices": [{"deviceID": "SELF"}, {"deviceID": "LAPTOP"}]
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/devices"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([{"deviceID": PHONE, "name": "phone"}])),
+            )
+            .mount(&server)
+            .await;
+        // LAPTOP, shared since the cache was written, must stay
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/folders/docs"))
+            .and(body_json(json!({"devices": [
+                {"deviceID": "SELF", "introducedBy": "", "encryptionPassword": ""},
+                {"deviceID": "LAPTOP", "introducedBy": "", "encryptionPassword": ""},
+                {"deviceID": PHONE, "introducedBy": "", "encryptionPassword": ""}
+            ]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache = tempfile::tempdir().unwrap();
+        std::fs::write(
+            cache.path().join("folders.json"),
+            json!([{"id": "docs", "devices": [{"deviceID": "SELF"}]}]).to_string(),
+        )
+        .unwrap();
+        let options = syncthing::api::ClientOptions {
+            cache_dir: Some(cache.path().to_path_buf()),
+            cache_ttl: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let client = Client::with_options("key", &server.uri(), &options).unwrap();
+        let out = Output {
+            json: true,
+            ..Default::default()
+        };
+
+        share(&client, &out, "docs", &["phone".to_string()], true)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_info_finds_folder_by_label() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([{"id": "abcd-1234", "label": "Docs"}])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/db/status"))
+            .and(query_param("folder", "abcd-1234"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"state": "idle"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+        let out = Output {
+            quiet: true,
+            ..Default::default()
+        };
+
+        info(&client, &out, "Docs").await.unwrap();
+        let err = info(&client, &out, "Photos").await.unwrap_err();
+        assert!(err.downcast_ref::<NotFound>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_accept_existing_folder_patches_devices() {
+        const PHONE: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/cluster/pending/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "docs": {"offeredBy": {PHONE: {"time": "2024-01-01T10:00:00Z", "label": "Docs"}}},
+                "photos": {"offeredBy": {}}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": "docs",
+                "path": "/srv/docs",
+                "devices": [{"deviceID": "SELF"}]
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/folders/docs"))
+            .and(body_json(json!({"devices": [
+                {"deviceID": "SELF", "introducedBy": "", "encryptionPassword": ""},
+                {"deviceID": PHONE, "introducedBy": "", "encryptionPassword": ""}
+            ]})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+        let out = Output {
+            json: true,
+            ..Default::default()
+        };
+
+        let err = accept(&client, &out, "photos", None, None)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<NotFound>().is_some());
+        accept(&client, &out, "docs", None, Some("/srv/docs/"))
+            .await
+            .unwrap();
+        assert!(
+            accept(&client, &out, "docs", None, Some("/tmp/docs"))
+                .await
+                .is_err()
+        );
+    }
+
+    fn change(versioning_type: &'static str) -> VersioningChange {
+        VersioningChange {
+            versioning_type,
+            keep: None,
+            cleanout_days: None,
+            max_age: None,
+            command: None,
+            path: None,
+            cleanup_interval: None,
+        }
+    }
+
+    #[test]
+    fn test_versioning_block() {
+        let simple = json!({
+            "type": "simple",
+            "params": {"keep": "5"},
+            "cleanupIntervalS": 3600,
+            "fsPath": "",
+            "fsType": "basic"
+        });
+        let staggered = VersioningChange {
+            max_age: Some(Duration::from_secs(30 * 86400)),
+            cleanup_interval: Some(Duration::from_secs(600)),
+            ..change("staggered")
+        };
+        assert_eq!(
+            versioning_block(&simple, &staggered).unwrap(),
+            json!({
+                "type": "staggered",
+                "params": {"maxAge": "2592000"},
+                "cleanupIntervalS": 600,
+                "fsPath": "",
+                "fsType": "basic"
+            })
+        );
+        assert_eq!(
+            versioning_block(&Value::Null, &change("")).unwrap(),
+            json!({"type": "", "params": {}})
+        );
+
+        let keep_on_trashcan = VersioningChange {
+            keep: Some(5),
+            ..change("trashcan")
+        };
+        assert!(versioning_block(&simple, &keep_on_trashcan).is_err());
+        assert!(versioning_block(&simple, &change("external")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_versioning_replaces_params() {
+        let server = MockServer::start().await;
+        let folder = json!({
+            "id": "docs",
+            "path": "/srv/docs",
+            "versioning": {"type": "simple", "params": {"keep": "5"}, "fsType": "basic"}
+        });
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([folder])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders/docs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&folder))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/rest/config/folders/docs"))
+            .and(body_json(json!({
+                "id": "docs",
+                "path": "/srv/docs",
+                "versioning": {"type": "trashcan", "params": {}, "fsType": "basic"}
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+        let out = Output {
+            json: true,
+            ..Default::default()
+        };
+
+        versioning(&client, &out, "docs", Some(change("trashcan")))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_set_type_patches_type() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"id": "docs", "type": "receiveonly"}
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/db/localchanged"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({"files": [{"name": "a.txt"}]})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("PATCH"))
+            .and(path("/rest/config/folders/docs"))
+            .and(body_json(json!({"type": "sendreceive"})))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let out = Output {
+            json: true,
+            ..Default::default()
+        };
+        set_type(&client, &out, "docs", "sendreceive", true)
+            .await
+            .unwrap();
+        assert!(
+            set_type(&client, &out, "docs", "receiveencrypted", true)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_summaries_keep_folder_order() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                {"id": "slow", "label": "Slow"},
+                {"id": "fast", "label": "Fast"},
+                {"id": "off", "label": "Off", "paused": true}
+            ])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/db/status"))
+            .and(query_param("folder", "slow"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"state": "syncing", "needFiles": 3}))
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/db/status"))
+            .and(query_param("folder", "fast"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"state": "idle"})))
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let summaries = summaries(&client).await.unwrap();
+        let ids: Vec<_> = summaries.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["slow", "fast", "off"]);
+        assert_eq!(summaries[0].need_files, 3);
+        assert_eq!(summaries[1].state.as_deref(), Some("idle"));
+        assert_eq!(summaries[2].state, None);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::output::{GuiReport, Output};
+use anyhow::{Result, bail};
+use serde_json::{Map, json};
+use syncthing::api::Client;
+
+pub async fn show(client: &Client, out: &Output) -> Result<()> {
+    let gui = client.gui().await?;
+    out.emit(&GuiReport {
+        enabled: gui.enabled,
+        address: gui.address,
+        tls: gui.use_tls,
+        user: gui.user,
+        password_set: !gui.password.is_empty(),
+    })
+}
+
+/// Change GUI settings. Syncthing hashes a plain-text password itself.
+pub async fn set(
+    client: &Client,
+    out: &Output,
+    address: Option<&str>,
+    tls: Option<bool>,
+    user: Option<&str>,
+    password: Option<&str>,
+) -> Result<()> {
+    let mut patch = Map::new();
+    if let Some(address) = address {
+        patch.insert("address".to_string(), json!(address));
+    }
+    if let Some(tls) = tls {
+        patch.insert("useTLS".to_string(), json!(tls));
+    }
+    if let Some(user) = user {
+        patch.insert("user".to_string(), json!(user));
+    }
+    if let Some(password) = password {
+        patch.insert("password".to_string(), json!(password));
+    }
+    if patch.is_empty() {
+        bail!("Nothing to change; pass --address, --tls, --user or --password");
+    }
+    client.patch_gui(&patch.into()).await?;
+    show(client, out).await
+}
@@ -0,0 +1,281 @@
+use crate::diff::{diff_lines, print_changes};
+use crate::output::{ActionResult, IgnoreTestReport, IgnoreVerdict, IgnoresReport, Output};
+use crate::prompt::confirm_or_abort;
+use anyhow::{Context, Result, bail};
+use std::io::Read;
+use std::process::Command;
+use syncthing::api::Client;
+use syncthing::ignore;
+use syncthing::models::find_folder;
+
+pub async fn get(client: &Client, out: &Output, query: &str, expanded: bool) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let ignores = client.db_ignores(&folder.id).await?;
+
+    let report = IgnoresReport {
+        folder: folder.id.clone(),
+        patterns: ignores.ignore.unwrap_or_default(),
+        expanded: ignores.expanded.unwrap_or_default(),
+        show_expanded: expanded,
+    };
+    out.emit(&report)
+}
+
+/// Replace a folder's ignore patterns with the contents of `file`, or stdin
+/// when no file is given.
+pub async fn set(client: &Client, out: &Output, query: &str, file: Option<&str>) -> Result<()> {
+    let content = match file {
+        Some(path) => {
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?
+        }
+        None => {
+            let mut buf = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buf)
+                .context("Failed to read patterns from stdin")?;
+            buf
+        }
+    };
+    let patterns: Vec<String> = content.lines().map(str::to_string).collect();
+
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    client.set_db_ignores(&folder.id, &patterns).await?;
+
+    out.emit(&ActionResult::new(format!(
+        "Set {} ignore lines for folder {}",
+        patterns.len(),
+        folder.display_name()
+    )))
+}
+
+/// Open a folder's ignore patterns in `$VISUAL`/`$EDITOR` and push back any changes.
+pub async fn edit(client: &Client, out: &Output, query: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let original = client
+        .db_ignores(&folder.id)
+        .await?
+        .ignore
+        .unwrap_or_default();
+
+    let path = std::env::temp_dir().join(format!(
+        "syncthing-{}-{}.stignore",
+        folder.id,
+        std::process::id()
+    ));
+    let mut content = original.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    std::fs::write(&path, content)?;
+
+    let edited = run_editor(&path)
+        .and_then(|()| std::fs::read_to_string(&path).context("Failed to read edited patterns"));
+    let _ = std::fs::remove_file(&path);
+    let edited: Vec<String> = edited?.lines().map(str::to_string).collect();
+
+    if edited == original {
+        return out.emit(&ActionResult::new(format!(
+            "No changes to ignore patterns for folder {}",
+            folder.display_name()
+        )));
+    }
+
+    print_changes(out, &diff_lines(&original, &edited));
+    client.set_db_ignores(&folder.id, &edited).await?;
+    out.emit(&ActionResult::new(format!(
+        "Updated ignore patterns for folder {}",
+        folder.display_name()
+    )))
+}
+
+/// Tell for each path whether the folder's patterns ignore it, and why.
+pub async fn test(client: &Client, out: &Output, query: &str, paths: &[String]) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let patterns = client
+        .db_ignores(&folder.id)
+        .await?
+        .expanded
+        .unwrap_or_default();
+
+    let paths = paths
+        .iter()
+        .map(|path| {
+            if ignore::is_internal(path) {
+                return IgnoreVerdict {
+                    path: path.clone(),
+                    ignored: true,
+                    internal: true,
+                    pattern: None,
+                    deletable: false,
+                };
+            }
+            let matched = ignore::first_match(&patterns, path);
+            IgnoreVerdict {
+                path: path.clone(),
+                ignored: matched.as_ref().is_some_and(|p| !p.negated),
+                internal: false,
+                deletable: matched.as_ref().is_some_and(|p| p.deletable),
+                pattern: matched.map(|p| p.line.to_string()),
+            }
+        })
+        .collect();
+    out.emit(&IgnoreTestReport {
+        folder: folder.id.clone(),
+        paths,
+    })
+}
+
+/// A curated set of ignore patterns for common clutter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Preset {
+    Node,
+    Python,
+    MacOs,
+    WindowsJunk,
+}
+
+impl Preset {
+    fn name(self) -> &'static str {
+        match self {
+            Preset::Node => "node",
+            Preset::Python => "python",
+            Preset::MacOs => "macos",
+            Preset::WindowsJunk => "windows-junk",
+        }
+    }
+
+    /// OS metadata files get `(?d)` so Syncthing may delete them when they
+    /// would otherwise keep a directory from being removed.
+    fn patterns(self) -> &'static [&'static str] {
+        match self {
+            Preset::Node => &[
+                "node_modules",
+                ".npm",
+                ".pnpm-store",
+                ".yarn/cache",
+                "npm-debug.log*",
+                "yarn-error.log*",
+            ],
+            Preset::Python => &[
+                "__pycache__",
+                "*.py[cod]",
+                ".venv",
+                ".pytest_cache",
+                ".mypy_cache",
+                ".tox",
+                "*.egg-info",
+            ],
+            Preset::MacOs => &[
+                "(?d).DS_Store",
+                "(?d)._*",
+                "(?d).AppleDouble",
+                "(?d).Spotlight-V100",
+                "(?d).Trashes",
+                "(?d).fseventsd",
+            ],
+            Preset::WindowsJunk => &[
+                "(?d)Thumbs.db",
+                "(?d)ehthumbs.db",
+                "(?d)desktop.ini",
+                "$RECYCLE.BIN",
+                "System Volume Information",
+            ],
+        }
+    }
+}
+
+/// Append preset patterns to a folder's ignores after showing what changes.
+pub async fn preset(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    presets: &[Preset],
+    yes: bool,
+) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let original = client
+        .db_ignores(&folder.id)
+        .await?
+        .ignore
+        .unwrap_or_default();
+
+    let updated = with_presets(&original, presets);
+    if updated == original {
+        return out.emit(&ActionResult::new(format!(
+            "Folder {} already ignores everything in the preset",
+            folder.display_name()
+        )));
+    }
+
+    print_changes(out, &diff_lines(&original, &updated));
+    let question = format!("Add these patterns to folder {}?", folder.display_name());
+    confirm_or_abort(&question, yes || client.dry_run())?;
+
+    client.set_db_ignores(&folder.id, &updated).await?;
+    out.emit(&ActionResult::new(format!(
+        "Added {} ignore patterns to folder {}",
+        updated.len() - original.len(),
+        folder.display_name()
+    )))
+}
+
+/// `patterns` followed by each preset's patterns that aren't there yet,
+/// under a comment naming the preset.
+fn with_presets(patterns: &[String], presets: &[Preset]) -> Vec<String> {
+    let mut updated = patterns.to_vec();
+    for preset in presets {
+        let missing: Vec<&str> = preset
+            .patterns()
+            .iter()
+            .copied()
+            .filter(|p| !updated.iter().any(|line| line.trim() == *p))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+        updated.push(format!("// {} preset", preset.name()));
+        updated.extend(missing.into_iter().map(String::from));
+    }
+    updated
+}
+
+fn run_editor(path: &std::path::Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Allow editors with arguments, e.g. EDITOR="code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", editor))?;
+    if !status.success() {
+        bail!("Editor exited with {}; no changes applied", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_presets_skips_existing() {
+        let patterns = vec!["(?d).DS_Store".to_string(), "build".to_string()];
+        let updated = with_presets(&patterns, &[Preset::MacOs, Preset::MacOs]);
+        assert_eq!(updated[..3], ["(?d).DS_Store", "build", "// macos preset"]);
+        assert_eq!(
+            updated.len(),
+            patterns.len() + Preset::MacOs.patterns().len()
+        );
+        assert_eq!(with_presets(&updated, &[Preset::MacOs]), updated);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::output::{DeviceLimits, LimitsReport, Output};
+use anyhow::{Result, bail};
+use serde_json::{Map, json};
+use syncthing::api::Client;
+use syncthing::models::find_device;
+
+/// Show the global limits and those of every device that has its own.
+pub async fn get(client: &Client, out: &Output) -> Result<()> {
+    let options = client.options().await?;
+    let devices = client
+        .config_devices()
+        .await?
+        .into_iter()
+        .filter(|d| d.max_send_kbps > 0 || d.max_recv_kbps > 0)
+        .map(|d| DeviceLimits {
+            name: d.display_name().to_string(),
+            id: d.device_id,
+            max_send_kbps: d.max_send_kbps,
+            max_recv_kbps: d.max_recv_kbps,
+        })
+        .collect();
+    out.emit(&LimitsReport {
+        max_send_kbps: options.max_send_kbps,
+        max_recv_kbps: options.max_recv_kbps,
+        devices,
+    })
+}
+
+/// Set the upload and/or download limit in KiB/s (0 = unlimited), globally
+/// or for a single device.
+pub async fn set(
+    client: &Client,
+    out: &Output,
+    device: Option<&str>,
+    down: Option<u64>,
+    up: Option<u64>,
+) -> Result<()> {
+    let mut patch = Map::new();
+    if let Some(down) = down {
+        patch.insert("maxRecvKbps".to_string(), json!(down));
+    }
+    if let Some(up) = up {
+        patch.insert("maxSendKbps".to_string(), json!(up));
+    }
+    if patch.is_empty() {
+        bail!("Nothing to change; pass --down and/or --up");
+    }
+
+    match device {
+        Some(query) => {
+            let devices = client.config_devices().await?;
+            let device = find_device(&devices, query)?;
+            client
+                .patch_device(&device.device_id, &patch.into())
+                .await?;
+        }
+        None => client.patch_options(&patch.into()).await?,
+    }
+    get(client, out).await
+}
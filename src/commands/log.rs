@@ -0,0 +1,58 @@
+use crate::output::{LogLine, Output};
+use anyhow::Result;
+use std::time::Duration;
+use syncthing::api::Client;
+use syncthing::models::LogEntry;
+
+/// How often `--follow` asks for new lines; the log endpoint doesn't long-poll.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+fn to_line(entry: LogEntry) -> LogLine {
+    LogLine {
+        when: entry.when,
+        level: entry.level,
+        message: entry.message,
+    }
+}
+
+/// The last `lines` lines of the Syncthing log.
+pub async fn recent(client: &Client, out: &Output, lines: usize) -> Result<()> {
+    let log = client.log(None).await?;
+    let skip = log.len().saturating_sub(lines);
+    let log: Vec<LogLine> = log.into_iter().skip(skip).map(to_line).collect();
+    out.emit(&log)
+}
+
+/// Print the last `lines` lines, then poll for new ones until interrupted.
+/// With `--json`, each line is printed as one line of JSON (JSONL).
+pub async fn follow(client: &Client, out: &Output, lines: usize) -> Result<()> {
+    let log = client.log(None).await?;
+    let mut since = log.last().map(|entry| entry.when.clone());
+    let skip = log.len().saturating_sub(lines);
+    for entry in log.into_iter().skip(skip) {
+        out.emit_line(&to_line(entry))?;
+    }
+
+    let mut delay = POLL_INTERVAL;
+    loop {
+        tokio::time::sleep(delay).await;
+        match client.log(since.as_deref()).await {
+            Ok(log) => {
+                delay = POLL_INTERVAL;
+                for entry in log {
+                    since = Some(entry.when.clone());
+                    out.emit_line(&to_line(entry))?;
+                }
+            }
+            Err(e) => {
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+                out.info(format!(
+                    "Lost connection ({:#}); retrying in {}s",
+                    e,
+                    delay.as_secs()
+                ));
+            }
+        }
+    }
+}
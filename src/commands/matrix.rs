@@ -0,0 +1,129 @@
+use crate::output::{MatrixDevice, MatrixReport, MatrixRow, Output};
+use anyhow::Result;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use syncthing::api::Client;
+
+pub async fn show(client: &Client, out: &Output) -> Result<()> {
+    out.emit(&report(client).await?)
+}
+
+/// Completion of every folder (rows) on every device (columns), including
+/// this one.
+pub async fn report(client: &Client) -> Result<MatrixReport> {
+    let folders = client.config_folders().await?;
+    let devices = client.config_devices().await?;
+    let my_id = client.status().await?.my_id;
+
+    // This device first, then the others in config order
+    let mut devices: Vec<_> = devices.iter().collect();
+    devices.sort_by_key(|d| d.device_id != my_id);
+
+    let cells: Vec<(&str, &str)> = folders
+        .iter()
+        .filter(|f| !f.paused)
+        .flat_map(|folder| {
+            folder
+                .devices
+                .iter()
+                .map(move |d| (folder.id.as_str(), d.device_id.as_str()))
+        })
+        .collect();
+    let completions: Vec<f64> = stream::iter(cells.iter().copied())
+        .map(|(folder, device)| async move {
+            client
+                .db_completion_for(folder, device)
+                .await
+                .map(|c| c.completion)
+        })
+        .buffered(client.max_connections())
+        .try_collect()
+        .await?;
+
+    let rows = folders
+        .iter()
+        .map(|folder| MatrixRow {
+            id: folder.id.clone(),
+            label: folder.display_name().to_string(),
+            completion: devices
+                .iter()
+                .map(|device| {
+                    cells
+                        .iter()
+                        .position(|&cell| cell == (&folder.id, &device.device_id))
+                        .map(|i| completions[i])
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(MatrixReport {
+        devices: devices
+            .iter()
+            .map(|d| MatrixDevice {
+                id: d.device_id.clone(),
+                name: if d.device_id == my_id {
+                    "(this)".to_string()
+                } else {
+                    d.display_name().to_string()
+                },
+            })
+            .collect(),
+        folders: rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_get(server: &MockServer, endpoint: &str, body: serde_json::Value) {
+        Mock::given(method("GET"))
+            .and(path(endpoint))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_matrix_cells() {
+        let server = MockServer::start().await;
+        mock_get(
+            &server,
+            "/rest/config/folders",
+            json!([{"id": "docs", "devices": [{"deviceID": "ME"}, {"deviceID": "NAS"}]}]),
+        )
+        .await;
+        mock_get(
+            &server,
+            "/rest/config/devices",
+            json!([{"deviceID": "NAS", "name": "nas"}, {"deviceID": "ME"}, {"deviceID": "PHONE"}]),
+        )
+        .await;
+        mock_get(&server, "/rest/system/status", json!({"myID": "ME"})).await;
+        for (device, completion) in [("ME", 100.0), ("NAS", 42.5)] {
+            Mock::given(method("GET"))
+                .and(path("/rest/db/completion"))
+                .and(query_param("folder", "docs"))
+                .and(query_param("device", device))
+                .respond_with(
+                    ResponseTemplate::new(200).set_body_json(json!({"completion": completion})),
+                )
+                .mount(&server)
+                .await;
+        }
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let report = report(&client).await.unwrap();
+
+        // This device first, then config order
+        let columns: Vec<_> = report.devices.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(columns, ["ME", "NAS", "PHONE"]);
+        assert_eq!(
+            report.folders[0].completion,
+            [Some(100.0), Some(42.5), None]
+        );
+    }
+}
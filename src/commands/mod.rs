@@ -0,0 +1,154 @@
+//! Command handlers. Each takes an API client (where needed) and an
+//! [`Output`](crate::output::Output) and prints its result.
+
+pub mod apply;
+pub mod browse;
+pub mod check;
+pub mod cluster;
+pub mod config;
+pub mod conflicts;
+pub mod debug;
+pub mod defaults;
+pub mod devices;
+pub mod du;
+pub mod events;
+pub mod exporter;
+pub mod file;
+pub mod folders;
+pub mod gui;
+pub mod ignores;
+pub mod limits;
+pub mod log;
+pub mod matrix;
+pub mod monitor;
+pub mod offline;
+pub mod options;
+pub mod search;
+pub mod system;
+pub mod tui;
+pub mod versions;
+pub mod wait;
+
+use crate::output::{Output, Render, format_bytes};
+use anyhow::Result;
+use std::future::Future;
+use std::io::Write;
+use std::time::Duration;
+use syncthing::models::FolderConfig;
+
+/// Re-fetch and redraw a report every `interval` seconds until interrupted.
+///
+/// The report is fetched before the screen is touched, so the terminal only
+/// goes blank for the instant it takes to print.
+pub async fn watch<T, F, Fut>(out: &Output, interval: u64, title: &str, mut fetch: F) -> Result<()>
+where
+    T: Render,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+    loop {
+        ticker.tick().await;
+        let report = fetch().await;
+
+        // Home the cursor and clear the screen
+        print!("\x1b[H\x1b[2J");
+        println!(
+            "Every {}s: {}    {}",
+            interval,
+            title,
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+        println!();
+        match report {
+            Ok(report) => out.emit(&report)?,
+            Err(e) => println!("Error: {:#}", e),
+        }
+        std::io::stdout().flush()?;
+    }
+}
+
+/// Parse a duration such as `90`, `90s`, `15m`, `2h` or `1d` (bare numbers
+/// are seconds). Used as a clap value parser.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", value))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration unit '{}' (use s, m, h or d)",
+                unit
+            ));
+        }
+    };
+    Ok(Duration::from_secs(number * seconds))
+}
+
+/// A warning when a folder is short of disk space: Syncthing stops pulling
+/// into it once less than `minDiskFree` is free, so anything within twice
+/// that is flagged too. Free space is measured locally when the folder's
+/// path exists on this machine; otherwise Syncthing's own "insufficient
+/// space" error in `status_error` is all there is to go on.
+pub fn disk_space_warning(folder: &FolderConfig, status_error: &str) -> Option<String> {
+    let path = folder.local_path();
+    if let (Ok(free), Ok(total)) = (fs2::available_space(&path), fs2::total_space(&path)) {
+        let min = folder.min_disk_free.bytes(total);
+        return space_warning(free, min);
+    }
+    status_error
+        .contains("insufficient space")
+        .then(|| status_error.to_string())
+}
+
+fn space_warning(free: u64, min: u64) -> Option<String> {
+    if min == 0 || free >= min.saturating_mul(2) {
+        return None;
+    }
+    let position = if free < min { "below" } else { "close to" };
+    Some(format!(
+        "{} free, {} the {} minimum",
+        format_bytes(free),
+        position,
+        format_bytes(min)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(900)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn test_space_warning() {
+        const GB: u64 = 1 << 30;
+        assert_eq!(space_warning(10 * GB, 4 * GB), None);
+        assert_eq!(
+            space_warning(6 * GB, 4 * GB).as_deref(),
+            Some("6.0 GB free, close to the 4.0 GB minimum")
+        );
+        assert_eq!(
+            space_warning(GB, 4 * GB).as_deref(),
+            Some("1.0 GB free, below the 4.0 GB minimum")
+        );
+        assert_eq!(space_warning(0, 0), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! `monitor`: an audit trail for headless nodes. Follows the event stream
+//! and appends one JSON line per finished sync, folder error and device
+//! (dis)connection to a log file that is rotated by size.
+
+use super::events;
+use crate::output::Output;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use syncthing::api::Client;
+use syncthing::models::Event;
+
+/// The events records are made from.
+const TYPES: &[&str] = &[
+    "StateChanged",
+    "FolderErrors",
+    "DeviceConnected",
+    "DeviceDisconnected",
+];
+
+/// One line of the monitor log.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Record {
+    /// RFC 3339 timestamp of the event.
+    pub time: String,
+    /// `sync-complete`, `folder-error`, `file-errors`, `device-connected` or
+    /// `device-disconnected`.
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub folder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+pub struct LogSettings {
+    pub path: PathBuf,
+    /// Rotate once the file would grow past this many bytes.
+    pub max_bytes: u64,
+    /// How many rotated files (`<path>.1` is the newest) to keep.
+    pub keep: u32,
+}
+
+pub async fn run(client: &Client, out: &Output, settings: LogSettings) -> Result<()> {
+    let names: HashMap<String, String> = client
+        .config_devices()
+        .await?
+        .into_iter()
+        .map(|d| (d.device_id.clone(), d.display_name().to_string()))
+        .collect();
+    let types: Vec<String> = TYPES.iter().map(|t| t.to_string()).collect();
+
+    // Only what happens from now on
+    let since = client
+        .events(None, Some(1), &types)
+        .await?
+        .last()
+        .map_or(0, |e| e.id);
+    out.info(format!(
+        "Logging sync events to {}",
+        settings.path.display()
+    ));
+
+    events::stream(client, out, since, &types, |event| {
+        let Some(mut record) = record(&event) else {
+            return Ok(());
+        };
+        record.device_name = record.device.as_ref().and_then(|id| names.get(id)).cloned();
+        append(&settings, &serde_json::to_string(&record)?)
+    })
+    .await
+}
+
+/// The log record for `event`, if it is one worth keeping.
+fn record(event: &Event) -> Option<Record> {
+    let data = &event.data;
+    let field = |key: &str| data[key].as_str().map(String::from);
+    let (kind, detail) = match event.event_type.as_str() {
+        "StateChanged" => match (data["from"].as_str()?, data["to"].as_str()?) {
+            ("syncing", "idle") => ("sync-complete", None),
+            (_, "error") => ("folder-error", field("error")),
+            _ => return None,
+        },
+        "FolderErrors" => {
+            let errors = data["errors"].as_array()?;
+            let first = errors.first()?;
+            let detail = format!(
+                "{} file errors, e.g. {}: {}",
+                errors.len(),
+                first["path"].as_str().unwrap_or_default(),
+                first["error"].as_str().unwrap_or_default()
+            );
+            ("file-errors", Some(detail))
+        }
+        "DeviceConnected" => ("device-connected", field("addr")),
+        "DeviceDisconnected" => ("device-disconnected", field("error")),
+        _ => return None,
+    };
+    Some(Record {
+        time: event.time.clone(),
+        kind,
+        folder: field("folder"),
+        device: field("id"),
+        device_name: None,
+        detail: detail.filter(|d| !d.is_empty()),
+    })
+}
+
+/// Append `line` to the log, rotating first if it would outgrow the limit.
+fn append(settings: &LogSettings, line: &str) -> Result<()> {
+    let size = fs::metadata(&settings.path).map_or(0, |m| m.len());
+    if size > 0 && size + line.len() as u64 + 1 > settings.max_bytes {
+        rotate(&settings.path, settings.keep)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&settings.path)
+        .with_context(|| format!("Failed to open {}", settings.path.display()))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Shift `path.1` .. `path.<keep-1>` up by one and move `path` to `path.1`,
+/// dropping the oldest.
+fn rotate(path: &Path, keep: u32) -> Result<()> {
+    let numbered = |n: u32| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    };
+    if keep == 0 {
+        fs::remove_file(path)?;
+        return Ok(());
+    }
+    let _ = fs::remove_file(numbered(keep));
+    for n in (1..keep).rev() {
+        let from = numbered(n);
+        if from.exists() {
+            fs::rename(&from, numbered(n + 1))?;
+        }
+    }
+    fs::rename(path, numbered(1)).with_context(|| format!("Failed to rotate {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(event_type: &str, data: serde_json::Value) -> Event {
+        Event {
+            id: 1,
+            global_id: 1,
+            event_type: event_type.to_string(),
+            time: "2024-06-01T10:00:00Z".to_string(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let done = record(&event(
+            "StateChanged",
+            json!({"folder": "docs", "from": "syncing", "to": "idle"}),
+        ))
+        .unwrap();
+        assert_eq!(done.kind, "sync-complete");
+        assert_eq!(done.folder.as_deref(), Some("docs"));
+
+        assert_eq!(
+            record(&event(
+                "StateChanged",
+                json!({"folder": "docs", "from": "idle", "to": "scanning"}),
+            )),
+            None
+        );
+
+        let gone = record(&event(
+            "DeviceDisconnected",
+            json!({"id": "NAS", "error": "reading length: EOF"}),
+        ))
+        .unwrap();
+        assert_eq!(gone.kind, "device-disconnected");
+        assert_eq!(gone.device.as_deref(), Some("NAS"));
+        assert_eq!(gone.detail.as_deref(), Some("reading length: EOF"));
+
+        let errors = record(&event(
+            "FolderErrors",
+            json!({"folder": "docs", "errors": [
+                {"path": "a.txt", "error": "permission denied"},
+                {"path": "b.txt", "error": "permission denied"}
+            ]}),
+        ))
+        .unwrap();
+        assert_eq!(
+            errors.detail.as_deref(),
+            Some("2 file errors, e.g. a.txt: permission denied")
+        );
+    }
+
+    #[test]
+    fn test_append_rotates() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = LogSettings {
+            path: dir.path().join("monitor.log"),
+            max_bytes: 10,
+            keep: 2,
+        };
+        for line in ["first", "second", "third", "fourth"] {
+            append(&settings, line).unwrap();
+        }
+        let read = |name: &str| fs::read_to_string(dir.path().join(name)).unwrap();
+        assert_eq!(read("monitor.log"), "fourth\n");
+        assert_eq!(read("monitor.log.1"), "third\n");
+        assert_eq!(read("monitor.log.2"), "second\n");
+        assert!(!dir.path().join("monitor.log.3").exists());
+    }
+}
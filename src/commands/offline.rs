@@ -0,0 +1,83 @@
+//! `--offline`: read-only views built from Syncthing's config.xml instead of
+//! the REST API, for diagnosing a node whose daemon is down.
+
+use crate::output::{ConfiguredDevice, ConfiguredFolder, Output};
+use anyhow::Result;
+use std::collections::HashMap;
+use syncthing::config::syncthing_config_path;
+use syncthing::config_xml::SyncthingConfig;
+
+pub fn folders(out: &Output) -> Result<()> {
+    out.emit(&configured_folders(&load()?))
+}
+
+pub fn devices(out: &Output) -> Result<()> {
+    let devices: Vec<ConfiguredDevice> = load()?
+        .devices
+        .into_iter()
+        .map(|d| ConfiguredDevice {
+            name: d.display_name().to_string(),
+            id: d.device_id,
+            addresses: d.addresses,
+            paused: d.paused,
+        })
+        .collect();
+    out.emit(&devices)
+}
+
+fn load() -> Result<SyncthingConfig> {
+    SyncthingConfig::load(&syncthing_config_path())
+}
+
+fn configured_folders(config: &SyncthingConfig) -> Vec<ConfiguredFolder> {
+    let names: HashMap<&str, &str> = config
+        .devices
+        .iter()
+        .map(|d| (d.device_id.as_str(), d.display_name()))
+        .collect();
+    config
+        .folders
+        .iter()
+        .map(|f| ConfiguredFolder {
+            id: f.id.clone(),
+            label: f.display_name().to_string(),
+            path: f.path.clone(),
+            folder_type: f.folder_type.clone(),
+            paused: f.paused,
+            devices: f
+                .devices
+                .iter()
+                .map(|d| {
+                    names
+                        .get(d.device_id.as_str())
+                        .copied()
+                        .unwrap_or(&d.device_id)
+                })
+                .map(String::from)
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_folders_names_devices() {
+        let config = SyncthingConfig::parse(
+            r#"<configuration version="37">
+                <folder id="docs" label="" path="/srv/docs" type="sendonly">
+                    <device id="AAA"></device>
+                    <device id="BBB"></device>
+                </folder>
+                <device id="AAA" name="laptop"></device>
+            </configuration>"#,
+        )
+        .unwrap();
+        let folders = configured_folders(&config);
+        assert_eq!(folders[0].label, "docs");
+        assert_eq!(folders[0].folder_type, "sendonly");
+        assert_eq!(folders[0].devices, ["laptop", "BBB"]);
+    }
+}
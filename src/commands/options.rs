@@ -0,0 +1,182 @@
+use crate::output::{ActionResult, OptionsReport, Output};
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value};
+use syncthing::api::Client;
+
+/// Keys `options set` accepts, and what kind of value each takes.
+const KEYS: &[(&str, Kind)] = &[
+    ("relaysEnabled", Kind::Bool),
+    ("natEnabled", Kind::Bool),
+    ("localAnnounceEnabled", Kind::Bool),
+    ("globalAnnounceEnabled", Kind::Bool),
+    ("urAccepted", Kind::Int),
+];
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    /// Any string, as given.
+    Text,
+    Bool,
+    Int,
+    /// One of a fixed set of strings.
+    Choice(&'static [&'static str]),
+    /// A size such as `1%` or `10GB`, as Syncthing's `{"value", "unit"}`.
+    Size,
+    /// A comma-separated list of strings; empty for an empty list.
+    List,
+}
+
+pub async fn get(client: &Client, out: &Output) -> Result<()> {
+    let options = client.options().await?;
+    out.emit(&OptionsReport {
+        relays_enabled: options.relays_enabled,
+        nat_enabled: options.nat_enabled,
+        local_announce_enabled: options.local_announce_enabled,
+        global_announce_enabled: options.global_announce_enabled,
+        ur_accepted: options.ur_accepted,
+    })
+}
+
+/// Apply `key=value` assignments in a single PATCH.
+pub async fn set(client: &Client, out: &Output, assignments: &[String]) -> Result<()> {
+    let mut patch = Map::new();
+    for assignment in assignments {
+        let (key, value) = parse_assignment(KEYS, assignment)?;
+        patch.insert(key, value);
+    }
+    if patch.is_empty() {
+        bail!("Nothing to change; pass key=value pairs");
+    }
+    let keys: Vec<_> = patch.keys().cloned().collect();
+    client.patch_options(&patch.into()).await?;
+    out.emit(&ActionResult::new(format!("Updated {}", keys.join(", "))))
+}
+
+/// Parse `key=value` against a table of known keys, matching key names
+/// case-insensitively and returning the canonical name with a typed value.
+pub fn parse_assignment(keys: &[(&str, Kind)], assignment: &str) -> Result<(String, Value)> {
+    let Some((key, value)) = assignment.split_once('=') else {
+        bail!("Expected key=value, got '{}'", assignment);
+    };
+    let Some((key, kind)) = keys
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key.trim()))
+    else {
+        let known: Vec<_> = keys.iter().map(|(k, _)| *k).collect();
+        bail!(
+            "Unknown option '{}' (known: {}; use 'config patch' for others)",
+            key,
+            known.join(", ")
+        );
+    };
+    let value = value.trim();
+    let value = match kind {
+        Kind::Text => Value::from(value),
+        Kind::Bool => Value::Bool(match value.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => true,
+            "false" | "no" | "off" | "0" => false,
+            _ => bail!("'{}' expects true or false, got '{}'", key, value),
+        }),
+        Kind::Int => Value::from(
+            value
+                .parse::<i64>()
+                .with_context(|| format!("'{}' expects an integer, got '{}'", key, value))?,
+        ),
+        Kind::Choice(choices) => match choices.iter().find(|c| c.eq_ignore_ascii_case(value)) {
+            Some(choice) => Value::from(*choice),
+            None => bail!(
+                "'{}' expects one of {}, got '{}'",
+                key,
+                choices.join(", "),
+                value
+            ),
+        },
+        Kind::Size => parse_size(key, value)?,
+        Kind::List => Value::from(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .collect::<Vec<_>>(),
+        ),
+    };
+    Ok((key.to_string(), value))
+}
+
+/// `5%` or `10GB` as `{"value": 5, "unit": "%"}` / `{"value": 10, "unit": "GB"}`.
+fn parse_size(key: &str, value: &str) -> Result<Value> {
+    const UNITS: &[&str] = &["%", "kB", "MB", "GB", "TB"];
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("'{}' expects a size like 1% or 10GB, got '{}'", key, value))?;
+    let Some(unit) = UNITS.iter().find(|u| u.eq_ignore_ascii_case(unit.trim())) else {
+        bail!(
+            "'{}' expects a unit of {}, got '{}'",
+            key,
+            UNITS.join(", "),
+            value
+        );
+    };
+    Ok(serde_json::json!({ "value": number, "unit": unit }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment(KEYS, "natenabled=off").unwrap(),
+            ("natEnabled".to_string(), Value::Bool(false))
+        );
+        assert_eq!(
+            parse_assignment(KEYS, "urAccepted=-1").unwrap(),
+            ("urAccepted".to_string(), Value::from(-1))
+        );
+        assert!(parse_assignment(KEYS, "relaysEnabled").is_err());
+        assert!(parse_assignment(KEYS, "relaysEnabled=maybe").is_err());
+        assert!(parse_assignment(KEYS, "startBrowser=true").is_err());
+    }
+
+    #[test]
+    fn test_parse_choice_and_size() {
+        const KEYS: &[(&str, Kind)] = &[
+            ("order", Kind::Choice(&["random", "smallestFirst"])),
+            ("minDiskFree", Kind::Size),
+        ];
+        assert_eq!(
+            parse_assignment(KEYS, "order=smallestfirst").unwrap().1,
+            Value::from("smallestFirst")
+        );
+        assert!(parse_assignment(KEYS, "order=biggest").is_err());
+        assert_eq!(
+            parse_assignment(KEYS, "minDiskFree=10gb").unwrap().1,
+            serde_json::json!({"value": 10.0, "unit": "GB"})
+        );
+        assert_eq!(
+            parse_assignment(KEYS, "minDiskFree=2.5%").unwrap().1,
+            serde_json::json!({"value": 2.5, "unit": "%"})
+        );
+        assert!(parse_assignment(KEYS, "minDiskFree=lots").is_err());
+    }
+
+    #[test]
+    fn test_parse_list() {
+        const KEYS: &[(&str, Kind)] = &[("allowedNetworks", Kind::List)];
+        assert_eq!(
+            parse_assignment(KEYS, "allowedNetworks=10.0.0.0/8, 192.168.0.0/16")
+                .unwrap()
+                .1,
+            serde_json::json!(["10.0.0.0/8", "192.168.0.0/16"])
+        );
+        assert_eq!(
+            parse_assignment(KEYS, "allowedNetworks=").unwrap().1,
+            serde_json::json!([])
+        );
+    }
+}
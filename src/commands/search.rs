@@ -0,0 +1,157 @@
+//! `search`: find files by name in the global view of every folder, like
+//! `locate` for Syncthing.
+
+use crate::output::{Output, SearchHit};
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use std::collections::HashSet;
+use syncthing::api::Client;
+use syncthing::glob::path_match;
+use syncthing::models::BrowseEntry;
+
+/// Paths matching `pattern` in every unpaused folder. Patterns with
+/// wildcards are globs (`*.pdf`, `photos/**/raw`); anything else matches as
+/// a case-insensitive substring of the path.
+pub async fn search(client: &Client, out: &Output, pattern: &str) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let matcher = Matcher::new(pattern);
+
+    let results: Vec<_> = stream::iter(folders.iter().filter(|f| !f.paused))
+        .map(|folder| {
+            let matcher = &matcher;
+            async move { (folder, search_folder(client, &folder.id, matcher).await) }
+        })
+        .buffered(client.max_connections())
+        .collect()
+        .await;
+
+    let mut hits = Vec::new();
+    for (folder, result) in results {
+        let (found, needed) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                out.info(format!(
+                    "Skipping folder {}: {:#}",
+                    folder.display_name(),
+                    e
+                ));
+                continue;
+            }
+        };
+        hits.extend(found.into_iter().map(|found| SearchHit {
+            folder: folder.id.clone(),
+            label: folder.display_name().to_string(),
+            needed: needed.contains(&found.path),
+            path: found.path,
+            size: found.size,
+            dir: found.dir,
+        }));
+    }
+    out.emit(&hits)
+}
+
+/// Matches in one folder, and which paths it still needs.
+async fn search_folder(
+    client: &Client,
+    folder: &str,
+    matcher: &Matcher,
+) -> Result<(Vec<Found>, HashSet<String>)> {
+    let tree = client.db_browse(folder, None, None).await?;
+    let mut found = Vec::new();
+    collect(&tree, "", matcher, &mut found);
+    if found.is_empty() {
+        return Ok((found, HashSet::new()));
+    }
+    let need = client.db_need_all(folder).await?;
+    let needed = need.files().map(|f| f.name.clone()).collect();
+    Ok((found, needed))
+}
+
+enum Matcher {
+    Glob(String),
+    /// Lowercased.
+    Substring(String),
+}
+
+impl Matcher {
+    fn new(pattern: &str) -> Self {
+        if pattern.contains(['*', '?', '[']) {
+            Matcher::Glob(pattern.to_string())
+        } else {
+            Matcher::Substring(pattern.to_lowercase())
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            Matcher::Glob(pattern) => path_match(pattern, path),
+            Matcher::Substring(needle) => path.to_lowercase().contains(needle),
+        }
+    }
+}
+
+struct Found {
+    path: String,
+    size: u64,
+    dir: bool,
+}
+
+/// Walk a browse tree, collecting matching entries with their full paths.
+fn collect(entries: &[BrowseEntry], parent: &str, matcher: &Matcher, found: &mut Vec<Found>) {
+    for entry in entries {
+        let path = if parent.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", parent, entry.name)
+        };
+        if matcher.matches(&path) {
+            found.push(Found {
+                path: path.clone(),
+                size: entry.size,
+                dir: entry.is_dir(),
+            });
+        }
+        collect(&entry.children, &path, matcher, found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, children: Vec<BrowseEntry>) -> BrowseEntry {
+        BrowseEntry {
+            name: name.to_string(),
+            entry_type: if children.is_empty() {
+                "FILE_INFO_TYPE_FILE".to_string()
+            } else {
+                "FILE_INFO_TYPE_DIRECTORY".to_string()
+            },
+            children,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collect() {
+        let tree = vec![
+            entry(
+                "Taxes",
+                vec![entry("2024", vec![entry("Receipt.pdf", vec![])])],
+            ),
+            entry("notes.txt", vec![]),
+        ];
+        let paths = |pattern: &str| {
+            let mut found = Vec::new();
+            collect(&tree, "", &Matcher::new(pattern), &mut found);
+            found.into_iter().map(|f| f.path).collect::<Vec<_>>()
+        };
+        assert_eq!(paths("receipt"), ["Taxes/2024/Receipt.pdf"]);
+        assert_eq!(paths("*.pdf"), ["Taxes/2024/Receipt.pdf"]);
+        assert_eq!(
+            paths("taxes"),
+            ["Taxes", "Taxes/2024", "Taxes/2024/Receipt.pdf"]
+        );
+        assert!(paths("*.doc").is_empty());
+    }
+}
@@ -0,0 +1,500 @@
+use super::wait::{bytes_bar, spinner};
+use crate::output::{
+    ActionResult, FolderError, FolderErrorsReport, IdReport, Output, PathsReport, PendingDevice,
+    PendingFolder, PendingReport, PingReport, StatusReport, SystemError,
+};
+use crate::prompt::confirm_or_abort;
+use anyhow::{Context, Result, bail};
+use indicatif::ProgressBar;
+use std::time::{Duration, Instant};
+use syncthing::api::Client;
+use syncthing::models::find_folder;
+
+const RESTART_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub async fn status(client: &Client, out: &Output) -> Result<()> {
+    out.emit(&status_report(client).await?)
+}
+
+pub async fn status_report(client: &Client) -> Result<StatusReport> {
+    let status = client.status().await?;
+    let version = client.version().await?;
+    let completion = client.db_completion().await?;
+    let devices = client.config_devices().await?;
+    let connections = client.connections().await?;
+    let pending_devices = client.pending_devices().await?;
+    let pending_folders = client.pending_folders().await?;
+    let errors = client.errors().await?;
+
+    let remote: Vec<_> = devices
+        .iter()
+        .filter(|d| d.device_id != status.my_id)
+        .collect();
+    let devices_connected = remote
+        .iter()
+        .filter(|d| {
+            connections
+                .connections
+                .get(&d.device_id)
+                .is_some_and(|c| c.connected)
+        })
+        .count();
+
+    let report = StatusReport {
+        version: version.version,
+        uptime: status.uptime,
+        memory_alloc: status.alloc,
+        memory_sys: status.sys,
+        completion: completion.completion,
+        global_bytes: completion.global_bytes,
+        need_bytes: completion.need_bytes,
+        devices_connected,
+        devices_total: remote.len(),
+        pending_devices: pending_devices.len(),
+        pending_folders: pending_folders.len(),
+        errors: errors.len(),
+    };
+    Ok(report)
+}
+
+/// Which part of which folder to rescan; everything when `folder` is `None`.
+pub struct ScanTarget {
+    pub folder: Option<String>,
+    /// Paths inside the folder to limit the scan to.
+    pub subs: Vec<String>,
+    /// Seconds to postpone the folder's next regular scan by.
+    pub next: Option<u64>,
+}
+
+pub async fn scan(client: &Client, out: &Output, target: ScanTarget) -> Result<()> {
+    let result = if let Some(query) = target.folder {
+        let folders = client.config_folders().await?;
+        let folder = find_folder(&folders, &query)?;
+        client
+            .db_scan_with(&folder.id, &target.subs, target.next)
+            .await?;
+        ActionResult::new(format!(
+            "Scan triggered for folder: {}",
+            folder.display_name()
+        ))
+    } else {
+        client.db_scan_all().await?;
+        ActionResult::new("Scan triggered for all folders")
+    };
+    out.emit(&result)
+}
+
+/// Trigger a scan and block until it has finished, drawing scan progress on
+/// stderr.
+///
+/// Syncthing only answers the scan request once scanning is done, so that
+/// request decides when we return; FolderScanProgress and LocalIndexUpdated
+/// events just feed the progress line meanwhile.
+pub async fn scan_and_wait(client: &Client, out: &Output, target: ScanTarget) -> Result<()> {
+    let folder = match target.folder {
+        Some(query) => {
+            let folders = client.config_folders().await?;
+            Some(find_folder(&folders, &query)?.clone())
+        }
+        None => None,
+    };
+    let folder_id = folder.as_ref().map(|f| f.id.as_str());
+    let since = client
+        .events(None, Some(1), &[])
+        .await?
+        .last()
+        .map_or(0, |e| e.id);
+
+    let scan = async {
+        match folder_id {
+            Some(id) => client.db_scan_with(id, &target.subs, target.next).await,
+            None => client.db_scan_all().await,
+        }
+    };
+    let bar = bytes_bar(out);
+    let progress = async {
+        if bar.is_hidden() {
+            std::future::pending().await
+        } else {
+            follow_scan_progress(client, since, folder_id, &bar).await
+        }
+    };
+    let result = tokio::select! {
+        result = scan => result,
+        () = progress => Ok(()),
+    };
+    bar.finish_and_clear();
+    result?;
+
+    out.emit(&ActionResult::new(match &folder {
+        Some(f) => format!("Scan of folder {} finished", f.display_name()),
+        None => "Scan of all folders finished".to_string(),
+    }))
+}
+
+/// Move `bar` along with scan events until cancelled.
+async fn follow_scan_progress(
+    client: &Client,
+    mut since: u64,
+    folder: Option<&str>,
+    bar: &ProgressBar,
+) {
+    let types = [
+        "FolderScanProgress".to_string(),
+        "LocalIndexUpdated".to_string(),
+    ];
+    loop {
+        let Ok(events) = client.events(Some(since), None, &types).await else {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            continue;
+        };
+        for event in events {
+            since = event.id;
+            let data = &event.data;
+            let event_folder = data["folder"].as_str().unwrap_or_default();
+            if folder.is_some_and(|f| f != event_folder) {
+                continue;
+            }
+            match event.event_type.as_str() {
+                "FolderScanProgress" => {
+                    bar.set_length(data["total"].as_u64().unwrap_or(0));
+                    bar.set_position(data["current"].as_u64().unwrap_or(0));
+                    bar.set_message(event_folder.to_string());
+                }
+                _ => {
+                    if let Some(file) = data["filenames"]
+                        .as_array()
+                        .and_then(|names| names.last())
+                        .and_then(|name| name.as_str())
+                    {
+                        bar.set_message(format!("{}: {}", event_folder, file));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub async fn errors(
+    client: &Client,
+    out: &Output,
+    folder: Option<String>,
+    clear: bool,
+) -> Result<()> {
+    if clear {
+        client.clear_errors().await?;
+        return out.emit(&ActionResult::new("Errors cleared"));
+    }
+
+    if let Some(query) = folder {
+        // Show folder-specific errors
+        let folders = client.config_folders().await?;
+        let folder = find_folder(&folders, &query)?;
+        let errors = client.folder_errors(&folder.id).await?;
+        let report = FolderErrorsReport {
+            folder: folder.id.clone(),
+            errors: errors
+                .errors
+                .unwrap_or_default()
+                .into_iter()
+                .map(|err| FolderError {
+                    path: err.path,
+                    error: err.error,
+                })
+                .collect(),
+        };
+        return out.emit(&report);
+    }
+
+    // Show system errors
+    let errors: Vec<SystemError> = client
+        .errors()
+        .await?
+        .into_iter()
+        .map(|err| SystemError {
+            when: err.when,
+            message: err.message,
+        })
+        .collect();
+    out.emit(&errors)
+}
+
+pub async fn pending(client: &Client, out: &Output) -> Result<()> {
+    out.emit(&pending_report(client).await?)
+}
+
+async fn pending_report(client: &Client) -> Result<PendingReport> {
+    let devices = client.pending_devices().await?;
+    let folders = client.pending_folders().await?;
+
+    let mut pending_devices: Vec<PendingDevice> = devices
+        .into_iter()
+        .map(|(id, info)| PendingDevice {
+            id,
+            name: if info.name.is_empty() {
+                "unknown".to_string()
+            } else {
+                info.name
+            },
+        })
+        .collect();
+    pending_devices.sort_by(|a, b| a.id.cmp(&b.id));
+
+    // To tell apart offers of new folders from existing ones not yet shared
+    // with the offering device, and to name the offering devices
+    let configured = client.config_folders().await?;
+    let known_devices = client.config_devices().await?;
+
+    let mut pending_folders = Vec::new();
+    for (folder_id, folder) in folders {
+        let exists = configured.iter().any(|f| f.id == folder_id);
+        for (device_id, offer) in folder.offered_by {
+            let label = if offer.label.is_empty() {
+                folder_id.clone()
+            } else {
+                offer.label
+            };
+            let device_name = known_devices
+                .iter()
+                .find(|d| d.device_id == device_id)
+                .map(|d| d.display_name().to_string());
+            pending_folders.push(PendingFolder {
+                id: folder_id.clone(),
+                label,
+                device: device_id,
+                device_name,
+                time: Some(offer.time).filter(|t| !t.is_empty()),
+                receive_encrypted: offer.receive_encrypted,
+                exists,
+            });
+        }
+    }
+    pending_folders.sort_by(|a, b| (&a.id, &a.device).cmp(&(&b.id, &b.device)));
+
+    Ok(PendingReport {
+        devices: pending_devices,
+        folders: pending_folders,
+    })
+}
+
+/// Time `count` round trips to `/rest/system/ping`, one second apart.
+/// Fails (exit code 1) as soon as one of them does.
+pub async fn ping(client: &Client, out: &Output, count: u32) -> Result<()> {
+    let count = count.max(1);
+    let mut times = Vec::new();
+    for i in 0..count {
+        if i > 0 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        let start = Instant::now();
+        client
+            .ping()
+            .await
+            .with_context(|| format!("Syncthing at {} is unreachable", client.base_url()))?;
+        times.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    out.emit(&PingReport {
+        host: client.base_url().to_string(),
+        count,
+        min_ms: times.iter().copied().fold(f64::INFINITY, f64::min),
+        avg_ms: times.iter().sum::<f64>() / times.len() as f64,
+        max_ms: times.iter().copied().fold(0.0, f64::max),
+    })
+}
+
+/// Print this node's device ID, optionally as a QR code for pairing.
+pub async fn id(client: &Client, out: &Output, qr: bool) -> Result<()> {
+    let id = client.status().await?.my_id;
+    out.emit(&IdReport { id, qr })
+}
+
+pub async fn paths(client: &Client, out: &Output) -> Result<()> {
+    out.emit(&PathsReport(client.paths().await?))
+}
+
+pub async fn restart(
+    client: &Client,
+    out: &Output,
+    wait: Option<Duration>,
+    yes: bool,
+) -> Result<()> {
+    confirm_or_abort(
+        &format!("Restart Syncthing at {}?", client.base_url()),
+        yes || client.dry_run(),
+    )?;
+    // A dry run never restarts anything to wait for
+    let Some(timeout) = wait.filter(|_| !client.dry_run()) else {
+        client.restart().await?;
+        return out.emit(&ActionResult::new("Syncthing restart initiated"));
+    };
+
+    // A new start time tells the restarted process apart from the old one
+    // still answering while it shuts down
+    let before = client.status().await?.start_time;
+    client.restart().await?;
+    let bar = spinner(out, "Waiting for Syncthing to restart");
+    let start = Instant::now();
+    loop {
+        tokio::time::sleep(RESTART_POLL_INTERVAL).await;
+        if let Ok(status) = client.status().await
+            && status.start_time != before
+        {
+            break;
+        }
+        if start.elapsed() >= timeout {
+            bar.finish_and_clear();
+            bail!(
+                "Syncthing did not come back within {}s of restarting",
+                timeout.as_secs()
+            );
+        }
+    }
+    bar.finish_and_clear();
+    out.emit(&ActionResult::new(format!(
+        "Syncthing restarted in {:.1}s",
+        start.elapsed().as_secs_f64()
+    )))
+}
+
+pub async fn shutdown(client: &Client, out: &Output, yes: bool) -> Result<()> {
+    confirm_or_abort(
+        &format!(
+            "Shut down Syncthing at {}? It has to be started again by hand.",
+            client.base_url()
+        ),
+        yes || client.dry_run(),
+    )?;
+    client.shutdown().await?;
+    out.emit(&ActionResult::new("Syncthing shutdown initiated"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_scan_and_wait_resolves_label() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([{"id": "abcd-1234", "label": "Docs"}])),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/events"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/db/scan"))
+            .and(query_param("folder", "abcd-1234"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let target = ScanTarget {
+            folder: Some("Docs".to_string()),
+            subs: Vec::new(),
+            next: None,
+        };
+        scan_and_wait(
+            &client,
+            &Output {
+                json: true,
+                ..Default::default()
+            },
+            target,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pending_folder_details() {
+        const NAS: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/cluster/pending/devices"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/cluster/pending/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "docs": {"offeredBy": {NAS: {
+                    "time": "2024-06-01T10:00:00Z",
+                    "label": "Docs",
+                    "receiveEncrypted": true
+                }}}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": "docs"}])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/config/devices"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([{"deviceID": NAS, "name": "nas"}])),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let report = pending_report(&client).await.unwrap();
+        let folder = &report.folders[0];
+        assert_eq!(folder.device_name.as_deref(), Some("nas"));
+        assert_eq!(folder.time.as_deref(), Some("2024-06-01T10:00:00Z"));
+        assert!(folder.receive_encrypted);
+        assert!(folder.exists);
+    }
+
+    #[tokio::test]
+    async fn test_restart_waits_for_new_process() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/system/status"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"startTime": "2024-06-01T10:00:00Z"})),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/system/status"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"startTime": "2024-06-01T10:05:00Z"})),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/rest/system/restart"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        restart(
+            &client,
+            &Output::default(),
+            Some(Duration::from_secs(10)),
+            true,
+        )
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,511 @@
+//! Full-screen dashboard: folders, devices, transfer rates and recent events.
+
+use crate::commands::events::summarize;
+use crate::commands::folders;
+use crate::output::{EventSummary, FolderSummary, format_bytes};
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use syncthing::api::Client;
+use tokio::sync::mpsc;
+
+/// How many events are kept for the events pane.
+const EVENT_HISTORY: usize = 200;
+
+struct DeviceRow {
+    id: String,
+    name: String,
+    paused: bool,
+    connected: bool,
+    in_bytes_total: u64,
+    out_bytes_total: u64,
+    /// Bytes per second since the previous refresh.
+    in_rate: f64,
+    out_rate: f64,
+}
+
+/// One refresh worth of data from the API.
+struct Snapshot {
+    folders: Vec<FolderSummary>,
+    devices: Vec<DeviceRow>,
+    taken: Instant,
+}
+
+enum Message {
+    Key(KeyCode),
+    Snapshot(Result<Snapshot>),
+    Event(EventSummary),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Pane {
+    Folders,
+    Devices,
+}
+
+struct App {
+    folders: Vec<FolderSummary>,
+    devices: Vec<DeviceRow>,
+    events: VecDeque<EventSummary>,
+    focus: Pane,
+    folder_state: TableState,
+    device_state: TableState,
+    last_refresh: Option<Instant>,
+    status: String,
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            folders: Vec::new(),
+            devices: Vec::new(),
+            events: VecDeque::new(),
+            focus: Pane::Folders,
+            folder_state: TableState::default(),
+            device_state: TableState::default(),
+            last_refresh: None,
+            status: "Loading...".to_string(),
+        }
+    }
+
+    fn apply(&mut self, mut snapshot: Snapshot) {
+        // Rates come from the byte counters' growth since the last snapshot
+        if let Some(last) = self.last_refresh {
+            let elapsed = snapshot.taken.duration_since(last).as_secs_f64();
+            let previous: HashMap<&str, &DeviceRow> =
+                self.devices.iter().map(|d| (d.id.as_str(), d)).collect();
+            for device in &mut snapshot.devices {
+                if let Some(prev) = previous.get(device.id.as_str())
+                    && elapsed > 0.0
+                {
+                    device.in_rate =
+                        device.in_bytes_total.saturating_sub(prev.in_bytes_total) as f64 / elapsed;
+                    device.out_rate = device.out_bytes_total.saturating_sub(prev.out_bytes_total)
+                        as f64
+                        / elapsed;
+                }
+            }
+        }
+        self.folders = snapshot.folders;
+        self.devices = snapshot.devices;
+        self.last_refresh = Some(snapshot.taken);
+        clamp(&mut self.folder_state, self.folders.len());
+        clamp(&mut self.device_state, self.devices.len());
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let (state, len) = match self.focus {
+            Pane::Folders => (&mut self.folder_state, self.folders.len()),
+            Pane::Devices => (&mut self.device_state, self.devices.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0);
+        let next = if down {
+            (current + 1).min(len - 1)
+        } else {
+            current.saturating_sub(1)
+        };
+        state.select(Some(next));
+    }
+}
+
+fn clamp(state: &mut TableState, len: usize) {
+    if len == 0 {
+        state.select(None);
+    } else {
+        state.select(Some(state.selected().unwrap_or(0).min(len - 1)));
+    }
+}
+
+pub async fn run(client: Client, interval: u64) -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // crossterm's event reading blocks, so it gets its own thread
+    let key_tx = tx.clone();
+    std::thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if let TermEvent::Key(key) = event
+                && key.kind == KeyEventKind::Press
+                && key_tx.send(Message::Key(key.code)).is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let (refresh_tx, refresh_rx) = mpsc::unbounded_channel();
+    tokio::spawn(poll_snapshots(
+        client.clone(),
+        tx.clone(),
+        refresh_rx,
+        interval,
+    ));
+    tokio::spawn(stream_events(client.clone(), tx, refresh_tx.clone()));
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &client, &mut rx, &refresh_tx).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    client: &Client,
+    rx: &mut mpsc::UnboundedReceiver<Message>,
+    refresh: &mpsc::UnboundedSender<()>,
+) -> Result<()> {
+    let mut app = App::new();
+
+    loop {
+        terminal.draw(|frame| draw(frame, &mut app))?;
+
+        let Some(message) = rx.recv().await else {
+            return Ok(());
+        };
+        match message {
+            Message::Snapshot(Ok(snapshot)) => {
+                app.apply(snapshot);
+                app.status.clear();
+            }
+            Message::Snapshot(Err(e)) => app.status = format!("Refresh failed: {:#}", e),
+            Message::Event(event) => {
+                app.events.push_front(event);
+                app.events.truncate(EVENT_HISTORY);
+            }
+            Message::Key(code) => match code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab | KeyCode::BackTab => {
+                    app.focus = match app.focus {
+                        Pane::Folders => Pane::Devices,
+                        Pane::Devices => Pane::Folders,
+                    };
+                }
+                KeyCode::Down | KeyCode::Char('j') => app.move_selection(true),
+                KeyCode::Up | KeyCode::Char('k') => app.move_selection(false),
+                KeyCode::Char('r') => {
+                    let _ = refresh.send(());
+                }
+                KeyCode::Char('p') | KeyCode::Char(' ') => {
+                    app.status = match toggle_paused(client, &app).await {
+                        Ok(message) => message,
+                        Err(e) => format!("{:#}", e),
+                    };
+                    let _ = refresh.send(());
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Pause the selected folder or device, or resume it if it is already paused.
+async fn toggle_paused(client: &Client, app: &App) -> Result<String> {
+    match app.focus {
+        Pane::Folders => {
+            let Some(folder) = app.folder_state.selected().and_then(|i| app.folders.get(i)) else {
+                return Ok(String::new());
+            };
+            let paused = !folder.paused;
+            client
+                .patch_folder(&folder.id, &json!({ "paused": paused }))
+                .await?;
+            let verb = if paused { "Paused" } else { "Resumed" };
+            Ok(format!("{} folder: {}", verb, folder.label))
+        }
+        Pane::Devices => {
+            let Some(device) = app.device_state.selected().and_then(|i| app.devices.get(i)) else {
+                return Ok(String::new());
+            };
+            let paused = !device.paused;
+            client
+                .patch_device(&device.id, &json!({ "paused": paused }))
+                .await?;
+            let verb = if paused { "Paused" } else { "Resumed" };
+            Ok(format!("{} device: {}", verb, device.name))
+        }
+    }
+}
+
+async fn snapshot(client: &Client) -> Result<Snapshot> {
+    let folders = folders::summaries(client).await?;
+    let my_id = client.status().await?.my_id;
+    let connections = client.connections().await?;
+    let devices = client
+        .config_devices()
+        .await?
+        .into_iter()
+        .filter(|d| d.device_id != my_id)
+        .map(|device| {
+            let conn = connections.connections.get(&device.device_id);
+            DeviceRow {
+                name: device.display_name().to_string(),
+                paused: device.paused,
+                connected: conn.is_some_and(|c| c.connected),
+                in_bytes_total: conn.map_or(0, |c| c.in_bytes_total),
+                out_bytes_total: conn.map_or(0, |c| c.out_bytes_total),
+                in_rate: 0.0,
+                out_rate: 0.0,
+                id: device.device_id,
+            }
+        })
+        .collect();
+
+    Ok(Snapshot {
+        folders,
+        devices,
+        taken: Instant::now(),
+    })
+}
+
+/// Refresh on a fixed interval, and early whenever something asks for it.
+async fn poll_snapshots(
+    client: Client,
+    tx: mpsc::UnboundedSender<Message>,
+    mut refresh: mpsc::UnboundedReceiver<()>,
+    interval: u64,
+) {
+    let mut ticker = tokio::time::interval(Duration::from_secs(interval.max(1)));
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            Some(()) = refresh.recv() => {
+                // Coalesce bursts of events into one refresh
+                while refresh.try_recv().is_ok() {}
+            }
+        }
+        if tx.send(Message::Snapshot(snapshot(&client).await)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Long-poll the events endpoint, forwarding events to the UI and asking for
+/// a refresh whenever folder or device state changes.
+async fn stream_events(
+    client: Client,
+    tx: mpsc::UnboundedSender<Message>,
+    refresh: mpsc::UnboundedSender<()>,
+) {
+    let mut since = 0;
+    loop {
+        let limit = if since == 0 { Some(20) } else { None };
+        let events = match client.events(Some(since), limit, &[]).await {
+            Ok(events) => events,
+            Err(_) => {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        let mut changed = false;
+        for event in events {
+            since = event.id;
+            changed |= matches!(
+                event.event_type.as_str(),
+                "StateChanged"
+                    | "FolderSummary"
+                    | "FolderPaused"
+                    | "FolderResumed"
+                    | "DeviceConnected"
+                    | "DeviceDisconnected"
+                    | "DevicePaused"
+                    | "DeviceResumed"
+                    | "ConfigSaved"
+            );
+            if tx.send(Message::Event(summarize(event))).is_err() {
+                return;
+            }
+        }
+        if changed {
+            let _ = refresh.send(());
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, events, footer] = Layout::vertical([
+        Constraint::Percentage(60),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [folders_area, devices_area] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main);
+
+    draw_folders(frame, app, folders_area);
+    draw_devices(frame, app, devices_area);
+    draw_events(frame, app, events);
+
+    let help = "q quit  tab switch pane  ↑/↓ select  p pause/resume  r refresh";
+    let line = if app.status.is_empty() {
+        help.to_string()
+    } else {
+        format!("{}  |  {}", app.status, help)
+    };
+    frame.render_widget(
+        Paragraph::new(line).style(Style::default().fg(Color::DarkGray)),
+        footer,
+    );
+}
+
+fn pane_block(title: &str, focused: bool) -> Block<'_> {
+    let style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(style)
+}
+
+fn highlight() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+fn draw_folders(frame: &mut Frame, app: &mut App, area: Rect) {
+    let rows = app.folders.iter().map(|folder| {
+        let (state, color) = if folder.paused {
+            ("paused".to_string(), Color::DarkGray)
+        } else {
+            let state = folder.state.clone().unwrap_or_else(|| "unknown".into());
+            let color = match state.as_str() {
+                "idle" if folder.need_files == 0 && folder.errors == 0 => Color::Green,
+                "idle" => Color::Yellow,
+                "error" | "unknown" => Color::Red,
+                _ => Color::Cyan,
+            };
+            (state, color)
+        };
+        let need = if folder.need_files > 0 {
+            format!(
+                "{} files, {}",
+                folder.need_files,
+                format_bytes(folder.need_bytes)
+            )
+        } else {
+            String::new()
+        };
+        Row::new(vec![folder.label.clone(), state, need]).style(Style::default().fg(color))
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(40),
+            Constraint::Length(14),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(["Folder", "State", "Need"]).style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(pane_block(" Folders ", app.focus == Pane::Folders))
+    .row_highlight_style(highlight());
+    frame.render_stateful_widget(table, area, &mut app.folder_state);
+}
+
+fn draw_devices(frame: &mut Frame, app: &mut App, area: Rect) {
+    let rows = app.devices.iter().map(|device| {
+        let (state, color) = if device.paused {
+            ("paused", Color::DarkGray)
+        } else if device.connected {
+            ("connected", Color::Green)
+        } else {
+            ("offline", Color::Red)
+        };
+        Row::new(vec![
+            device.name.clone(),
+            state.to_string(),
+            format!("{}/s", format_bytes(device.in_rate as u64)),
+            format!("{}/s", format_bytes(device.out_rate as u64)),
+        ])
+        .style(Style::default().fg(color))
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(
+        Row::new(["Device", "State", "Down", "Up"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(pane_block(" Devices ", app.focus == Pane::Devices))
+    .row_highlight_style(highlight());
+    frame.render_stateful_widget(table, area, &mut app.device_state);
+}
+
+fn draw_events(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .events
+        .iter()
+        .map(|event| {
+            let time = chrono::DateTime::parse_from_rfc3339(&event.time)
+                .map(|t| {
+                    t.with_timezone(&chrono::Local)
+                        .format("%H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            ListItem::new(Line::from(format!(
+                "{} {:<22} {}",
+                time,
+                event.event_type,
+                event.describe()
+            )))
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(pane_block(" Events ", false)), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(in_bytes_total: u64, out_bytes_total: u64) -> DeviceRow {
+        DeviceRow {
+            id: "PEER".to_string(),
+            name: "nas".to_string(),
+            paused: false,
+            connected: true,
+            in_bytes_total,
+            out_bytes_total,
+            in_rate: 0.0,
+            out_rate: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_apply_computes_rates() {
+        let start = Instant::now();
+        let mut app = App::new();
+        app.apply(Snapshot {
+            folders: Vec::new(),
+            devices: vec![device(1000, 500)],
+            taken: start,
+        });
+        assert_eq!(app.devices[0].in_rate, 0.0);
+        assert_eq!(app.device_state.selected(), Some(0));
+
+        app.apply(Snapshot {
+            folders: Vec::new(),
+            devices: vec![device(3000, 500)],
+            taken: start + Duration::from_secs(2),
+        });
+        assert_eq!(app.devices[0].in_rate, 1000.0);
+        assert_eq!(app.devices[0].out_rate, 0.0);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::output::{ActionResult, Output, VersionedFile, VersionsReport, format_bytes};
+use crate::prompt::choose;
+use anyhow::{Result, bail};
+use chrono::DateTime;
+use std::collections::HashMap;
+use syncthing::api::Client;
+use syncthing::glob::path_match;
+use syncthing::models::{FileVersion, NotFound, find_folder};
+
+pub async fn list(client: &Client, out: &Output, query: &str, pattern: Option<&str>) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let versions = client.folder_versions(&folder.id).await?;
+
+    let mut files: Vec<VersionedFile> = versions
+        .into_iter()
+        .filter(|(path, _)| pattern.is_none_or(|p| path_match(p, path)))
+        .map(|(path, mut versions)| {
+            // Newest first
+            versions.sort_by(|a, b| b.version_time.cmp(&a.version_time));
+            VersionedFile { path, versions }
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let report = VersionsReport {
+        folder: folder.id.clone(),
+        files,
+    };
+    out.emit(&report)
+}
+
+/// Restore an archived version of `path`. With `at`, the version whose
+/// archive time matches it (exactly, or as a prefix like `2024-05-01`) is
+/// used; otherwise the user picks when there is more than one.
+pub async fn restore(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    path: &str,
+    at: Option<&str>,
+) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    let mut all = client.folder_versions(&folder.id).await?;
+    let Some(mut versions) = all.remove(path) else {
+        bail!(
+            "No archived versions of '{}' in folder {}",
+            path,
+            folder.display_name()
+        );
+    };
+    versions.sort_by(|a, b| b.version_time.cmp(&a.version_time));
+
+    if let Some(at) = at {
+        versions.retain(|v| matches_time(v, at));
+    }
+
+    let version = match versions.as_slice() {
+        [] => {
+            return Err(NotFound(format!(
+                "No version of '{}' archived at {}",
+                path,
+                at.unwrap_or("?")
+            ))
+            .into());
+        }
+        [only] => only,
+        many => {
+            let options: Vec<String> = many
+                .iter()
+                .map(|v| {
+                    format!(
+                        "archived {}  modified {}  {}",
+                        v.version_time,
+                        v.mod_time,
+                        format_bytes(v.size)
+                    )
+                })
+                .collect();
+            &many[choose(&format!("Versions of {}:", path), &options)?]
+        }
+    };
+
+    let request = HashMap::from([(path.to_string(), version.version_time.clone())]);
+    let errors = client.restore_versions(&folder.id, &request).await?;
+    if let Some(error) = errors.get(path) {
+        bail!("Failed to restore {}: {}", path, error);
+    }
+
+    out.emit(&ActionResult::new(format!(
+        "Restored {} from version archived {}",
+        path, version.version_time
+    )))
+}
+
+fn matches_time(version: &FileVersion, at: &str) -> bool {
+    if version.version_time.starts_with(at) {
+        return true;
+    }
+    match (
+        DateTime::parse_from_rfc3339(&version.version_time),
+        DateTime::parse_from_rfc3339(at),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
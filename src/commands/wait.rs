@@ -0,0 +1,245 @@
+use crate::output::{ActionResult, Output, format_bytes};
+use anyhow::{Result, bail};
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+use std::fmt;
+use std::time::{Duration, Instant};
+use syncthing::api::Client;
+use syncthing::models::{find_device, find_folder};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Block until a folder is idle with nothing left to pull, drawing a
+/// progress bar on stderr while it syncs.
+pub async fn sync(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let folders = client.config_folders().await?;
+    let folder = find_folder(&folders, query)?;
+    if folder.paused {
+        bail!("Folder '{}' is paused", folder.display_name());
+    }
+
+    let bar = bytes_bar(out);
+    let start = Instant::now();
+    loop {
+        let status = client.db_status(&folder.id).await?;
+        if status.state == "idle" && status.need_bytes == 0 {
+            break;
+        }
+        if timeout.is_some_and(|t| start.elapsed() >= t) {
+            bar.finish_and_clear();
+            bail!(
+                "Timed out after {}s waiting for folder '{}' to sync",
+                start.elapsed().as_secs(),
+                folder.display_name()
+            );
+        }
+        bar.set_length(status.global_bytes);
+        bar.set_position(status.in_sync_bytes);
+        bar.set_message(format!(
+            "{}, {} files left",
+            status.state, status.need_files
+        ));
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    bar.finish_and_clear();
+
+    out.emit(&ActionResult::new(format!(
+        "Folder {} is in sync",
+        folder.display_name()
+    )))
+}
+
+/// Block until a device shows up as connected.
+pub async fn device(
+    client: &Client,
+    out: &Output,
+    query: &str,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let devices = client.config_devices().await?;
+    let device = find_device(&devices, query)?;
+
+    let start = Instant::now();
+    loop {
+        let connections = client.connections().await?;
+        if connections
+            .connections
+            .get(&device.device_id)
+            .is_some_and(|c| c.connected)
+        {
+            break;
+        }
+        if timeout.is_some_and(|t| start.elapsed() >= t) {
+            bail!(
+                "Timed out after {}s waiting for device '{}' to connect",
+                start.elapsed().as_secs(),
+                device.display_name()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    out.emit(&ActionResult::new(format!(
+        "Device {} is connected",
+        device.display_name()
+    )))
+}
+
+/// A progress bar on stderr counting bytes, with the rate and an ETA
+/// estimated from recent progress. Hidden unless `out` shows progress.
+pub fn bytes_bar(out: &Output) -> ProgressBar {
+    if !out.show_progress() {
+        return ProgressBar::hidden();
+    }
+    // Sizes in the same units as the rest of the output
+    let style = ProgressStyle::with_template(
+        "[{bar:30}] {percent:>3}% {done} of {size}, {rate}/s, ETA {eta}  {wide_msg}",
+    )
+    .expect("valid template")
+    .with_key("done", |state: &ProgressState, w: &mut dyn fmt::Write| {
+        let _ = w.write_str(&format_bytes(state.pos()));
+    })
+    .with_key("size", |state: &ProgressState, w: &mut dyn fmt::Write| {
+        let _ = w.write_str(&format_bytes(state.len().unwrap_or(0)));
+    })
+    .with_key("rate", |state: &ProgressState, w: &mut dyn fmt::Write| {
+        let _ = w.write_str(&format_bytes(state.per_sec() as u64));
+    })
+    .progress_chars("#>-");
+    ProgressBar::new(0).with_style(style)
+}
+
+/// A spinner on stderr showing `message`. Hidden unless `out` shows progress.
+pub fn spinner(out: &Output, message: &'static str) -> ProgressBar {
+    if !out.show_progress() {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new_spinner().with_message(message);
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_folder(server: &MockServer, state: &str, need_bytes: u64) {
+        Mock::given(method("GET"))
+            .and(path("/rest/config/folders"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": "docs"}])))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/db/status"))
+            .and(query_param("folder", "docs"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"state": state, "needBytes": need_bytes})),
+            )
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_returns_when_idle() {
+        let server = MockServer::start().await;
+        mock_folder(&server, "idle", 0).await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        sync(
+            &client,
+            &Output {
+                json: true,
+                ..Default::default()
+            },
+            "docs",
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn mock_device(server: &MockServer, connected: bool) {
+        Mock::given(method("GET"))
+            .and(path("/rest/config/devices"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!([{"deviceID": "PEER-ID", "name": "laptop"}])),
+            )
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/rest/system/connections"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "connections": {"PEER-ID": {"connected": connected}}
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_device_returns_when_connected() {
+        let server = MockServer::start().await;
+        mock_device(&server, true).await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        device(
+            &client,
+            &Output {
+                json: true,
+                ..Default::default()
+            },
+            "laptop",
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_device_times_out() {
+        let server = MockServer::start().await;
+        mock_device(&server, false).await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let err = device(
+            &client,
+            &Output {
+                json: true,
+                ..Default::default()
+            },
+            "laptop",
+            Some(Duration::ZERO),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_sync_times_out() {
+        let server = MockServer::start().await;
+        mock_folder(&server, "syncing", 100).await;
+        let client = Client::new("key", &server.uri()).unwrap();
+
+        let err = sync(
+            &client,
+            &Output {
+                json: true,
+                ..Default::default()
+            },
+            "docs",
+            Some(Duration::ZERO),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Timed out"));
+    }
+}
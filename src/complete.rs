@@ -0,0 +1,73 @@
+//! Dynamic shell completion of folder and device identifiers.
+//!
+//! The shell calls back into the binary (see `clap_complete::CompleteEnv`),
+//! and these completers ask the default instance for its folders and
+//! devices. Any failure just means no suggestions.
+
+use crate::output::short_id;
+use crate::{Target, get_client};
+use clap_complete::engine::CompletionCandidate;
+use std::future::Future;
+use std::time::Duration;
+use syncthing::api::{self, Client};
+
+/// Don't keep the shell waiting on an unreachable instance.
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Folder IDs, described by their labels.
+pub fn folders() -> Vec<CompletionCandidate> {
+    query(|client| async move {
+        let folders = client.config_folders().await.ok()?;
+        let candidates = folders
+            .iter()
+            .map(|f| {
+                CompletionCandidate::new(&f.id).help(Some(f.display_name().to_string().into()))
+            })
+            .collect();
+        Some(candidates)
+    })
+}
+
+/// Device names (IDs for unnamed devices), described by their short IDs.
+pub fn devices() -> Vec<CompletionCandidate> {
+    query(|client| async move {
+        let devices = client.config_devices().await.ok()?;
+        let candidates = devices
+            .iter()
+            .map(|d| {
+                CompletionCandidate::new(d.display_name())
+                    .help(Some(short_id(&d.device_id).to_string().into()))
+            })
+            .collect();
+        Some(candidates)
+    })
+}
+
+fn query<F, Fut>(fetch: F) -> Vec<CompletionCandidate>
+where
+    F: FnOnce(Client) -> Fut,
+    Fut: Future<Output = Option<Vec<CompletionCandidate>>>,
+{
+    let target = Target {
+        host: None,
+        profile: None,
+        insecure: false,
+        cacert: None,
+        timeout: Some(TIMEOUT),
+        retries: 0,
+        no_cache: false,
+        verbosity: 0,
+        dry_run: false,
+        max_connections: api::DEFAULT_MAX_CONNECTIONS,
+    };
+    let Ok(client) = get_client(&target) else {
+        return Vec::new();
+    };
+    let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return Vec::new();
+    };
+    runtime.block_on(fetch(client)).unwrap_or_default()
+}
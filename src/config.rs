@@ -1,17 +1,114 @@
+use crate::api::{ClientOptions, cert_fingerprint};
+use crate::config_xml::SyncthingConfig;
+use crate::models::GuiConfig;
 use anyhow::{Context, Result};
+use base64::Engine;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Where Syncthing's GUI listens unless configured otherwise.
+pub const DEFAULT_HOST: &str = "http://localhost:8384";
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
     pub api_key: Option<String>,
     pub host: Option<String>,
+    /// Named Syncthing instances, each with its own host and API key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Profile used when none is given on the command line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Extra trusted CA certificate (PEM) for connections without a profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cacert: Option<String>,
+    /// Pinned SHA-256 certificate fingerprints by host URL (trust on first use).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pins: BTreeMap<String, String>,
+    /// The API key lives in the OS keyring instead of this file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyring: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub host: String,
+    pub api_key: String,
+    /// Accept the instance's self-signed TLS certificate without verification.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure: bool,
+    /// Extra trusted CA certificate (PEM) for this instance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cacert: Option<String>,
 }
 
 impl Config {
     pub fn host(&self) -> &str {
-        self.host.as_deref().unwrap_or("http://localhost:8384")
+        self.host.as_deref().unwrap_or(DEFAULT_HOST)
+    }
+
+    /// TLS settings for connecting to `host`, taken from `profile` when one
+    /// is in use and from the top level otherwise.
+    pub fn client_options(&self, host: &str, profile: Option<&Profile>) -> ClientOptions {
+        let cacert = match profile {
+            Some(profile) => profile.cacert.as_ref(),
+            None => self.cacert.as_ref(),
+        };
+        ClientOptions {
+            insecure: profile.is_some_and(|p| p.insecure),
+            ca_cert: cacert.map(PathBuf::from),
+            pinned_cert: self.pins.get(host).cloned(),
+            ..Default::default()
+        }
+    }
+
+    /// The profile named `name`, or the default profile when `name` is `None`.
+    /// Returns `None` when no profile is selected, meaning the top-level
+    /// `host`/`api_key` apply.
+    pub fn profile(&self, name: Option<&str>) -> Result<Option<(&str, &Profile)>> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(None);
+        };
+        match self.profiles.get_key_value(name) {
+            Some((name, profile)) => Ok(Some((name.as_str(), profile))),
+            None => anyhow::bail!(
+                "No profile named '{}' (see 'syncthing config profile list')",
+                name
+            ),
+        }
+    }
+}
+
+/// Environment variables that take precedence over the config files.
+pub const API_KEY_ENV: &str = "SYNCTHING_API_KEY";
+pub const HOST_ENV: &str = "SYNCTHING_HOST";
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+/// `SYNCTHING_HOST`, normalized like `--host`.
+pub fn env_host() -> Option<String> {
+    env_var(HOST_ENV).map(|h| normalize_host(&h))
+}
+
+/// Whether `SYNCTHING_API_KEY` or `SYNCTHING_HOST` is set.
+pub fn env_configured() -> bool {
+    env_var(API_KEY_ENV).is_some() || env_var(HOST_ENV).is_some()
+}
+
+/// Add `http://` to a host given without a scheme (e.g. `192.168.2.32:8384`).
+/// `unix://` hosts name a socket and are kept as they are.
+pub fn normalize_host(host: &str) -> String {
+    if ["http://", "https://", "unix://"]
+        .iter()
+        .any(|scheme| host.starts_with(scheme))
+    {
+        host.to_string()
+    } else {
+        format!("http://{}", host)
     }
 }
 
@@ -22,13 +119,38 @@ fn config_path() -> PathBuf {
         .join("config.json")
 }
 
-fn syncthing_config_path() -> PathBuf {
+/// Where Syncthing keeps its own configuration (and API key).
+pub fn syncthing_config_path() -> PathBuf {
     dirs::config_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("syncthing")
         .join("config.xml")
 }
 
+/// The certificate the local Syncthing GUI serves over HTTPS.
+pub fn syncthing_cert_path() -> PathBuf {
+    syncthing_config_path().with_file_name("https-cert.pem")
+}
+
+/// Where the API responses of one instance are cached, keyed by profile name
+/// or, without a profile, by host.
+pub fn cache_dir(key: &str) -> PathBuf {
+    let key: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("syncthing-cli")
+        .join(key)
+}
+
 pub fn load_config() -> Result<Config> {
     let path = config_path();
     if path.exists() {
@@ -47,39 +169,154 @@ pub fn save_config(config: &Config) -> Result<()> {
     Ok(())
 }
 
-pub fn get_api_key() -> Result<String> {
-    // First check our config
-    let config = load_config()?;
-    if let Some(key) = config.api_key {
-        return Ok(key);
-    }
+/// How to reach Syncthing when no profile is selected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detected {
+    pub host: String,
+    pub api_key: String,
+    /// Fingerprint of the local GUI's own certificate, set when the host was
+    /// read from config.xml and uses HTTPS.
+    pub pinned_cert: Option<String>,
+}
 
-    // Fall back to reading from syncthing's config.xml
-    let st_config = syncthing_config_path();
-    extract_api_key_from_path(&st_config)
+/// Find the host and API key: the environment first, then our config (and
+/// keyring), then the `<gui>` section of Syncthing's config.xml, so a local
+/// install needs no setup even off `localhost:8384` or behind HTTPS.
+pub fn detect(config: &Config) -> Result<Detected> {
+    detect_from(
+        config,
+        env_var(API_KEY_ENV),
+        env_host(),
+        &syncthing_config_path(),
+    )
 }
 
-pub fn extract_api_key_from_path(path: &PathBuf) -> Result<String> {
-    if path.exists() {
-        let content = fs::read_to_string(path).context("Failed to read syncthing config.xml")?;
-        return extract_api_key_from_xml(&content);
+/// `detect` with the environment and the location of config.xml (and the
+/// `https-cert.pem` next to it) passed in.
+fn detect_from(
+    config: &Config,
+    env_key: Option<String>,
+    env_host: Option<String>,
+    path: &Path,
+) -> Result<Detected> {
+    let local = path.exists().then(|| SyncthingConfig::load(path));
+    let gui = local.as_ref().and_then(|l| l.as_ref().ok()).map(|l| &l.gui);
+
+    let (host, pinned_cert) = match env_host.or_else(|| config.host.clone()) {
+        Some(host) => (host, None),
+        None => match gui.and_then(gui_url) {
+            Some(host) => {
+                let cert = path.with_file_name("https-cert.pem");
+                let pin = if host.starts_with("https://") && cert.exists() {
+                    Some(pem_fingerprint(&cert)?)
+                } else {
+                    None
+                };
+                (host, pin)
+            }
+            None => (DEFAULT_HOST.to_string(), None),
+        },
+    };
+
+    let api_key = if let Some(key) = env_key {
+        key
+    } else if let Some(key) = &config.api_key {
+        key.clone()
+    } else if config.keyring {
+        load_keyring_api_key()?
+    } else {
+        match local {
+            Some(local) => Some(local?.gui.api_key)
+                .filter(|key| !key.is_empty())
+                .context("No apikey element found in config")?,
+            None => anyhow::bail!(
+                "No API key found. Either configure with 'syncthing config --api-key <KEY>' \
+                 or ensure syncthing is running with config at {:?}",
+                path
+            ),
+        }
+    };
+
+    Ok(Detected {
+        host,
+        api_key,
+        pinned_cert,
+    })
+}
+
+/// The URL Syncthing's GUI (and API) answers on, as Syncthing itself derives
+/// it: wildcard addresses mean this machine, and a path is a Unix socket.
+pub fn gui_url(gui: &GuiConfig) -> Option<String> {
+    let address = gui.address.trim();
+    if address.is_empty() {
+        return None;
     }
+    if address.starts_with("unix://") {
+        return Some(address.to_string());
+    }
+    if address.starts_with('/') {
+        return Some(format!("unix://{}", address));
+    }
+    let port = ["0.0.0.0", "[::]", ""]
+        .iter()
+        .find_map(|wildcard| address.strip_prefix(wildcard)?.strip_prefix(':'));
+    let scheme = if gui.use_tls { "https" } else { "http" };
+    Some(match port {
+        Some(port) => format!("{}://localhost:{}", scheme, port),
+        None => format!("{}://{}", scheme, address),
+    })
+}
+
+/// SHA-256 fingerprint of the (first) certificate in a PEM file, in the
+/// form `config pin` stores.
+pub fn pem_fingerprint(path: &Path) -> Result<String> {
+    let pem = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read certificate {}", path.display()))?;
+    let body: String = pem
+        .lines()
+        .skip_while(|line| !line.starts_with("-----BEGIN CERTIFICATE-----"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("-----END"))
+        .collect();
+    let der = base64::engine::general_purpose::STANDARD
+        .decode(body.trim())
+        .with_context(|| format!("Invalid certificate {}", path.display()))?;
+    Ok(cert_fingerprint(&der))
+}
+
+const KEYRING_SERVICE: &str = "syncthing-cli";
+const KEYRING_USER: &str = "api-key";
+
+/// Save the API key in the OS credential store.
+#[cfg(feature = "keyring")]
+pub fn store_keyring_api_key(key: &str) -> Result<()> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?
+        .set_password(key)
+        .context("Failed to store API key in the OS keyring")
+}
 
+#[cfg(feature = "keyring")]
+fn load_keyring_api_key() -> Result<String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?
+        .get_password()
+        .context("Failed to read API key from the OS keyring")
+}
+
+#[cfg(not(feature = "keyring"))]
+pub fn store_keyring_api_key(_key: &str) -> Result<()> {
     anyhow::bail!(
-        "No API key found. Either configure with 'syncthing config --api-key <KEY>' \
-         or ensure syncthing is running with config at {:?}",
-        path
+        "Keyring support is not compiled in (rebuild with --features keyring to store {}/{})",
+        KEYRING_SERVICE,
+        KEYRING_USER
     )
 }
 
-pub fn extract_api_key_from_xml(content: &str) -> Result<String> {
-    if let Some(start) = content.find("<apikey>") {
-        let start = start + 8;
-        if let Some(end) = content[start..].find("</apikey>") {
-            return Ok(content[start..start + end].to_string());
-        }
-    }
-    anyhow::bail!("No apikey element found in config")
+#[cfg(not(feature = "keyring"))]
+fn load_keyring_api_key() -> Result<String> {
+    anyhow::bail!(
+        "The API key is stored in the OS keyring, but keyring support is not compiled in \
+         (rebuild with --features keyring)"
+    )
 }
 
 #[cfg(test)]
@@ -98,31 +335,180 @@ mod tests {
     #[test]
     fn test_config_with_custom_host() {
         let config = Config {
-            api_key: None,
             host: Some("http://192.168.1.100:8384".to_string()),
+            ..Default::default()
         };
         assert_eq!(config.host(), "http://192.168.1.100:8384");
     }
 
     #[test]
-    fn test_extract_api_key_from_xml() {
-        let xml = r#"
-<configuration version="37">
-    <gui enabled="true" tls="false" debugging="false" sendBasicAuthPrompt="false">
-        <address>127.0.0.1:8384</address>
-        <apikey>abc123def456</apikey>
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("10.0.0.1:8384"), "http://10.0.0.1:8384");
+        assert_eq!(normalize_host("https://nas:8384"), "https://nas:8384");
+        assert_eq!(
+            normalize_host("unix:///run/syncthing/api.sock"),
+            "unix:///run/syncthing/api.sock"
+        );
+    }
+
+    #[test]
+    fn test_gui_url() {
+        let url = |address: &str, use_tls: bool| {
+            gui_url(&GuiConfig {
+                address: address.to_string(),
+                use_tls,
+                ..Default::default()
+            })
+        };
+        assert_eq!(
+            url("127.0.0.1:8384", false).as_deref(),
+            Some("http://127.0.0.1:8384")
+        );
+        assert_eq!(
+            url("0.0.0.0:8385", true).as_deref(),
+            Some("https://localhost:8385")
+        );
+        assert_eq!(
+            url("[::]:8384", false).as_deref(),
+            Some("http://localhost:8384")
+        );
+        assert_eq!(
+            url(":9000", false).as_deref(),
+            Some("http://localhost:9000")
+        );
+        assert_eq!(
+            url("/run/syncthing/gui.sock", false).as_deref(),
+            Some("unix:///run/syncthing/gui.sock")
+        );
+        assert_eq!(url("", false), None);
+    }
+
+    #[test]
+    fn test_pem_fingerprint() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("https-cert.pem");
+        // "not really DER", which is all the fingerprint looks at
+        fs::write(
+            &path,
+            "-----BEGIN CERTIFICATE-----\nbm90IHJlYWxs\neSBERVI=\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        assert_eq!(
+            pem_fingerprint(&path).unwrap(),
+            cert_fingerprint(b"not really DER")
+        );
+    }
+
+    const LOCAL_XML: &str = r#"<configuration version="37">
+    <gui enabled="true" tls="true">
+        <address>0.0.0.0:8385</address>
+        <apikey>local-key</apikey>
     </gui>
-</configuration>
-"#;
-        let key = extract_api_key_from_xml(xml).unwrap();
-        assert_eq!(key, "abc123def456");
+</configuration>"#;
+
+    /// A config.xml (and https-cert.pem) in a temporary directory.
+    fn syncthing_dir(xml: &str) -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("config.xml"), xml).unwrap();
+        fs::write(
+            dir.path().join("https-cert.pem"),
+            "-----BEGIN CERTIFICATE-----\nYWJj\n-----END CERTIFICATE-----\n",
+        )
+        .unwrap();
+        dir
     }
 
     #[test]
-    fn test_extract_api_key_missing() {
-        let xml = "<configuration></configuration>";
-        let result = extract_api_key_from_xml(xml);
-        assert!(result.is_err());
+    fn test_detect_from_syncthing_config() {
+        let dir = syncthing_dir(LOCAL_XML);
+        let path = dir.path().join("config.xml");
+        let detected = detect_from(&Config::default(), None, None, &path).unwrap();
+        assert_eq!(
+            detected,
+            Detected {
+                host: "https://localhost:8385".to_string(),
+                api_key: "local-key".to_string(),
+                pinned_cert: Some(cert_fingerprint(b"abc")),
+            }
+        );
+
+        // Without the certificate there is nothing to pin
+        fs::remove_file(dir.path().join("https-cert.pem")).unwrap();
+        let detected = detect_from(&Config::default(), None, None, &path).unwrap();
+        assert_eq!(detected.pinned_cert, None);
+
+        let plain = syncthing_dir(&LOCAL_XML.replace(r#"tls="true""#, r#"tls="false""#));
+        let detected = detect_from(
+            &Config::default(),
+            None,
+            None,
+            &plain.path().join("config.xml"),
+        )
+        .unwrap();
+        assert_eq!(detected.host, "http://localhost:8385");
+        assert_eq!(detected.pinned_cert, None);
+    }
+
+    #[test]
+    fn test_detect_precedence() {
+        let dir = syncthing_dir(LOCAL_XML);
+        let path = dir.path().join("config.xml");
+        let mut config = Config {
+            api_key: Some("cli-key".to_string()),
+            host: Some("http://nas:8384".to_string()),
+            ..Default::default()
+        };
+
+        // The environment beats our config
+        let detected = detect_from(
+            &config,
+            Some("env-key".to_string()),
+            Some("http://env:8384".to_string()),
+            &path,
+        )
+        .unwrap();
+        assert_eq!(detected.api_key, "env-key");
+        assert_eq!(detected.host, "http://env:8384");
+        assert_eq!(detected.pinned_cert, None);
+
+        // Our config beats config.xml, and a configured host isn't pinned
+        let detected = detect_from(&config, None, None, &path).unwrap();
+        assert_eq!(detected.api_key, "cli-key");
+        assert_eq!(detected.host, "http://nas:8384");
+        assert_eq!(detected.pinned_cert, None);
+
+        // The keyring beats config.xml
+        config.api_key = None;
+        config.keyring = true;
+        #[cfg(not(feature = "keyring"))]
+        assert!(
+            detect_from(&config, None, None, &path)
+                .unwrap_err()
+                .to_string()
+                .contains("keyring")
+        );
+
+        config.keyring = false;
+        assert_eq!(
+            detect_from(&config, None, None, &path).unwrap().api_key,
+            "local-key"
+        );
+    }
+
+    #[test]
+    fn test_detect_without_syncthing_config() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.xml");
+        let err = detect_from(&Config::default(), None, None, &path).unwrap_err();
+        assert!(err.to_string().contains("No API key found"));
+
+        let detected =
+            detect_from(&Config::default(), Some("key".to_string()), None, &path).unwrap();
+        assert_eq!(detected.host, DEFAULT_HOST);
+
+        fs::write(&path, "<configuration><gui></gui></configuration>").unwrap();
+        let err = detect_from(&Config::default(), None, None, &path).unwrap_err();
+        assert!(err.to_string().contains("No apikey element"));
     }
 
     #[test]
@@ -133,6 +519,7 @@ mod tests {
         let config = Config {
             api_key: Some("test-key".to_string()),
             host: Some("http://test:8384".to_string()),
+            ..Default::default()
         };
 
         // Save
@@ -150,21 +537,23 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_api_key_from_path() {
-        let dir = tempdir().unwrap();
-        let path = dir.path().join("config.xml");
-
-        let xml = "<configuration><gui><apikey>mykey123</apikey></gui></configuration>";
-        fs::write(&path, xml).unwrap();
+    fn test_profile_selection() {
+        let mut config: Config =
+            serde_json::from_str(r#"{"api_key": "local", "host": null}"#).unwrap();
+        assert!(config.profiles.is_empty());
+        assert_eq!(config.profile(None).unwrap(), None);
 
-        let key = extract_api_key_from_path(&path).unwrap();
-        assert_eq!(key, "mykey123");
-    }
+        let nas = Profile {
+            host: "http://nas:8384".to_string(),
+            api_key: "nas-key".to_string(),
+            insecure: false,
+            cacert: None,
+        };
+        config.profiles.insert("nas".to_string(), nas.clone());
+        assert_eq!(config.profile(Some("nas")).unwrap(), Some(("nas", &nas)));
+        assert!(config.profile(Some("laptop")).is_err());
 
-    #[test]
-    fn test_extract_api_key_from_missing_file() {
-        let path = PathBuf::from("/nonexistent/config.xml");
-        let result = extract_api_key_from_path(&path);
-        assert!(result.is_err());
+        config.default_profile = Some("nas".to_string());
+        assert_eq!(config.profile(None).unwrap(), Some(("nas", &nas)));
     }
 }
@@ -0,0 +1,202 @@
+//! Syncthing's own `config.xml`, read directly so folders and devices can
+//! be inspected while the daemon (and with it the REST API) is down.
+//!
+//! Folders, devices and the GUI section are read into the same models the
+//! API returns. The rest of the file (options, defaults, ...) is skipped, so
+//! this is no substitute for `/rest/config`.
+
+use crate::models::{DeviceConfig, DiskSize, FolderConfig, FolderDevice, GuiConfig, Versioning};
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+use std::path::Path;
+
+/// The parts of `config.xml` the CLI understands.
+#[derive(Debug, Clone, Default)]
+pub struct SyncthingConfig {
+    pub version: u64,
+    pub folders: Vec<FolderConfig>,
+    pub devices: Vec<DeviceConfig>,
+    pub gui: GuiConfig,
+}
+
+impl SyncthingConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let xml = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&xml).with_context(|| format!("Invalid Syncthing config {}", path.display()))
+    }
+
+    pub fn parse(xml: &str) -> Result<Self> {
+        let doc = Document::parse(xml)?;
+        let root = doc.root_element();
+        let mut config = Self {
+            version: root
+                .attribute("version")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            ..Default::default()
+        };
+        for node in root.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "folder" => config.folders.push(folder(node)),
+                "device" => config.devices.push(device(node)),
+                "gui" => config.gui = gui(node),
+                _ => {}
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn folder(node: Node) -> FolderConfig {
+    let mut versioning = Versioning::default();
+    if let Some(v) = child(node, "versioning") {
+        versioning.versioning_type = v.attribute("type").unwrap_or_default().to_string();
+        versioning.cleanup_interval_s = number(text(v, "cleanupIntervalS"));
+        versioning.fs_path = text(v, "fsPath").to_string();
+        versioning.params = v
+            .children()
+            .filter(|p| p.has_tag_name("param"))
+            .filter_map(|p| Some((p.attribute("key")?.into(), p.attribute("val")?.into())))
+            .collect();
+    }
+    let min_disk_free = child(node, "minDiskFree").map_or_else(DiskSize::default, |m| DiskSize {
+        value: m.text().unwrap_or_default().trim().parse().unwrap_or(0.0),
+        unit: m.attribute("unit").unwrap_or_default().to_string(),
+    });
+    FolderConfig {
+        id: attr(node, "id"),
+        label: attr(node, "label"),
+        path: attr(node, "path"),
+        folder_type: attr(node, "type"),
+        devices: node
+            .children()
+            .filter(|d| d.has_tag_name("device"))
+            .map(|d| FolderDevice {
+                device_id: attr(d, "id"),
+                introduced_by: attr(d, "introducedBy"),
+                encryption_password: text(d, "encryptionPassword").to_string(),
+            })
+            .collect(),
+        paused: text(node, "paused") == "true",
+        rescan_interval_s: number(node.attribute("rescanIntervalS").unwrap_or_default()),
+        fs_watcher_enabled: node.attribute("fsWatcherEnabled") == Some("true"),
+        versioning,
+        min_disk_free,
+    }
+}
+
+fn device(node: Node) -> DeviceConfig {
+    DeviceConfig {
+        device_id: attr(node, "id"),
+        name: attr(node, "name"),
+        addresses: node
+            .children()
+            .filter(|a| a.has_tag_name("address"))
+            .filter_map(|a| a.text())
+            .map(|a| a.trim().to_string())
+            .collect(),
+        compression: attr(node, "compression"),
+        introducer: node.attribute("introducer") == Some("true"),
+        paused: text(node, "paused") == "true",
+        auto_accept_folders: text(node, "autoAcceptFolders") == "true",
+        max_send_kbps: number(text(node, "maxSendKbps")),
+        max_recv_kbps: number(text(node, "maxRecvKbps")),
+        untrusted: text(node, "untrusted") == "true",
+    }
+}
+
+fn gui(node: Node) -> GuiConfig {
+    GuiConfig {
+        enabled: node.attribute("enabled") != Some("false"),
+        address: text(node, "address").to_string(),
+        user: text(node, "user").to_string(),
+        password: text(node, "password").to_string(),
+        use_tls: node.attribute("tls") == Some("true"),
+        api_key: text(node, "apikey").to_string(),
+        theme: text(node, "theme").to_string(),
+        auth_mode: text(node, "authMode").to_string(),
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|c| c.has_tag_name(name))
+}
+
+fn text<'a>(node: Node<'a, '_>, name: &str) -> &'a str {
+    child(node, name)
+        .and_then(|c| c.text())
+        .unwrap_or_default()
+        .trim()
+}
+
+fn attr(node: Node, name: &str) -> String {
+    node.attribute(name).unwrap_or_default().to_string()
+}
+
+fn number(text: &str) -> u64 {
+    text.trim().parse().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<configuration version="37">
+    <folder id="docs" label="Documents" path="~/Documents" type="sendreceive" rescanIntervalS="3600" fsWatcherEnabled="true">
+        <filesystemType>basic</filesystemType>
+        <device id="AAA" introducedBy="">
+            <encryptionPassword></encryptionPassword>
+        </device>
+        <device id="BBB" introducedBy=""></device>
+        <minDiskFree unit="%">1</minDiskFree>
+        <versioning type="simple">
+            <param key="keep" val="5"></param>
+            <cleanupIntervalS>3600</cleanupIntervalS>
+            <fsPath></fsPath>
+        </versioning>
+        <paused>true</paused>
+    </folder>
+    <device id="BBB" name="nas" compression="metadata" introducer="false">
+        <address>dynamic</address>
+        <address>tcp://nas:22000</address>
+        <paused>false</paused>
+        <maxSendKbps>500</maxSendKbps>
+    </device>
+    <gui enabled="true" tls="true" debugging="false">
+        <address>0.0.0.0:8384</address>
+        <apikey>secret-key</apikey>
+    </gui>
+    <options>
+        <listenAddress>default</listenAddress>
+        <listenAddress>quic://0.0.0.0:22000</listenAddress>
+        <relaysEnabled>false</relaysEnabled>
+        <maxSendKbps>0</maxSendKbps>
+    </options>
+</configuration>"#;
+
+    #[test]
+    fn test_parse() {
+        let config = SyncthingConfig::parse(XML).unwrap();
+        assert_eq!(config.version, 37);
+
+        let folder = &config.folders[0];
+        assert_eq!(folder.display_name(), "Documents");
+        assert_eq!(folder.path, "~/Documents");
+        assert!(folder.paused);
+        assert_eq!(folder.rescan_interval_s, 3600);
+        assert_eq!(folder.devices.len(), 2);
+        assert_eq!(folder.versioning.versioning_type, "simple");
+        assert_eq!(folder.versioning.params["keep"], "5");
+        assert_eq!(folder.min_disk_free.unit, "%");
+
+        let device = &config.devices[0];
+        assert_eq!(device.name, "nas");
+        assert_eq!(device.addresses, ["dynamic", "tcp://nas:22000"]);
+        assert_eq!(device.max_send_kbps, 500);
+
+        assert_eq!(config.gui.address, "0.0.0.0:8384");
+        assert!(config.gui.use_tls);
+        assert_eq!(config.gui.api_key, "secret-key");
+    }
+}
@@ -0,0 +1,116 @@
+//! `--output csv|tsv`: the JSON form of a command's output as delimited
+//! rows, one per list item, under a header of field names.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delimiter {
+    /// RFC 4180 CSV: fields with commas, quotes or newlines are quoted.
+    Comma,
+    /// Tab-separated, with tabs, newlines and backslashes escaped as `\t`,
+    /// `\n` and `\\`.
+    Tab,
+}
+
+impl Delimiter {
+    /// The header and rows for `value`. Columns are the top-level keys of
+    /// the items in order of first appearance; nested values are written as
+    /// compact JSON.
+    pub fn lines(self, value: &Value, header: bool) -> Vec<String> {
+        let items = match value {
+            Value::Array(items) => items.as_slice(),
+            value => std::slice::from_ref(value),
+        };
+        let mut columns: Vec<&str> = Vec::new();
+        for item in items {
+            if let Value::Object(fields) = item {
+                for key in fields.keys() {
+                    if !columns.contains(&key.as_str()) {
+                        columns.push(key);
+                    }
+                }
+            }
+        }
+
+        let mut lines = Vec::new();
+        if header && !columns.is_empty() {
+            lines.push(self.join(columns.iter().map(|c| c.to_string())));
+        }
+        for item in items {
+            let line = match item {
+                Value::Object(fields) => self.join(columns.iter().map(|c| cell(fields.get(*c)))),
+                other => self.join([cell(Some(other))]),
+            };
+            lines.push(line);
+        }
+        lines
+    }
+
+    fn join(self, cells: impl IntoIterator<Item = String>) -> String {
+        let (separator, escape): (&str, fn(&str) -> String) = match self {
+            Delimiter::Comma => (",", quote_csv),
+            Delimiter::Tab => ("\t", escape_tsv),
+        };
+        cells
+            .into_iter()
+            .map(|c| escape(&c))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+fn quote_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn escape_tsv(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_csv() {
+        let value = json!([
+            {"id": "docs", "label": "Docs, \"work\"", "needBytes": 0},
+            {"id": "photos", "label": "Photos", "needBytes": 12, "state": null},
+        ]);
+        assert_eq!(
+            Delimiter::Comma.lines(&value, true),
+            [
+                "id,label,needBytes,state",
+                "docs,\"Docs, \"\"work\"\"\",0,",
+                "photos,Photos,12,",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsv_nested_without_header() {
+        let value = json!({"id": 7, "type": "StateChanged", "data": {"to": "idle"}});
+        assert_eq!(
+            Delimiter::Tab.lines(&value, false),
+            ["7\tStateChanged\t{\"to\":\"idle\"}"]
+        );
+        assert_eq!(Delimiter::Tab.lines(&json!(["a\tb"]), true), ["a\\tb"]);
+    }
+}
@@ -0,0 +1,116 @@
+//! Device ID validation.
+//!
+//! A device ID is the base32 SHA-256 of the device's certificate: 52
+//! characters, split into four groups of 13 that each get a Luhn mod 32
+//! check character, written as eight dash-separated groups of seven.
+
+use anyhow::{Result, bail};
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Canonicalize a typed or pasted device ID, rejecting it if a check
+/// character doesn't match.
+///
+/// Like Syncthing, this ignores case, dashes and spaces, reads `0`, `1` and
+/// `8` as `O`, `I` and `B`, and accepts the old 52-character form without
+/// check characters.
+pub fn normalize(id: &str) -> Result<String> {
+    let chars: Vec<u8> = id
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| match c.to_ascii_uppercase() {
+            '0' => 'O',
+            '1' => 'I',
+            '8' => 'B',
+            c => c,
+        })
+        .map(|c| {
+            u8::try_from(c)
+                .ok()
+                .filter(|b| ALPHABET.contains(b))
+                .ok_or_else(|| anyhow::anyhow!("Invalid character '{}' in device ID", c))
+        })
+        .collect::<Result<_>>()?;
+
+    let data: Vec<u8> = match chars.len() {
+        56 => {
+            let mut data = Vec::with_capacity(52);
+            for (i, group) in chars.chunks(14).enumerate() {
+                let (digits, check) = group.split_at(13);
+                if luhn32(digits) != check[0] {
+                    bail!(
+                        "Device ID has a wrong check character in group {}; it was probably mistyped",
+                        i + 1
+                    );
+                }
+                data.extend_from_slice(digits);
+            }
+            data
+        }
+        52 => chars,
+        n => bail!(
+            "A device ID has 56 characters (not counting dashes), got {}",
+            n
+        ),
+    };
+
+    let mut full = Vec::with_capacity(56);
+    for digits in data.chunks(13) {
+        full.extend_from_slice(digits);
+        full.push(luhn32(digits));
+    }
+    let groups: Vec<&str> = full
+        .chunks(7)
+        .map(|group| std::str::from_utf8(group).expect("base32 is ASCII"))
+        .collect();
+    Ok(groups.join("-"))
+}
+
+/// Luhn mod N check character over the base32 alphabet, as Syncthing
+/// computes it.
+fn luhn32(digits: &[u8]) -> u8 {
+    let n = ALPHABET.len();
+    let mut factor = 1;
+    let mut sum = 0;
+    for &c in digits {
+        let codepoint = ALPHABET.iter().position(|&a| a == c).unwrap_or(0);
+        let addend = factor * codepoint;
+        factor = if factor == 2 { 1 } else { 2 };
+        sum += addend / n + addend % n;
+    }
+    ALPHABET[(n - sum % n) % n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "MFZWI3D-BONSGYC-YLTMRWG-C43ENR5-QXGZDMM-FZWI3DP-BONSGYY-LTMRWAD";
+
+    #[test]
+    fn test_normalize_accepts_sloppy_input() {
+        assert_eq!(normalize(ID).unwrap(), ID);
+        assert_eq!(normalize(&ID.to_lowercase().replace('-', " ")).unwrap(), ID);
+        // Digits that are easily confused with letters
+        assert_eq!(normalize(&ID.replace('O', "0")).unwrap(), ID);
+    }
+
+    #[test]
+    fn test_normalize_adds_missing_check_characters() {
+        let old: String = ID
+            .replace('-', "")
+            .chars()
+            .enumerate()
+            .filter(|(i, _)| i % 14 != 13)
+            .map(|(_, c)| c)
+            .collect();
+        assert_eq!(normalize(&old).unwrap(), ID);
+    }
+
+    #[test]
+    fn test_normalize_rejects_typos() {
+        assert!(normalize(&ID.replacen("MFZ", "MFY", 1)).is_err());
+        assert!(normalize("MFZWI3D-BONSGYC").is_err());
+        assert!(normalize(&ID.replacen('M', "9", 1)).is_err());
+    }
+}
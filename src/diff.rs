@@ -0,0 +1,92 @@
+//! Minimal line diff for previewing text changes.
+
+use crate::output::Output;
+
+/// One line of a diff.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+/// Diff two sequences of lines using a longest-common-subsequence table.
+/// Inputs are small (ignore files, config fragments), so O(n*m) is fine.
+pub fn diff_lines<'a>(old: &'a [String], new: &'a [String]) -> Vec<DiffLine<'a>> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            result.push(DiffLine::Same(&old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(DiffLine::Removed(&old[i]));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(&new[j]));
+            j += 1;
+        }
+    }
+    result.extend(old[i..].iter().map(|l| DiffLine::Removed(l)));
+    result.extend(new[j..].iter().map(|l| DiffLine::Added(l)));
+    result
+}
+
+/// Print only the changed lines, prefixed with `+`/`-`, to stderr (unless
+/// `--quiet`).
+pub fn print_changes(out: &Output, diff: &[DiffLine]) {
+    for line in diff {
+        match line {
+            DiffLine::Added(l) => out.info(format!("+ {}", l)),
+            DiffLine::Removed(l) => out.info(format!("- {}", l)),
+            DiffLine::Same(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &[&str]) -> Vec<String> {
+        s.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let old = lines(&["a", "b", "c"]);
+        let new = lines(&["a", "c", "d"]);
+        assert_eq!(
+            diff_lines(&old, &new),
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Removed("b"),
+                DiffLine::Same("c"),
+                DiffLine::Added("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let old = lines(&["x", "y"]);
+        assert!(
+            diff_lines(&old, &old)
+                .iter()
+                .all(|l| matches!(l, DiffLine::Same(_)))
+        );
+    }
+}
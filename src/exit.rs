@@ -0,0 +1,74 @@
+//! Process exit codes, so scripts can branch on the kind of failure
+//! instead of parsing error messages. Code 2 is clap's, for invalid
+//! arguments.
+
+use syncthing::api::ApiError;
+use syncthing::models::NotFound;
+
+pub const OK: i32 = 0;
+/// Any failure not covered below.
+pub const FAILURE: i32 = 1;
+/// Syncthing could not be reached, or rejected the API key.
+pub const CONNECTION: i32 = 3;
+/// Syncthing answered a request with an error.
+pub const API: i32 = 4;
+/// A folder, device, profile or file that was asked for doesn't exist.
+pub const NOT_FOUND: i32 = 5;
+/// `check` found a folder over its thresholds.
+pub const THRESHOLD: i32 = 6;
+
+/// The exit code for a command that failed with `error`, decided by the
+/// first cause in its chain that says what went wrong.
+pub fn code_for(error: &anyhow::Error) -> i32 {
+    for cause in error.chain() {
+        if let Some(api) = cause.downcast_ref::<ApiError>() {
+            return match api.status.as_u16() {
+                401 | 403 => CONNECTION,
+                404 => NOT_FOUND,
+                _ => API,
+            };
+        }
+        if cause.is::<NotFound>() {
+            return NOT_FOUND;
+        }
+        if cause.is::<reqwest::Error>() {
+            return CONNECTION;
+        }
+    }
+    FAILURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use reqwest::StatusCode;
+
+    fn api_error(status: StatusCode) -> anyhow::Error {
+        ApiError {
+            status,
+            message: String::new(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_code_for() {
+        assert_eq!(code_for(&api_error(StatusCode::FORBIDDEN)), CONNECTION);
+        assert_eq!(code_for(&api_error(StatusCode::NOT_FOUND)), NOT_FOUND);
+        assert_eq!(
+            code_for(&api_error(StatusCode::BAD_REQUEST).context("Failed to add folder")),
+            API
+        );
+        let missing: anyhow::Error = NotFound("No folder with ID or label 'x'".into()).into();
+        assert_eq!(code_for(&missing), NOT_FOUND);
+        assert_eq!(code_for(&anyhow!("Aborted")), FAILURE);
+    }
+
+    #[tokio::test]
+    async fn test_code_for_unreachable() {
+        let client = syncthing::api::Client::new("key", "http://127.0.0.1:1").unwrap();
+        let error = client.status().await.unwrap_err();
+        assert_eq!(code_for(&error), CONNECTION);
+    }
+}
@@ -0,0 +1,141 @@
+//! Glob matching with Syncthing's wildcard semantics.
+//!
+//! - `*` matches any run of characters except `/`
+//! - `**` matches any run of characters including `/`
+//! - `?` matches a single character except `/`
+//! - `[abc]`, `[a-z]` and `[!a-z]` match character classes
+//! - `\` escapes the next character
+
+/// Match `text` against `pattern`. The whole text must match.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    match_from(&pattern, &text)
+}
+
+/// Match a pattern against a path. Patterns without a `/` also match the
+/// last path component, so `*.txt` finds `notes/a.txt`.
+pub fn path_match(pattern: &str, path: &str) -> bool {
+    if glob_match(pattern, path) {
+        return true;
+    }
+    !pattern.contains('/') && glob_match(pattern, path.rsplit('/').next().unwrap_or(path))
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return text.is_empty();
+    };
+
+    match first {
+        '*' => {
+            let (crosses_dirs, rest) = match rest.split_first() {
+                Some(('*', after)) => (true, after),
+                _ => (false, rest),
+            };
+            for i in 0..=text.len() {
+                if match_from(rest, &text[i..]) {
+                    return true;
+                }
+                if i < text.len() && text[i] == '/' && !crosses_dirs {
+                    return false;
+                }
+            }
+            false
+        }
+        '?' => match text.split_first() {
+            Some((&c, text_rest)) if c != '/' => match_from(rest, text_rest),
+            _ => false,
+        },
+        '[' => {
+            let Some((&c, text_rest)) = text.split_first() else {
+                return false;
+            };
+            match match_class(rest, c) {
+                Some((true, after)) => match_from(after, text_rest),
+                Some((false, _)) => false,
+                // Unterminated class: treat `[` literally
+                None => c == '[' && match_from(rest, text_rest),
+            }
+        }
+        '\\' if !rest.is_empty() => match text.split_first() {
+            Some((&c, text_rest)) if c == rest[0] => match_from(&rest[1..], text_rest),
+            _ => false,
+        },
+        _ => match text.split_first() {
+            Some((&c, text_rest)) if c == first => match_from(rest, text_rest),
+            _ => false,
+        },
+    }
+}
+
+/// Match `c` against the class starting right after `[`. Returns whether it
+/// matched and the pattern after the closing `]`.
+fn match_class(class: &[char], c: char) -> Option<(bool, &[char])> {
+    let (negated, mut i) = match class.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    let mut matched = false;
+    let mut first = true;
+    while i < class.len() {
+        if class[i] == ']' && !first {
+            return Some((matched != negated, &class[i + 1..]));
+        }
+        first = false;
+        if i + 2 < class.len() && class[i + 1] == '-' && class[i + 2] != ']' {
+            if class[i] <= c && c <= class[i + 2] {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                matched = true;
+            }
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_star_stops_at_separator() {
+        assert!(glob_match("*.txt", "notes.txt"));
+        assert!(!glob_match("*.txt", "dir/notes.txt"));
+        assert!(glob_match("dir/*", "dir/notes.txt"));
+    }
+
+    #[test]
+    fn test_double_star_crosses_directories() {
+        assert!(glob_match("**.txt", "a/b/c.txt"));
+        assert!(glob_match("docs/**", "docs/a/b"));
+        assert!(!glob_match("docs/**", "other/a"));
+    }
+
+    #[test]
+    fn test_question_mark_and_classes() {
+        assert!(glob_match("file?.log", "file1.log"));
+        assert!(!glob_match("file?.log", "file10.log"));
+        assert!(glob_match("[a-c]at", "bat"));
+        assert!(!glob_match("[!a-c]at", "bat"));
+        assert!(glob_match("[!a-c]at", "rat"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert!(glob_match(r"\*.txt", "*.txt"));
+        assert!(!glob_match(r"\*.txt", "a.txt"));
+    }
+
+    #[test]
+    fn test_path_match_uses_basename() {
+        assert!(path_match("*.txt", "notes/a.txt"));
+        assert!(!path_match("notes/*.md", "notes/a.txt"));
+        assert!(path_match("notes/*.txt", "notes/a.txt"));
+    }
+}
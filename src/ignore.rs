@@ -0,0 +1,149 @@
+//! Local evaluation of Syncthing's ignore patterns.
+//!
+//! Works on the expanded patterns from `/rest/db/ignores`, in which Syncthing
+//! has already resolved `#include`s, stripped comments and added the
+//! variants that let `foo` match in subdirectories (`**/foo`) and everything
+//! inside it (`foo/**`). Patterns are tried in order and the first one that
+//! matches decides, so a `!` pattern only rescues paths from patterns below it.
+
+use crate::glob::glob_match;
+
+/// Names Syncthing keeps to itself and never syncs, whatever the patterns say.
+const INTERNAL_NAMES: &[&str] = &[".stfolder", ".stignore", ".stversions"];
+
+/// One pattern with its prefixes split off.
+#[derive(Debug, PartialEq)]
+pub struct Pattern<'a> {
+    /// The pattern as Syncthing reported it, prefixes included.
+    pub line: &'a str,
+    glob: &'a str,
+    /// `!`: matching paths are synced rather than ignored.
+    pub negated: bool,
+    /// `(?i)`: matched case-insensitively.
+    pub fold_case: bool,
+    /// `(?d)`: may be deleted when it keeps a directory from being removed.
+    pub deletable: bool,
+}
+
+impl<'a> Pattern<'a> {
+    /// Parse a pattern line, accepting `!`, `(?i)` and `(?d)` once each in
+    /// any order. Blank lines and `//` comments yield `None`.
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let mut pattern = Pattern {
+            line,
+            glob: line.trim(),
+            negated: false,
+            fold_case: false,
+            deletable: false,
+        };
+        loop {
+            if !pattern.negated
+                && let Some(rest) = pattern.glob.strip_prefix('!')
+            {
+                pattern.negated = true;
+                pattern.glob = rest;
+            } else if !pattern.fold_case
+                && let Some(rest) = pattern.glob.strip_prefix("(?i)")
+            {
+                pattern.fold_case = true;
+                pattern.glob = rest;
+            } else if !pattern.deletable
+                && let Some(rest) = pattern.glob.strip_prefix("(?d)")
+            {
+                pattern.deletable = true;
+                pattern.glob = rest;
+            } else {
+                break;
+            }
+        }
+        if pattern.glob.is_empty() || pattern.glob.starts_with("//") {
+            return None;
+        }
+        Some(pattern)
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        if self.fold_case {
+            glob_match(&self.glob.to_lowercase(), &path.to_lowercase())
+        } else {
+            glob_match(self.glob, path)
+        }
+    }
+}
+
+/// The first of `patterns` that matches `path`, which is relative to the
+/// folder root.
+pub fn first_match<'a>(patterns: &'a [String], path: &str) -> Option<Pattern<'a>> {
+    let path = normalize(path);
+    patterns
+        .iter()
+        .filter_map(|line| Pattern::parse(line))
+        .find(|pattern| pattern.matches(&path))
+}
+
+/// Whether Syncthing skips `path` on its own: its marker and ignore files,
+/// the versions directory, and temporary files of transfers in progress.
+pub fn is_internal(path: &str) -> bool {
+    let path = normalize(path);
+    let first = path.split('/').next().unwrap_or_default();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    INTERNAL_NAMES.contains(&first)
+        || (name.ends_with(".tmp")
+            && (name.starts_with(".syncthing.") || name.starts_with("~syncthing~")))
+}
+
+/// `path` the way Syncthing sees it: relative, with `/` separators and no
+/// trailing slash.
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = path.trim_start_matches("./").trim_matches('/');
+    path.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_prefixes() {
+        let pattern = Pattern::parse("(?d)!(?i)Thumbs.db").unwrap();
+        assert!(pattern.negated && pattern.fold_case && pattern.deletable);
+        assert!(pattern.matches("thumbs.DB"));
+        assert_eq!(Pattern::parse("// comment"), None);
+        assert_eq!(Pattern::parse("  "), None);
+    }
+
+    #[test]
+    fn test_first_match_wins() {
+        let patterns = expanded(&[
+            "!important.log",
+            "!**/important.log",
+            "*.log",
+            "**/*.log",
+            "node_modules",
+            "node_modules/**",
+            "**/node_modules",
+            "**/node_modules/**",
+        ]);
+        let matched = |path| first_match(&patterns, path).map(|p| p.line);
+        assert_eq!(matched("logs/important.log"), Some("!**/important.log"));
+        assert_eq!(matched("logs/debug.log"), Some("**/*.log"));
+        assert_eq!(
+            matched("/web/node_modules/x/y.js"),
+            Some("**/node_modules/**")
+        );
+        assert_eq!(matched("src/main.rs"), None);
+    }
+
+    #[test]
+    fn test_is_internal() {
+        assert!(is_internal(".stfolder"));
+        assert!(is_internal(".stversions/a~20240101.txt"));
+        assert!(is_internal("docs/.syncthing.report.pdf.tmp"));
+        assert!(!is_internal("docs/.stignore.bak"));
+    }
+}
@@ -0,0 +1,29 @@
+//! Client library for the Syncthing REST API.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use syncthing::api::{Client, ClientOptions};
+//! use syncthing::config;
+//!
+//! // Host, API key and certificate of the local Syncthing, from its config.xml
+//! let local = config::detect(&config::load_config()?)?;
+//! let options = ClientOptions {
+//!     pinned_cert: local.pinned_cert,
+//!     ..Default::default()
+//! };
+//! let client = Client::with_options(&local.api_key, &local.host, &options)?;
+//! for folder in client.config_folders().await? {
+//!     let status = client.db_status(&folder.id).await?;
+//!     println!("{}: {}", folder.display_name(), status.state);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod api;
+pub mod config;
+pub mod config_xml;
+pub mod device_id;
+pub mod glob;
+pub mod ignore;
+pub mod models;
@@ -1,18 +1,99 @@
-mod api;
-mod config;
+mod commands;
+mod complete;
+mod delimited;
+mod diff;
+mod exit;
+mod output;
+mod prompt;
+mod table;
+mod template;
 
-use anyhow::Result;
-use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand};
+use anyhow::{Result, bail};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::CompleteEnv;
+use clap_complete::engine::ArgValueCandidates;
+use output::Output;
+use std::io::IsTerminal;
+use std::process::ExitCode;
+use syncthing::{api, config};
 
 #[derive(Parser)]
 #[command(name = "syncthing")]
 #[command(about = "Syncthing CLI for monitoring and control")]
+#[command(
+    after_help = "Exit codes: 0 ok, 1 other failure, 2 usage, 3 connection or auth failure, \
+                  4 API error, 5 not found, 6 check threshold exceeded"
+)]
 struct Cli {
-    /// Host URL (e.g., 192.168.2.32:8384 or http://host:8384)
+    /// Host URL (e.g., 192.168.2.32:8384, http://host:8384 or unix:///run/syncthing/api.sock)
     #[arg(short = 'H', long, global = true)]
     host: Option<String>,
 
+    /// Use a configured profile instead of the default one
+    #[arg(short, long, global = true)]
+    profile: Option<String>,
+
+    /// Skip TLS certificate verification (for self-signed GUI certificates)
+    #[arg(long, global = true)]
+    insecure: bool,
+
+    /// Trust this PEM certificate, e.g. Syncthing's https-cert.pem
+    #[arg(long, global = true, value_name = "PATH")]
+    cacert: Option<String>,
+
+    /// Give up on a request after this long (e.g. 10s, 2m)
+    #[arg(long, global = true, value_name = "DURATION", value_parser = commands::parse_duration)]
+    request_timeout: Option<std::time::Duration>,
+
+    /// Retry connection failures and server errors this many times
+    #[arg(long, global = true, default_value = "2")]
+    retries: u32,
+
+    /// Most API requests to have in flight at once when a command fans out
+    #[arg(long, global = true, value_name = "N", default_value_t = api::DEFAULT_MAX_CONNECTIONS as u16,
+          value_parser = clap::value_parser!(u16).range(1..))]
+    max_connections: u16,
+
+    /// Always ask Syncthing instead of reusing recently cached folder/device lists
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Print the requests (and JSON bodies) that would change Syncthing's
+    /// state instead of sending them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Print machine-readable JSON instead of human-readable text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Print each item through a template, e.g. '{{.label}}\t{{.state}}'
+    /// (fields as in --json output)
+    #[arg(long, global = true, value_name = "TEMPLATE", value_parser = template::Template::parse, conflicts_with = "json")]
+    format: Option<template::Template>,
+
+    /// Print lists as CSV or TSV rows with a header (fields as in --json output)
+    #[arg(long, global = true, value_enum, conflicts_with_all = ["json", "format"])]
+    output: Option<OutputMode>,
+
+    /// Print only essential values (IDs, results) and no progress or notes
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Color text output: auto colors when writing to a terminal and
+    /// NO_COLOR is not set
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    color: ColorChoice,
+
+    /// Log API requests to stderr; repeat (-vv) to include bodies
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+
+    /// Read Syncthing's config.xml instead of asking the API, for when the
+    /// daemon is down (folders and devices only)
+    #[arg(long)]
+    offline: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -20,24 +101,222 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Show system status
-    Status,
+    Status {
+        /// Refresh every SECS seconds (default 2) until interrupted
+        #[arg(short, long, value_name = "SECS", num_args = 0..=1, default_missing_value = "2")]
+        watch: Option<u64>,
+    },
+    /// Show this node's device ID as text and a QR code
+    Id {
+        /// Only print the ID
+        #[arg(long)]
+        no_qr: bool,
+    },
     /// List folders with sync status
+    #[command(args_conflicts_with_subcommands = true)]
     Folders {
-        /// Show detailed info for a specific folder
+        #[command(subcommand)]
+        command: Option<FoldersCommand>,
+        /// Show the sync status of one folder (ID, label, or path)
         #[arg(short, long)]
         id: Option<String>,
+        /// Refresh every SECS seconds (default 2) until interrupted
+        #[arg(short, long, value_name = "SECS", num_args = 0..=1, default_missing_value = "2", conflicts_with = "id")]
+        watch: Option<u64>,
+        /// Only show paused folders
+        #[arg(long, conflicts_with = "id")]
+        paused: bool,
+        /// Only show folders that are syncing
+        #[arg(long, conflicts_with = "id")]
+        syncing: bool,
+        /// Only show folders with errors
+        #[arg(long, conflicts_with = "id")]
+        errors: bool,
+        /// Only show idle folders
+        #[arg(long, conflicts_with = "id")]
+        idle: bool,
+        /// Sort by bytes needed or folder size (largest first), or by name
+        #[arg(long, value_enum, conflicts_with = "id")]
+        sort: Option<FolderSortKey>,
+    },
+    /// Completion of every folder on every device, as a table
+    Matrix,
+    /// Manage individual folders
+    Folder {
+        #[command(subcommand)]
+        command: FolderCommand,
     },
     /// List connected devices
-    Devices,
+    Devices {
+        /// Only show connected devices
+        #[arg(long, conflicts_with = "offline")]
+        connected: bool,
+        /// Only show devices that are not connected
+        #[arg(long)]
+        offline: bool,
+        /// Only show offline devices not seen for this long (e.g. 7d)
+        #[arg(long, value_name = "DURATION", value_parser = commands::parse_duration, conflicts_with = "connected")]
+        stale: Option<std::time::Duration>,
+        /// Sort by last seen (most recent first) or by name
+        #[arg(long, value_enum)]
+        sort: Option<DeviceSortKey>,
+    },
+    /// Manage individual devices
+    Device {
+        #[command(subcommand)]
+        command: DeviceCommand,
+    },
+    /// Manage folder ignore patterns (.stignore)
+    Ignores {
+        #[command(subcommand)]
+        command: IgnoresCommand,
+    },
+    /// Browse a folder's contents as Syncthing sees them
+    Browse {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Directory inside the folder to start from
+        prefix: Option<String>,
+        /// How many directory levels to descend (0 = only direct children)
+        #[arg(short = 'L', long)]
+        levels: Option<u32>,
+    },
+    /// Show local and global database info for a file
+    File {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Path of the file relative to the folder root
+        path: String,
+    },
+    /// Download a file before the rest of its folder's queue
+    Prio {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Path of the file relative to the folder root
+        path: String,
+    },
+    /// Find files by name in every folder, e.g. `search invoice` or `search '*.pdf'`
+    Search {
+        /// Glob (with *, ? or [...]) or case-insensitive substring of the path
+        pattern: String,
+    },
+    /// Make the config match a manifest of devices, folders and options
+    Apply {
+        /// Manifest file (YAML or JSON; - for stdin)
+        #[arg(short, long)]
+        file: String,
+        /// Also remove folders and devices the manifest leaves out
+        #[arg(long)]
+        prune: bool,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Open a folder's directory in the file manager (-q: just print its path)
+    Open {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+    },
+    /// List the files a folder still has to download
+    Need {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Only the N largest files, biggest first
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+    },
+    /// List locally changed files in a receive-only folder
+    #[command(name = "localchanged")]
+    LocalChanged {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+    },
+    /// Inspect and restore archived file versions
+    Versions {
+        #[command(subcommand)]
+        command: VersionsCommand,
+    },
+    /// Show connection details (address, transport, crypto) per device
+    Connections,
+    /// Validate device IDs
+    DeviceId {
+        #[command(subcommand)]
+        command: DeviceIdCommand,
+    },
+    /// Show where each device was discovered and whether discovery works
+    Discovery,
+    /// List or toggle Syncthing's debug logging facilities
+    Debug {
+        #[command(subcommand)]
+        command: Option<DebugCommand>,
+    },
+    /// Show how much space each folder takes, largest first, with totals
+    Du {
+        /// Add up every configured profile instead of one instance
+        #[arg(long)]
+        all_profiles: bool,
+    },
+    /// Overview of every configured profile
+    Cluster {
+        #[command(subcommand)]
+        command: ClusterCommand,
+    },
+    /// Show or change the templates new folders and devices start from
+    Defaults {
+        #[command(subcommand)]
+        command: DefaultsCommand,
+    },
+    /// Show or change GUI settings (listen address, TLS, credentials)
+    Gui {
+        #[command(subcommand)]
+        command: Option<GuiCommand>,
+    },
+    /// Show or change bandwidth limits
+    Limits {
+        #[command(subcommand)]
+        command: Option<LimitsCommand>,
+    },
+    /// Show or change global options
+    Options {
+        #[command(subcommand)]
+        command: Option<OptionsCommand>,
+    },
+    /// Block until a condition is met (for scripts)
+    Wait {
+        #[command(subcommand)]
+        command: WaitCommand,
+    },
+    /// Find sync-conflict files in a folder
+    Conflicts {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+    },
     /// Trigger folder rescan
     Scan {
-        /// Folder ID (rescan all if not specified)
+        /// Folder ID, label, or a local path inside it (rescan all if not specified)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
         folder: Option<String>,
+        /// Only rescan this path inside the folder (repeatable)
+        #[arg(long, value_name = "PATH", requires = "folder")]
+        sub: Vec<String>,
+        /// Postpone the folder's next regular scan by SECS seconds
+        #[arg(long, value_name = "SECS", requires = "folder")]
+        next: Option<u64>,
+        /// Return only once the scan has finished, showing its progress
+        #[arg(long)]
+        wait: bool,
     },
     /// Show sync errors
     Errors {
-        /// Show errors for specific folder
-        #[arg(short, long)]
+        /// Show errors for a specific folder (ID, label, or local path)
+        #[arg(short, long, add = ArgValueCandidates::new(complete::folders))]
         folder: Option<String>,
         /// Clear all errors
         #[arg(short, long)]
@@ -45,385 +324,1612 @@ enum Commands {
     },
     /// Show pending devices and folders
     Pending,
+    /// Show where config, database, log file and certificates live on the host
+    Paths,
+    /// Check that the API is reachable and measure its latency
+    Ping {
+        /// Number of pings to send
+        #[arg(short, long, default_value = "1")]
+        count: u32,
+    },
     /// Restart syncthing
-    Restart,
+    Restart {
+        /// Wait for Syncthing to come back up, giving up after DURATION
+        /// (default 2m)
+        #[arg(
+            long,
+            value_name = "DURATION",
+            num_args = 0..=1,
+            default_missing_value = "2m",
+            value_parser = commands::parse_duration
+        )]
+        wait: Option<std::time::Duration>,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
     /// Shutdown syncthing
-    Shutdown,
+    Shutdown {
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
     /// Show recent events
     Events {
         /// Number of events to show
         #[arg(short, long, default_value = "20")]
         limit: u32,
+        /// Keep streaming new events as they happen
+        #[arg(short, long)]
+        follow: bool,
+        /// Only show these event types (comma-separated, e.g. ItemFinished,FolderSummary)
+        #[arg(short, long = "type", alias = "types", value_delimiter = ',')]
+        types: Vec<String>,
+    },
+    /// Show recent Syncthing log lines
+    Log {
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value = "50")]
+        lines: usize,
+        /// Keep printing new lines as they are logged
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Keep a log of finished syncs, folder errors and device connections
+    Monitor {
+        /// File to append JSON records to
+        #[arg(long, value_name = "PATH")]
+        log_file: std::path::PathBuf,
+        /// Rotate the log once it reaches this many megabytes
+        #[arg(long, value_name = "MB", default_value = "10")]
+        max_size: u64,
+        /// Number of rotated logs to keep
+        #[arg(long, default_value = "5")]
+        keep: u32,
+    },
+    /// Serve Prometheus metrics for this Syncthing instance
+    Exporter {
+        /// Address to listen on
+        #[arg(short, long, default_value = "0.0.0.0:9100")]
+        listen: String,
+        /// Seconds between polls of the Syncthing API
+        #[arg(short, long, default_value = "15")]
+        interval: u64,
+    },
+    /// Full-screen dashboard of folders, devices and events
+    #[command(alias = "dashboard")]
+    Tui {
+        /// Seconds between refreshes (events also trigger a refresh)
+        #[arg(short, long, default_value = "5")]
+        interval: u64,
+    },
+    /// Exit 0 when in sync, 6 when out of sync, 3 when unreachable (for monitoring)
+    Check {
+        /// Bytes a folder may still need before it counts as out of sync
+        #[arg(long, default_value = "0")]
+        max_need_bytes: u64,
+        /// Maximum time since a folder's last scan (e.g. 30m, 2h, 1d)
+        #[arg(long, value_parser = commands::parse_duration)]
+        max_age: Option<std::time::Duration>,
     },
     /// Configure API key and host
     Config {
+        #[command(subcommand)]
+        command: Option<ConfigCommand>,
         /// API key
         #[arg(long)]
         api_key: Option<String>,
         /// Host URL (e.g., http://localhost:8384)
         #[arg(long)]
         host: Option<String>,
+        /// Store the API key in the OS keyring instead of the config file
+        #[arg(long, requires = "api_key")]
+        keyring: bool,
     },
+    /// Print a shell completion script, e.g. `syncthing completions bash > /etc/bash_completion.d/syncthing`
+    Completions { shell: clap_complete::Shell },
 }
 
-fn get_client(host_override: Option<&str>) -> Result<api::Client> {
-    let api_key = config::get_api_key()?;
-    let cfg = config::load_config()?;
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Manage named Syncthing instances
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+    /// Save Syncthing's full configuration as JSON (secrets redacted)
+    Export {
+        /// File to write instead of stdout
+        #[arg(short, long)]
+        file: Option<String>,
+        /// Keep API keys and passwords in the export
+        #[arg(long)]
+        include_secrets: bool,
+    },
+    /// Trust a certificate for HTTPS (default: Syncthing's https-cert.pem)
+    TrustCert {
+        /// PEM file to trust
+        path: Option<String>,
+    },
+    /// Pin the certificate the GUI presents now (trust on first use)
+    Pin {
+        /// Forget the pinned certificate instead
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Check whether a restart is needed to apply config changes
+    RestartRequired {
+        /// Restart Syncthing now if it is
+        #[arg(long)]
+        restart: bool,
+    },
+    /// Show or change the LDAP server the GUI can authenticate against
+    Ldap {
+        #[command(subcommand)]
+        command: Option<LdapCommand>,
+    },
+    /// Merge a raw JSON fragment into Syncthing's configuration
+    Patch {
+        /// JSON object, e.g. '{"options": {"natEnabled": false}}' (stdin if omitted or -)
+        fragment: Option<String>,
+    },
+}
 
-    let host = match host_override {
-        Some(h) => {
-            // Add http:// if no scheme provided
-            if h.starts_with("http://") || h.starts_with("https://") {
-                h.to_string()
-            } else {
-                format!("http://{}", h)
+#[derive(Subcommand)]
+enum LdapCommand {
+    /// Show LDAP settings
+    Get,
+    /// Change LDAP settings
+    Set {
+        /// Server address (e.g. ldap.example.com:636)
+        #[arg(long)]
+        address: Option<String>,
+        /// DN to bind as, with %s for the user name (e.g. uid=%s,ou=people,dc=example,dc=com)
+        #[arg(long)]
+        bind_dn: Option<String>,
+        /// How to connect to the server
+        #[arg(long)]
+        transport: Option<LdapTransport>,
+        /// Accept any certificate from the server
+        #[arg(long)]
+        insecure_skip_verify: Option<bool>,
+        /// Where to search for the user (needed with --search-filter)
+        #[arg(long)]
+        search_base_dn: Option<String>,
+        /// Filter the user must match, with %s for the user name
+        #[arg(long)]
+        search_filter: Option<String>,
+        /// Make the GUI log in through LDAP (false: its own user and password)
+        #[arg(long)]
+        gui_auth: Option<bool>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum LdapTransport {
+    Plain,
+    Tls,
+    #[value(name = "starttls")]
+    StartTls,
+}
+
+impl LdapTransport {
+    /// The value used for `transport` in the LDAP config.
+    fn as_str(self) -> &'static str {
+        match self {
+            LdapTransport::Plain => "plain",
+            LdapTransport::Tls => "tls",
+            LdapTransport::StartTls => "starttls",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ProfileCommand {
+    /// List profiles (the default is marked with *)
+    List,
+    /// Add or replace a profile
+    Add {
+        /// Profile name
+        name: String,
+        /// Host URL (e.g., http://nas:8384)
+        #[arg(long)]
+        host: String,
+        /// API key of that instance
+        #[arg(long)]
+        api_key: String,
+    },
+    /// Remove a profile
+    Remove {
+        /// Profile name
+        name: String,
+    },
+    /// Make a profile the default
+    Use {
+        /// Profile name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum FolderCommand {
+    /// Pause syncing a folder
+    Pause {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(required_unless_present = "all", add = ArgValueCandidates::new(complete::folders))]
+        folder: Option<String>,
+        /// Pause every folder
+        #[arg(long, conflicts_with = "folder")]
+        all: bool,
+    },
+    /// Resume syncing a paused folder
+    Resume {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(required_unless_present = "all", add = ArgValueCandidates::new(complete::folders))]
+        folder: Option<String>,
+        /// Resume every folder
+        #[arg(long, conflicts_with = "folder")]
+        all: bool,
+    },
+    /// Accept a pending folder offered by a remote device
+    Accept {
+        /// ID of the offered folder
+        folder: String,
+        /// Offering device (ID, short ID, or name); required if several devices offer it
+        #[arg(long, add = ArgValueCandidates::new(complete::devices))]
+        from: Option<String>,
+        /// Local path to store the folder at (not needed if the folder already exists)
+        #[arg(long)]
+        path: Option<String>,
+    },
+    /// Create a new folder
+    Add {
+        /// Folder ID (must be the same on all devices sharing it)
+        #[arg(long)]
+        id: String,
+        /// Local path of the folder
+        #[arg(long)]
+        path: String,
+        /// Human-readable label
+        #[arg(long)]
+        label: Option<String>,
+        /// Folder type
+        #[arg(long = "type", value_enum, default_value_t = FolderType::SendReceive)]
+        folder_type: FolderType,
+        /// Devices to share with (IDs, short IDs, or names; comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        share: Vec<String>,
+    },
+    /// Remove a folder from the config (files on disk are kept)
+    Remove {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Discard local changes in a receive-only folder
+    Revert {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Overwrite remote changes in a send-only folder with the local state
+    Override {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Tune a folder, e.g. `folder set docs rescanIntervalS=3600 fsWatcherDelayS=30`
+    ///
+    /// Keys: rescanIntervalS, fsWatcherEnabled, fsWatcherDelayS, order, copiers,
+    /// hashers, ignorePerms, minDiskFree (e.g. 5% or 10GB)
+    Set {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// key=value pairs
+        #[arg(required = true)]
+        assignments: Vec<String>,
+    },
+    /// Show or configure file versioning, e.g. `--type staggered --max-age 30d`
+    Versioning {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Versioning type to switch to (omit to show the current settings)
+        #[arg(long = "type", value_enum)]
+        versioning_type: Option<VersioningType>,
+        /// Number of old versions to keep (simple)
+        #[arg(long, requires = "versioning_type")]
+        keep: Option<u32>,
+        /// Delete versions older than this many days; 0 keeps them forever (simple, trashcan)
+        #[arg(long, requires = "versioning_type")]
+        cleanout_days: Option<u32>,
+        /// Maximum age of versions, e.g. 30d (staggered)
+        #[arg(long, requires = "versioning_type", value_parser = commands::parse_duration)]
+        max_age: Option<std::time::Duration>,
+        /// Command to run instead of deleting or replacing a file (external)
+        #[arg(long, requires = "versioning_type")]
+        command: Option<String>,
+        /// Directory to store versions in instead of .stversions
+        #[arg(long, requires = "versioning_type")]
+        path: Option<String>,
+        /// How often to clean out old versions, e.g. 1h
+        #[arg(long, requires = "versioning_type", value_parser = commands::parse_duration)]
+        cleanup_interval: Option<std::time::Duration>,
+    },
+    /// Switch a folder between sendreceive, sendonly and receiveonly
+    SetType {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// New folder type
+        #[arg(value_enum)]
+        folder_type: FolderType,
+        /// Don't ask for confirmation when changes are pending
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Share a folder with one or more devices
+    Share {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Devices to share with (IDs, short IDs, or names; comma-separated)
+        #[arg(long = "with", value_delimiter = ',', required = true, add = ArgValueCandidates::new(complete::devices))]
+        devices: Vec<String>,
+    },
+    /// Encrypt a folder for an untrusted device (prompts for the password)
+    SetPassword {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Device the folder is shared with (ID, short ID, or name)
+        #[arg(long, add = ArgValueCandidates::new(complete::devices))]
+        device: String,
+        /// Remove the password, trusting the device with the folder again
+        #[arg(long)]
+        clear: bool,
+    },
+    /// List the devices sharing a folder by how far behind they are
+    Lag {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+    },
+    /// Stop sharing a folder with one or more devices
+    Unshare {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Devices to stop sharing with (IDs, short IDs, or names; comma-separated)
+        #[arg(long = "with", value_delimiter = ',', required = true, add = ArgValueCandidates::new(complete::devices))]
+        devices: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+#[value(rename_all = "lower")]
+enum FolderType {
+    SendReceive,
+    SendOnly,
+    ReceiveOnly,
+    ReceiveEncrypted,
+}
+
+impl FolderType {
+    /// The value used for `type` in the folder config.
+    fn as_str(self) -> &'static str {
+        match self {
+            FolderType::SendReceive => "sendreceive",
+            FolderType::SendOnly => "sendonly",
+            FolderType::ReceiveOnly => "receiveonly",
+            FolderType::ReceiveEncrypted => "receiveencrypted",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
             }
         }
-        None => cfg.host().to_string(),
-    };
+    }
+}
 
-    api::Client::new(&api_key, &host)
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputMode {
+    Csv,
+    Tsv,
 }
 
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
+impl OutputMode {
+    fn delimiter(self) -> delimited::Delimiter {
+        match self {
+            OutputMode::Csv => delimited::Delimiter::Comma,
+            OutputMode::Tsv => delimited::Delimiter::Tab,
+        }
+    }
+}
 
-    if bytes >= TB {
-        format!("{:.1} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
+#[derive(Clone, Copy, ValueEnum)]
+enum FolderSortKey {
+    Need,
+    Size,
+    Name,
+}
+
+impl FolderSortKey {
+    fn into_sort(self) -> commands::folders::FolderSort {
+        use commands::folders::FolderSort;
+        match self {
+            FolderSortKey::Need => FolderSort::Need,
+            FolderSortKey::Size => FolderSort::Size,
+            FolderSortKey::Name => FolderSort::Name,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DeviceSortKey {
+    LastSeen,
+    Name,
+}
+
+impl DeviceSortKey {
+    fn into_sort(self) -> commands::devices::DeviceSort {
+        use commands::devices::DeviceSort;
+        match self {
+            DeviceSortKey::LastSeen => DeviceSort::LastSeen,
+            DeviceSortKey::Name => DeviceSort::Name,
+        }
     }
 }
 
-fn format_duration_since(timestamp: &str) -> String {
-    if let Ok(dt) = DateTime::parse_from_rfc3339(timestamp) {
-        let now = Utc::now();
-        let duration = now.signed_duration_since(dt.with_timezone(&Utc));
+#[derive(Clone, Copy, ValueEnum)]
+enum VersioningType {
+    None,
+    Simple,
+    Trashcan,
+    Staggered,
+    External,
+}
 
-        if duration.num_days() > 0 {
-            format!("{}d ago", duration.num_days())
-        } else if duration.num_hours() > 0 {
-            format!("{}h ago", duration.num_hours())
-        } else if duration.num_minutes() > 0 {
-            format!("{}m ago", duration.num_minutes())
-        } else {
-            "just now".to_string()
+impl VersioningType {
+    /// The value used for `versioning.type` in the folder config.
+    fn as_str(self) -> &'static str {
+        match self {
+            VersioningType::None => "",
+            VersioningType::Simple => "simple",
+            VersioningType::Trashcan => "trashcan",
+            VersioningType::Staggered => "staggered",
+            VersioningType::External => "external",
         }
+    }
+}
+
+#[derive(Subcommand)]
+enum DeviceCommand {
+    /// Pause syncing with a device
+    Pause {
+        /// Device ID, short ID, or name
+        #[arg(required_unless_present = "all", add = ArgValueCandidates::new(complete::devices))]
+        device: Option<String>,
+        /// Pause every remote device
+        #[arg(long, conflicts_with = "device")]
+        all: bool,
+    },
+    /// Resume syncing with a paused device
+    Resume {
+        /// Device ID, short ID, or name
+        #[arg(required_unless_present = "all", add = ArgValueCandidates::new(complete::devices))]
+        device: Option<String>,
+        /// Resume every remote device
+        #[arg(long, conflicts_with = "device")]
+        all: bool,
+    },
+    /// Remove a device from the config
+    Remove {
+        /// Device ID, short ID, or name
+        #[arg(add = ArgValueCandidates::new(complete::devices))]
+        device: String,
+        /// Also remove the device from every folder it is shared with
+        #[arg(long)]
+        unshare: bool,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Change device settings, e.g. `device set nas introducer=true compression=always`
+    ///
+    /// Keys: introducer, autoAcceptFolders, compression (metadata, always, never),
+    /// allowedNetworks (comma-separated CIDRs), untrusted
+    Set {
+        /// Device ID, short ID, or name
+        #[arg(add = ArgValueCandidates::new(complete::devices))]
+        device: String,
+        /// key=value pairs
+        #[arg(required = true)]
+        assignments: Vec<String>,
+    },
+    /// Change a device's display name
+    Rename {
+        /// Device ID, short ID, or current name
+        #[arg(add = ArgValueCandidates::new(complete::devices))]
+        device: String,
+        /// New name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum IgnoresCommand {
+    /// Print a folder's ignore patterns
+    Get {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Show patterns after #include expansion
+        #[arg(long)]
+        expanded: bool,
+    },
+    /// Replace a folder's ignore patterns
+    Set {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Read patterns from this file instead of stdin
+        #[arg(short, long)]
+        file: Option<String>,
+    },
+    /// Edit a folder's ignore patterns in $EDITOR
+    Edit {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+    },
+    /// Check whether paths would be ignored, and by which pattern
+    Test {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Paths relative to the folder root
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Add a curated set of patterns, e.g. `--apply node,macos`
+    Preset {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Presets to add (comma-separated)
+        #[arg(long, value_delimiter = ',', required = true)]
+        apply: Vec<IgnorePreset>,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum IgnorePreset {
+    Node,
+    Python,
+    Macos,
+    WindowsJunk,
+}
+
+impl IgnorePreset {
+    fn into_preset(self) -> commands::ignores::Preset {
+        use commands::ignores::Preset;
+        match self {
+            IgnorePreset::Node => Preset::Node,
+            IgnorePreset::Python => Preset::Python,
+            IgnorePreset::Macos => Preset::MacOs,
+            IgnorePreset::WindowsJunk => Preset::WindowsJunk,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum VersionsCommand {
+    /// List archived versions of files in a folder
+    List {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Only show paths matching this glob (e.g. "*.pdf" or "docs/**")
+        pattern: Option<String>,
+    },
+    /// Restore an archived version of a file
+    Restore {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Path of the file relative to the folder root
+        path: String,
+        /// Archive time of the version to restore (RFC 3339, or a prefix like 2024-05-01)
+        #[arg(long)]
+        at: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ClusterCommand {
+    /// Query all profiles at once and show version, uptime, sync and errors
+    Status,
+}
+
+#[derive(Subcommand)]
+enum DefaultsCommand {
+    /// Show the folder template, or change it, e.g. `defaults folder path=~/Sync versioning=simple`
+    ///
+    /// Keys: path, type, versioning (none, simple, trashcan, staggered, external),
+    /// rescanIntervalS, fsWatcherEnabled, fsWatcherDelayS, ignorePerms, minDiskFree, order
+    Folder {
+        /// key=value pairs to change
+        assignments: Vec<String>,
+    },
+    /// Show the device template, or change it, e.g. `defaults device autoAcceptFolders=true`
+    ///
+    /// Keys: addresses, introducer, autoAcceptFolders, compression, allowedNetworks, untrusted
+    Device {
+        /// key=value pairs to change
+        assignments: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum GuiCommand {
+    /// Show GUI settings
+    Get,
+    /// Change GUI settings
+    Set {
+        /// Listen address (e.g. 0.0.0.0:8384)
+        #[arg(long)]
+        address: Option<String>,
+        /// Serve the GUI over HTTPS
+        #[arg(long)]
+        tls: Option<bool>,
+        /// GUI user name
+        #[arg(long)]
+        user: Option<String>,
+        /// GUI password (hashed by Syncthing)
+        #[arg(long)]
+        password: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FoldersCommand {
+    /// Show each folder's last scan and the last file received from peers
+    Stats,
+}
+
+#[derive(Subcommand)]
+enum DeviceIdCommand {
+    /// Verify a pasted device ID and print it in canonical form
+    Check {
+        /// Device ID (case, dashes and spaces don't matter)
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DebugCommand {
+    /// List debug facilities (enabled ones are marked with *)
+    List,
+    /// Enable debug logging for facilities, e.g. `model connections`
+    Enable {
+        #[arg(required = true)]
+        facilities: Vec<String>,
+    },
+    /// Disable debug logging for facilities
+    Disable {
+        #[arg(required = true)]
+        facilities: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LimitsCommand {
+    /// Show global and per-device bandwidth limits
+    Get,
+    /// Set bandwidth limits in KiB/s (0 = unlimited)
+    Set {
+        /// Limit only this device (ID, short ID, or name) instead of globally
+        #[arg(long, add = ArgValueCandidates::new(complete::devices))]
+        device: Option<String>,
+        /// Download limit
+        #[arg(long)]
+        down: Option<u64>,
+        /// Upload limit
+        #[arg(long)]
+        up: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum OptionsCommand {
+    /// Show relaysEnabled, natEnabled, local/globalAnnounceEnabled and urAccepted
+    Get,
+    /// Change options, e.g. `options set relaysEnabled=false natEnabled=true`
+    Set {
+        /// key=value pairs
+        #[arg(required = true)]
+        assignments: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum WaitCommand {
+    /// Wait until a folder is idle with nothing left to sync
+    Sync {
+        /// Folder ID, label, or a local path inside it (e.g. .)
+        #[arg(add = ArgValueCandidates::new(complete::folders))]
+        folder: String,
+        /// Give up after this long (e.g. 90s, 10m, 1h)
+        #[arg(long, value_parser = commands::parse_duration)]
+        timeout: Option<std::time::Duration>,
+    },
+    /// Wait until a device is connected
+    Device {
+        /// Device ID, short ID, or name
+        #[arg(add = ArgValueCandidates::new(complete::devices))]
+        device: String,
+        /// Give up after this long (e.g. 90s, 10m, 1h)
+        #[arg(long, value_parser = commands::parse_duration)]
+        timeout: Option<std::time::Duration>,
+    },
+}
+
+impl Commands {
+    /// Whether the command writes Syncthing's config, so a pending restart
+    /// is worth pointing out afterwards.
+    fn changes_config(&self) -> bool {
+        match self {
+            Commands::Folder { command } => !matches!(
+                command,
+                FolderCommand::Revert { .. }
+                    | FolderCommand::Override { .. }
+                    | FolderCommand::Lag { .. }
+            ),
+            Commands::Device { .. } | Commands::Apply { .. } => true,
+            Commands::Defaults { command } => match command {
+                DefaultsCommand::Folder { assignments }
+                | DefaultsCommand::Device { assignments } => !assignments.is_empty(),
+            },
+            Commands::Gui { command } => matches!(command, Some(GuiCommand::Set { .. })),
+            Commands::Limits { command } => matches!(command, Some(LimitsCommand::Set { .. })),
+            Commands::Options { command } => matches!(command, Some(OptionsCommand::Set { .. })),
+            Commands::Config { command, .. } => {
+                matches!(
+                    command,
+                    Some(ConfigCommand::Patch { .. })
+                        | Some(ConfigCommand::Ldap {
+                            command: Some(LdapCommand::Set { .. })
+                        })
+                )
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Which Syncthing instance to talk to, as given by the global flags.
+struct Target<'a> {
+    host: Option<&'a str>,
+    profile: Option<&'a str>,
+    insecure: bool,
+    cacert: Option<&'a str>,
+    timeout: Option<std::time::Duration>,
+    retries: u32,
+    no_cache: bool,
+    verbosity: u8,
+    dry_run: bool,
+    max_connections: usize,
+}
+
+/// How long cached folder and device lists are reused.
+const CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(10);
+
+fn get_client(target: &Target) -> Result<api::Client> {
+    let cfg = config::load_config()?;
+
+    // An explicit --profile beats the environment; the default profile doesn't
+    let selected = if target.profile.is_some() || !config::env_configured() {
+        cfg.profile(target.profile)?
     } else {
-        timestamp.to_string()
+        None
+    };
+    let (profile_name, selected) = selected.unzip();
+    let (host, api_key, detected_pin) = match selected {
+        Some(profile) => (profile.host.clone(), profile.api_key.clone(), None),
+        None => {
+            let detected = config::detect(&cfg)?;
+            (detected.host, detected.api_key, detected.pinned_cert)
+        }
+    };
+    let (host, detected_pin) = match target.host {
+        Some(h) => (config::normalize_host(h), None),
+        None => (host, detected_pin),
+    };
+
+    let mut options = cfg.client_options(&host, selected);
+    options.insecure |= target.insecure;
+    if let Some(path) = target.cacert {
+        options.ca_cert = Some(path.into());
+    }
+    // Syncthing's own certificate, unless something else is trusted already
+    if options.pinned_cert.is_none() && options.ca_cert.is_none() && !options.insecure {
+        options.pinned_cert = detected_pin;
+    }
+    options.timeout = target.timeout;
+    options.retries = target.retries;
+    options.verbosity = target.verbosity;
+    options.dry_run = target.dry_run;
+    options.max_connections = Some(target.max_connections);
+    if !target.no_cache {
+        let key = match profile_name {
+            Some(name) if target.host.is_none() => name,
+            _ => host.as_str(),
+        };
+        options.cache_dir = Some(config::cache_dir(key));
+        options.cache_ttl = CACHE_TTL;
+    }
+    api::Client::with_options(&api_key, &host, &options)
+}
+
+fn main() -> ExitCode {
+    // Answers the shell's completion requests (`COMPLETE=bash syncthing`) and exits
+    CompleteEnv::with_factory(Cli::command).complete();
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{} {:?}", output::paint("Error:", output::Color::Red), e);
+            ExitCode::from(exit::code_for(&e) as u8)
+        }
+    }
+}
+
+/// The read-only commands `--offline` can answer from config.xml.
+fn run_offline(command: Commands, out: &Output) -> Result<()> {
+    match command {
+        // Filters need live state, so only the plain listings are offered
+        Commands::Folders {
+            command: None,
+            id: None,
+            watch: None,
+            paused: false,
+            syncing: false,
+            errors: false,
+            idle: false,
+            sort: None,
+        } => commands::offline::folders(out),
+        Commands::Devices {
+            connected: false,
+            offline: false,
+            stale: None,
+            sort: None,
+        } => commands::offline::devices(out),
+        // config.xml holds only part of /rest/config, under other names, so an
+        // export from it would look complete without being so
+        Commands::Config {
+            command: Some(ConfigCommand::Export { .. }),
+            ..
+        } => bail!(
+            "config export needs a running Syncthing; config.xml can't be turned into a \
+             complete /rest/config export"
+        ),
+        _ => bail!("--offline only works with plain `folders` and `devices`"),
     }
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn run() -> Result<()> {
     let cli = Cli::parse();
-    let host_override = cli.host.as_deref();
+    let target = Target {
+        host: cli.host.as_deref(),
+        profile: cli.profile.as_deref(),
+        insecure: cli.insecure,
+        cacert: cli.cacert.as_deref(),
+        timeout: cli.request_timeout,
+        retries: cli.retries,
+        no_cache: cli.no_cache,
+        verbosity: cli.verbose,
+        dry_run: cli.dry_run,
+        max_connections: usize::from(cli.max_connections),
+    };
+    output::set_color(cli.color.enabled());
+    let out = Output {
+        json: cli.json,
+        quiet: cli.quiet,
+        format: cli.format,
+        delimited: cli.output.map(OutputMode::delimiter),
+        ..Default::default()
+    };
+    if cli.offline {
+        return run_offline(cli.command, &out);
+    }
+    let changes_config = cli.command.changes_config();
 
     match cli.command {
-        Commands::Config { api_key, host } => {
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "syncthing",
+                &mut std::io::stdout(),
+            );
+        }
+        Commands::Config {
+            command: Some(ConfigCommand::Profile { command }),
+            ..
+        } => match command {
+            ProfileCommand::List => commands::config::profile_list(&out)?,
+            ProfileCommand::Add {
+                name,
+                host,
+                api_key,
+            } => commands::config::profile_add(
+                &out,
+                &name,
+                &host,
+                &api_key,
+                target.insecure,
+                target.cacert,
+            )?,
+            ProfileCommand::Remove { name } => commands::config::profile_remove(&out, &name)?,
+            ProfileCommand::Use { name } => commands::config::profile_use(&out, &name)?,
+        },
+        Commands::Config {
+            command:
+                Some(ConfigCommand::Export {
+                    file,
+                    include_secrets,
+                }),
+            ..
+        } => {
+            let client = get_client(&target)?;
+            commands::config::export(&client, &out, file.as_deref(), include_secrets).await?;
+        }
+        Commands::Config {
+            command: Some(ConfigCommand::TrustCert { path }),
+            ..
+        } => commands::config::trust_cert(&out, target.profile, path.as_deref())?,
+        Commands::Config {
+            command: Some(ConfigCommand::Pin { remove }),
+            ..
+        } => {
+            // The certificate is not trusted yet, so it can't be verified here
+            let client = get_client(&Target {
+                insecure: true,
+                ..target
+            })?;
+            commands::config::pin(&client, &out, remove).await?;
+        }
+        Commands::Config {
+            command: Some(ConfigCommand::RestartRequired { restart }),
+            ..
+        } => {
+            let client = get_client(&target)?;
+            commands::config::restart_required(&client, &out, restart).await?;
+        }
+        Commands::Config {
+            command: Some(ConfigCommand::Ldap { command }),
+            ..
+        } => {
+            let client = get_client(&target)?;
+            match command.unwrap_or(LdapCommand::Get) {
+                LdapCommand::Get => commands::config::ldap_show(&client, &out).await?,
+                LdapCommand::Set {
+                    address,
+                    bind_dn,
+                    transport,
+                    insecure_skip_verify,
+                    search_base_dn,
+                    search_filter,
+                    gui_auth,
+                } => {
+                    let change = commands::config::LdapChange {
+                        address,
+                        bind_dn,
+                        transport: transport.map(LdapTransport::as_str),
+                        insecure_skip_verify,
+                        search_base_dn,
+                        search_filter,
+                        gui_auth,
+                    };
+                    commands::config::ldap_set(&client, &out, change).await?
+                }
+            }
+        }
+        Commands::Config {
+            command: Some(ConfigCommand::Patch { fragment }),
+            ..
+        } => {
+            let client = get_client(&target)?;
+            commands::config::patch(&client, &out, fragment.as_deref()).await?;
+        }
+        Commands::Config {
+            command: None,
+            api_key,
+            host,
+            keyring,
+        } => {
             if api_key.is_none() && host.is_none() {
-                // Show current config
-                let cfg = config::load_config()?;
-                println!(
-                    "API Key: {}",
-                    cfg.api_key.as_deref().unwrap_or("(from syncthing config)")
-                );
-                println!("Host: {}", cfg.host());
+                commands::config::show(&out, target.profile)?;
             } else {
-                let mut cfg = config::load_config()?;
-                if let Some(key) = api_key {
-                    cfg.api_key = Some(key);
+                commands::config::set(&out, api_key, host, keyring)?;
+            }
+        }
+        Commands::Id { no_qr } => {
+            let client = get_client(&target)?;
+            commands::system::id(&client, &out, !no_qr).await?;
+        }
+        Commands::Status { watch } => {
+            let client = get_client(&target)?;
+            match watch {
+                Some(interval) => {
+                    commands::watch(&out, interval, "syncthing status", || {
+                        commands::system::status_report(&client)
+                    })
+                    .await?
                 }
-                if let Some(h) = host {
-                    cfg.host = Some(h);
+                None => commands::system::status(&client, &out).await?,
+            }
+        }
+        Commands::Folders {
+            command: Some(FoldersCommand::Stats),
+            ..
+        } => {
+            let client = get_client(&target)?;
+            commands::folders::stats(&client, &out).await?;
+        }
+        Commands::Folders {
+            id,
+            watch,
+            paused,
+            syncing,
+            errors,
+            idle,
+            sort,
+            ..
+        } => {
+            let client = get_client(&target)?;
+            let view = commands::folders::FolderView {
+                paused,
+                syncing,
+                errors,
+                idle,
+                sort: sort.map(FolderSortKey::into_sort),
+            };
+            match (id, watch) {
+                (Some(folder_id), _) => commands::folders::info(&client, &out, &folder_id).await?,
+                (None, Some(interval)) => {
+                    commands::watch(&out, interval, "syncthing folders", || async {
+                        Ok(view.apply(commands::folders::summaries(&client).await?))
+                    })
+                    .await?
                 }
-                config::save_config(&cfg)?;
-                eprintln!("Configuration saved");
+                (None, None) => commands::folders::list(&client, &out, &view).await?,
             }
         }
-
-        Commands::Status => {
-            let client = get_client(host_override)?;
-            let status = client.status().await?;
-            let version = client.version().await?;
-            let completion = client.db_completion().await?;
-
-            println!(
-                "Syncthing {}",
-                version
-                    .get("version")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown")
-            );
-            println!();
-
-            let uptime = status.get("uptime").and_then(|u| u.as_u64()).unwrap_or(0);
-            let hours = uptime / 3600;
-            let mins = (uptime % 3600) / 60;
-            println!("Uptime: {}h {}m", hours, mins);
-
-            let alloc = status.get("alloc").and_then(|a| a.as_u64()).unwrap_or(0);
-            let sys = status.get("sys").and_then(|s| s.as_u64()).unwrap_or(0);
-            println!("Memory: {} / {}", format_bytes(alloc), format_bytes(sys));
-
-            let global_bytes = completion
-                .get("globalBytes")
-                .and_then(|b| b.as_u64())
-                .unwrap_or(0);
-            let need_bytes = completion
-                .get("needBytes")
-                .and_then(|b| b.as_u64())
-                .unwrap_or(0);
-            let pct = completion
-                .get("completion")
-                .and_then(|c| c.as_f64())
-                .unwrap_or(100.0);
-
-            println!();
-            println!("Sync: {:.1}% complete", pct);
-            println!("Total: {}", format_bytes(global_bytes));
-            if need_bytes > 0 {
-                println!("Need: {}", format_bytes(need_bytes));
+        Commands::Matrix => {
+            let client = get_client(&target)?;
+            commands::matrix::show(&client, &out).await?;
+        }
+        Commands::Folder { command } => {
+            let client = get_client(&target)?;
+            match command {
+                FolderCommand::Pause { folder, .. } => {
+                    commands::folders::set_paused(&client, &out, folder.as_deref(), true).await?
+                }
+                FolderCommand::Resume { folder, .. } => {
+                    commands::folders::set_paused(&client, &out, folder.as_deref(), false).await?
+                }
+                FolderCommand::Accept { folder, from, path } => {
+                    commands::folders::accept(
+                        &client,
+                        &out,
+                        &folder,
+                        from.as_deref(),
+                        path.as_deref(),
+                    )
+                    .await?
+                }
+                FolderCommand::Add {
+                    id,
+                    path,
+                    label,
+                    folder_type,
+                    share,
+                } => {
+                    let new_folder = commands::folders::NewFolder {
+                        id,
+                        path,
+                        label,
+                        folder_type: folder_type.as_str(),
+                        share,
+                    };
+                    commands::folders::add(&client, &out, new_folder).await?
+                }
+                FolderCommand::Remove { folder, yes } => {
+                    commands::folders::remove(&client, &out, &folder, yes).await?
+                }
+                FolderCommand::Revert { folder, yes } => {
+                    commands::folders::revert(&client, &out, &folder, yes).await?
+                }
+                FolderCommand::Override { folder, yes } => {
+                    commands::folders::override_changes(&client, &out, &folder, yes).await?
+                }
+                FolderCommand::Set {
+                    folder,
+                    assignments,
+                } => commands::folders::set(&client, &out, &folder, &assignments).await?,
+                FolderCommand::Versioning {
+                    folder,
+                    versioning_type,
+                    keep,
+                    cleanout_days,
+                    max_age,
+                    command,
+                    path,
+                    cleanup_interval,
+                } => {
+                    let change = versioning_type.map(|t| commands::folders::VersioningChange {
+                        versioning_type: t.as_str(),
+                        keep,
+                        cleanout_days,
+                        max_age,
+                        command,
+                        path,
+                        cleanup_interval,
+                    });
+                    commands::folders::versioning(&client, &out, &folder, change).await?
+                }
+                FolderCommand::SetType {
+                    folder,
+                    folder_type,
+                    yes,
+                } => {
+                    commands::folders::set_type(&client, &out, &folder, folder_type.as_str(), yes)
+                        .await?
+                }
+                FolderCommand::Share { folder, devices } => {
+                    commands::folders::share(&client, &out, &folder, &devices, true).await?
+                }
+                FolderCommand::SetPassword {
+                    folder,
+                    device,
+                    clear,
+                } => {
+                    let password = if clear {
+                        String::new()
+                    } else {
+                        prompt::new_password("Encryption password")?
+                    };
+                    commands::folders::set_password(&client, &out, &folder, &device, &password)
+                        .await?
+                }
+                FolderCommand::Lag { folder } => {
+                    commands::folders::lag(&client, &out, &folder).await?
+                }
+                FolderCommand::Unshare { folder, devices } => {
+                    commands::folders::share(&client, &out, &folder, &devices, false).await?
+                }
             }
         }
-
-        Commands::Folders { id } => {
-            let client = get_client(host_override)?;
-
-            if let Some(folder_id) = id {
-                let status = client.db_status(&folder_id).await?;
-                println!("{}", serde_json::to_string_pretty(&status)?);
-            } else {
-                let folders = client.config_folders().await?;
-
-                if let Some(folders) = folders.as_array() {
-                    for folder in folders {
-                        let id = folder.get("id").and_then(|i| i.as_str()).unwrap_or("?");
-                        let label = folder
-                            .get("label")
-                            .and_then(|l| l.as_str())
-                            .filter(|s| !s.is_empty())
-                            .unwrap_or(id);
-                        let paused = folder
-                            .get("paused")
-                            .and_then(|p| p.as_bool())
-                            .unwrap_or(false);
-
-                        if paused {
-                            println!("{:<20} paused", label);
-                            continue;
-                        }
-
-                        // Get sync status for this folder
-                        match client.db_status(id).await {
-                            Ok(status) => {
-                                let state = status
-                                    .get("state")
-                                    .and_then(|s| s.as_str())
-                                    .unwrap_or("unknown");
-                                let need_files = status
-                                    .get("needFiles")
-                                    .and_then(|n| n.as_u64())
-                                    .unwrap_or(0);
-                                let need_bytes = status
-                                    .get("needBytes")
-                                    .and_then(|n| n.as_u64())
-                                    .unwrap_or(0);
-                                let errors =
-                                    status.get("errors").and_then(|e| e.as_u64()).unwrap_or(0);
-
-                                let mut status_parts = vec![state.to_string()];
-                                if need_files > 0 {
-                                    status_parts.push(format!(
-                                        "{} files ({})",
-                                        need_files,
-                                        format_bytes(need_bytes)
-                                    ));
-                                }
-                                if errors > 0 {
-                                    status_parts.push(format!("{} errors", errors));
-                                }
-
-                                println!("{:<20} {}", label, status_parts.join(", "));
-                            }
-                            Err(_) => {
-                                println!("{:<20} (status unavailable)", label);
-                            }
-                        }
-                    }
+        Commands::Devices {
+            connected,
+            offline,
+            stale,
+            sort,
+        } => {
+            let client = get_client(&target)?;
+            let view = commands::devices::DeviceView {
+                connected,
+                offline,
+                stale,
+                sort: sort.map(DeviceSortKey::into_sort),
+            };
+            commands::devices::list(&client, &out, &view).await?;
+        }
+        Commands::Device { command } => {
+            let client = get_client(&target)?;
+            match command {
+                DeviceCommand::Pause { device, .. } => {
+                    commands::devices::set_paused(&client, &out, device.as_deref(), true).await?
+                }
+                DeviceCommand::Resume { device, .. } => {
+                    commands::devices::set_paused(&client, &out, device.as_deref(), false).await?
+                }
+                DeviceCommand::Remove {
+                    device,
+                    unshare,
+                    yes,
+                } => commands::devices::remove(&client, &out, &device, unshare, yes).await?,
+                DeviceCommand::Set {
+                    device,
+                    assignments,
+                } => commands::devices::set(&client, &out, &device, &assignments).await?,
+                DeviceCommand::Rename { device, name } => {
+                    commands::devices::rename(&client, &out, &device, &name).await?
                 }
             }
         }
-
-        Commands::Devices => {
-            let client = get_client(host_override)?;
-            let devices = client.config_devices().await?;
-            let connections = client.connections().await?;
-            let stats = client.stats_device().await?;
-
-            if let Some(devices) = devices.as_array() {
-                for device in devices {
-                    let id = device
-                        .get("deviceID")
-                        .and_then(|i| i.as_str())
-                        .unwrap_or("?");
-                    let name = device.get("name").and_then(|n| n.as_str()).unwrap_or(id);
-                    let short_id = &id[..7.min(id.len())];
-
-                    let connected = connections
-                        .get("connections")
-                        .and_then(|c| c.get(id))
-                        .and_then(|d| d.get("connected"))
-                        .and_then(|c| c.as_bool())
-                        .unwrap_or(false);
-
-                    let last_seen = stats
-                        .get(id)
-                        .and_then(|s| s.get("lastSeen"))
-                        .and_then(|t| t.as_str())
-                        .map(format_duration_since)
-                        .unwrap_or_else(|| "never".to_string());
-
-                    let status = if connected { "connected" } else { "offline" };
-                    println!(
-                        "{:<20} ({}) {:<12} last: {}",
-                        name, short_id, status, last_seen
-                    );
+        Commands::Ignores { command } => {
+            let client = get_client(&target)?;
+            match command {
+                IgnoresCommand::Get { folder, expanded } => {
+                    commands::ignores::get(&client, &out, &folder, expanded).await?
+                }
+                IgnoresCommand::Set { folder, file } => {
+                    commands::ignores::set(&client, &out, &folder, file.as_deref()).await?
+                }
+                IgnoresCommand::Edit { folder } => {
+                    commands::ignores::edit(&client, &out, &folder).await?
+                }
+                IgnoresCommand::Test { folder, paths } => {
+                    commands::ignores::test(&client, &out, &folder, &paths).await?
+                }
+                IgnoresCommand::Preset { folder, apply, yes } => {
+                    let presets: Vec<_> =
+                        apply.into_iter().map(IgnorePreset::into_preset).collect();
+                    commands::ignores::preset(&client, &out, &folder, &presets, yes).await?
                 }
             }
         }
-
-        Commands::Scan { folder } => {
-            let client = get_client(host_override)?;
-            if let Some(f) = folder {
-                client.db_scan(&f).await?;
-                println!("Scan triggered for folder: {}", f);
-            } else {
-                client.db_scan_all().await?;
-                println!("Scan triggered for all folders");
+        Commands::Browse {
+            folder,
+            prefix,
+            levels,
+        } => {
+            let client = get_client(&target)?;
+            commands::browse::browse(&client, &out, &folder, prefix.as_deref(), levels).await?;
+        }
+        Commands::File { folder, path } => {
+            let client = get_client(&target)?;
+            commands::file::file(&client, &out, &folder, &path).await?;
+        }
+        Commands::Prio { folder, path } => {
+            let client = get_client(&target)?;
+            commands::file::prio(&client, &out, &folder, &path).await?;
+        }
+        Commands::Search { pattern } => {
+            let client = get_client(&target)?;
+            commands::search::search(&client, &out, &pattern).await?;
+        }
+        Commands::Apply { file, prune, yes } => {
+            let client = get_client(&target)?;
+            commands::apply::run(&client, &out, &file, prune, yes).await?;
+        }
+        Commands::Open { folder } => {
+            let client = get_client(&target)?;
+            commands::folders::open(&client, &out, &folder).await?;
+        }
+        Commands::Need { folder, top } => {
+            let client = get_client(&target)?;
+            commands::folders::need(&client, &out, &folder, top).await?;
+        }
+        Commands::LocalChanged { folder } => {
+            let client = get_client(&target)?;
+            commands::folders::local_changed(&client, &out, &folder).await?;
+        }
+        Commands::Versions { command } => {
+            let client = get_client(&target)?;
+            match command {
+                VersionsCommand::List { folder, pattern } => {
+                    commands::versions::list(&client, &out, &folder, pattern.as_deref()).await?
+                }
+                VersionsCommand::Restore { folder, path, at } => {
+                    commands::versions::restore(&client, &out, &folder, &path, at.as_deref())
+                        .await?
+                }
             }
         }
-
-        Commands::Errors { folder, clear } => {
-            let client = get_client(host_override)?;
-            if clear {
-                client.clear_errors().await?;
-                println!("Errors cleared");
-            } else if let Some(folder_id) = folder {
-                // Show folder-specific errors
-                let errors = client.folder_errors(&folder_id).await?;
-                if let Some(errs) = errors.get("errors").and_then(|e| e.as_array()) {
-                    if errs.is_empty() {
-                        println!("No errors for folder '{}'", folder_id);
-                    } else {
-                        for err in errs {
-                            let path = err.get("path").and_then(|p| p.as_str()).unwrap_or("?");
-                            let error = err.get("error").and_then(|e| e.as_str()).unwrap_or("?");
-                            println!("{}: {}", path, error);
-                        }
-                    }
-                } else {
-                    println!("No errors for folder '{}'", folder_id);
+        Commands::Connections => {
+            let client = get_client(&target)?;
+            commands::devices::connections(&client, &out).await?;
+        }
+        Commands::DeviceId { command } => match command {
+            DeviceIdCommand::Check { id } => {
+                let client = get_client(&target)?;
+                commands::devices::check_id(&client, &out, &id).await?
+            }
+        },
+        Commands::Discovery => {
+            let client = get_client(&target)?;
+            commands::devices::discovery(&client, &out).await?;
+        }
+        Commands::Debug { command } => {
+            let client = get_client(&target)?;
+            match command.unwrap_or(DebugCommand::List) {
+                DebugCommand::List => commands::debug::list(&client, &out).await?,
+                DebugCommand::Enable { facilities } => {
+                    commands::debug::set(&client, &out, &facilities, true).await?
                 }
-            } else {
-                // Show system errors
-                let errors = client.errors().await?;
-                if let Some(errs) = errors.get("errors").and_then(|e| e.as_array()) {
-                    if errs.is_empty() {
-                        println!("No errors");
-                    } else {
-                        for err in errs {
-                            let when = err.get("when").and_then(|w| w.as_str()).unwrap_or("?");
-                            let msg = err.get("message").and_then(|m| m.as_str()).unwrap_or("?");
-                            println!("[{}] {}", format_duration_since(when), msg);
-                        }
-                    }
-                } else {
-                    println!("No errors");
+                DebugCommand::Disable { facilities } => {
+                    commands::debug::set(&client, &out, &facilities, false).await?
                 }
             }
         }
-
-        Commands::Pending => {
-            let client = get_client(host_override)?;
-            let devices = client.pending_devices().await?;
-            let folders = client.pending_folders().await?;
-
-            println!("Pending Devices:");
-            if let Some(devs) = devices.as_object() {
-                if devs.is_empty() {
-                    println!("  (none)");
-                } else {
-                    for (id, info) in devs {
-                        let name = info
-                            .get("name")
-                            .and_then(|n| n.as_str())
-                            .unwrap_or("unknown");
-                        println!("  {} ({})", name, &id[..7.min(id.len())]);
-                    }
+        Commands::Du { all_profiles: true } => commands::du::all_profiles(&out).await?,
+        Commands::Du {
+            all_profiles: false,
+        } => {
+            let client = get_client(&target)?;
+            commands::du::run(&client, &out).await?;
+        }
+        Commands::Cluster { command } => match command {
+            ClusterCommand::Status => commands::cluster::status(&out).await?,
+        },
+        Commands::Defaults { command } => {
+            let client = get_client(&target)?;
+            match command {
+                DefaultsCommand::Folder { assignments } => {
+                    commands::defaults::folder(&client, &out, &assignments).await?
+                }
+                DefaultsCommand::Device { assignments } => {
+                    commands::defaults::device(&client, &out, &assignments).await?
                 }
             }
-
-            println!("\nPending Folders:");
-            if let Some(flds) = folders.as_object() {
-                if flds.is_empty() {
-                    println!("  (none)");
-                } else {
-                    for (device_id, device_folders) in flds {
-                        if let Some(folders) = device_folders.as_object() {
-                            for (folder_id, info) in folders {
-                                let label = info
-                                    .get("label")
-                                    .and_then(|l| l.as_str())
-                                    .unwrap_or(folder_id);
-                                println!(
-                                    "  {} from {}",
-                                    label,
-                                    &device_id[..7.min(device_id.len())]
-                                );
-                            }
-                        }
-                    }
+        }
+        Commands::Gui { command } => {
+            let client = get_client(&target)?;
+            match command.unwrap_or(GuiCommand::Get) {
+                GuiCommand::Get => commands::gui::show(&client, &out).await?,
+                GuiCommand::Set {
+                    address,
+                    tls,
+                    user,
+                    password,
+                } => {
+                    commands::gui::set(
+                        &client,
+                        &out,
+                        address.as_deref(),
+                        tls,
+                        user.as_deref(),
+                        password.as_deref(),
+                    )
+                    .await?
                 }
             }
         }
-
-        Commands::Restart => {
-            let client = get_client(host_override)?;
-            client.restart().await?;
-            println!("Syncthing restart initiated");
+        Commands::Limits { command } => {
+            let client = get_client(&target)?;
+            match command.unwrap_or(LimitsCommand::Get) {
+                LimitsCommand::Get => commands::limits::get(&client, &out).await?,
+                LimitsCommand::Set { device, down, up } => {
+                    commands::limits::set(&client, &out, device.as_deref(), down, up).await?
+                }
+            }
         }
-
-        Commands::Shutdown => {
-            let client = get_client(host_override)?;
-            client.shutdown().await?;
-            println!("Syncthing shutdown initiated");
+        Commands::Options { command } => {
+            let client = get_client(&target)?;
+            match command.unwrap_or(OptionsCommand::Get) {
+                OptionsCommand::Get => commands::options::get(&client, &out).await?,
+                OptionsCommand::Set { assignments } => {
+                    commands::options::set(&client, &out, &assignments).await?
+                }
+            }
+        }
+        Commands::Wait { command } => {
+            let client = get_client(&target)?;
+            match command {
+                WaitCommand::Sync { folder, timeout } => {
+                    commands::wait::sync(&client, &out, &folder, timeout).await?
+                }
+                WaitCommand::Device { device, timeout } => {
+                    commands::wait::device(&client, &out, &device, timeout).await?
+                }
+            }
+        }
+        Commands::Conflicts { folder } => {
+            let client = get_client(&target)?;
+            commands::conflicts::conflicts(&client, &out, &folder).await?;
+        }
+        Commands::Scan {
+            folder,
+            sub,
+            next,
+            wait,
+        } => {
+            let client = get_client(&target)?;
+            let scan = commands::system::ScanTarget {
+                folder,
+                subs: sub,
+                next,
+            };
+            if wait {
+                commands::system::scan_and_wait(&client, &out, scan).await?;
+            } else {
+                commands::system::scan(&client, &out, scan).await?;
+            }
+        }
+        Commands::Errors { folder, clear } => {
+            let client = get_client(&target)?;
+            commands::system::errors(&client, &out, folder, clear).await?;
+        }
+        Commands::Pending => {
+            let client = get_client(&target)?;
+            commands::system::pending(&client, &out).await?;
+        }
+        Commands::Paths => {
+            let client = get_client(&target)?;
+            commands::system::paths(&client, &out).await?;
+        }
+        Commands::Ping { count } => {
+            let client = get_client(&target)?;
+            commands::system::ping(&client, &out, count).await?;
+        }
+        Commands::Restart { wait, yes } => {
+            let client = get_client(&target)?;
+            commands::system::restart(&client, &out, wait, yes).await?;
+        }
+        Commands::Shutdown { yes } => {
+            let client = get_client(&target)?;
+            commands::system::shutdown(&client, &out, yes).await?;
+        }
+        Commands::Events {
+            limit,
+            follow,
+            types,
+        } => {
+            let client = get_client(&target)?;
+            if follow {
+                commands::events::follow(&client, &out, limit, &types).await?;
+            } else {
+                commands::events::recent(&client, &out, limit, &types).await?;
+            }
         }
+        Commands::Log { lines, follow } => {
+            let client = get_client(&target)?;
+            if follow {
+                commands::log::follow(&client, &out, lines).await?;
+            } else {
+                commands::log::recent(&client, &out, lines).await?;
+            }
+        }
+        Commands::Monitor {
+            log_file,
+            max_size,
+            keep,
+        } => {
+            let client = get_client(&target)?;
+            let settings = commands::monitor::LogSettings {
+                path: log_file,
+                max_bytes: max_size * 1024 * 1024,
+                keep,
+            };
+            commands::monitor::run(&client, &out, settings).await?;
+        }
+        Commands::Exporter { listen, interval } => {
+            let client = get_client(&target)?;
+            commands::exporter::run(client, &out, &listen, interval).await?;
+        }
+        Commands::Tui { interval } => {
+            let client = get_client(&target)?;
+            commands::tui::run(client, interval).await?;
+        }
+        Commands::Check {
+            max_need_bytes,
+            max_age,
+        } => {
+            let code = match get_client(&target) {
+                Ok(client) => commands::check::run(&client, &out, max_need_bytes, max_age).await?,
+                Err(e) => {
+                    let report = commands::check::failed(&e);
+                    out.emit(&report)?;
+                    report.exit_code
+                }
+            };
+            std::process::exit(code);
+        }
+    }
 
-        Commands::Events { limit } => {
-            let client = get_client(host_override)?;
-            let events = client.events(None, Some(limit)).await?;
+    if cli.dry_run {
+        out.info("Dry run: no changes were sent to Syncthing");
+    } else if changes_config {
+        let client = get_client(&target)?;
+        commands::config::warn_if_restart_required(&client, &out).await;
+    }
 
-            if let Some(events) = events.as_array() {
-                for event in events.iter().rev().take(limit as usize) {
-                    let id = event.get("id").and_then(|i| i.as_u64()).unwrap_or(0);
-                    let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("?");
-                    let time = event.get("time").and_then(|t| t.as_str()).unwrap_or("?");
+    Ok(())
+}
 
-                    println!("[{}] {} - {}", id, format_duration_since(time), event_type);
-                }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_timeout_and_wait_timeout() {
+        let cli = Cli::try_parse_from([
+            "syncthing",
+            "--request-timeout",
+            "3s",
+            "wait",
+            "sync",
+            "docs",
+            "--timeout",
+            "10m",
+        ])
+        .unwrap();
+        assert_eq!(cli.request_timeout, Some(std::time::Duration::from_secs(3)));
+        let Commands::Wait {
+            command: WaitCommand::Sync { timeout, .. },
+        } = cli.command
+        else {
+            panic!("not `wait sync`");
+        };
+        assert_eq!(timeout, Some(std::time::Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    /// A subcommand argument with the same id as a global one is merged
+    /// with it by clap, and reading it back then panics or mixes the two.
+    #[test]
+    fn test_no_argument_shadows_a_global() {
+        fn check(command: &clap::Command, globals: &[String], path: &str) {
+            for arg in command.get_arguments() {
+                let id = arg.get_id().as_str();
+                // `config [profile add] --host` saves the global `--host`, on purpose
+                let shared = path.starts_with("syncthing config") && id == "host";
+                assert!(
+                    arg.is_global_set() || shared || !globals.iter().any(|g| g == id),
+                    "`{} --{}` clashes with the global flag of the same id",
+                    path,
+                    id
+                );
+            }
+            for sub in command.get_subcommands() {
+                check(sub, globals, &format!("{} {}", path, sub.get_name()));
             }
         }
+        let cli = Cli::command();
+        let globals: Vec<String> = cli
+            .get_arguments()
+            .filter(|a| a.is_global_set())
+            .map(|a| a.get_id().to_string())
+            .collect();
+        check(&cli, &globals, "syncthing");
     }
 
-    Ok(())
+    #[test]
+    fn test_config_export_file() {
+        let cli =
+            Cli::try_parse_from(["syncthing", "config", "export", "-f", "backup.json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Config {
+                command: Some(ConfigCommand::Export { file: Some(_), .. }),
+                ..
+            }
+        ));
+    }
 }
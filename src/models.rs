@@ -0,0 +1,758 @@
+//! Typed models for Syncthing REST API responses.
+//!
+//! Field names follow the API (camelCase on the wire). Everything that the
+//! API may omit is `#[serde(default)]` so older or newer Syncthing versions
+//! still deserialize.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// `/rest/system/status`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SystemStatus {
+    #[serde(rename = "myID")]
+    pub my_id: String,
+    pub alloc: u64,
+    pub sys: u64,
+    pub goroutines: u64,
+    pub uptime: u64,
+    pub start_time: String,
+    /// Per discovery mechanism (e.g. `IPv4 local`, `global@https://...`),
+    /// whether its last lookup or announcement failed.
+    pub discovery_status: HashMap<String, DiscoveryMethodStatus>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiscoveryMethodStatus {
+    pub error: Option<String>,
+}
+
+/// `/rest/svc/deviceid`: the canonical ID, or why it is invalid.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeviceIdCheck {
+    pub id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// `/rest/system/debug`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DebugFacilities {
+    /// Facilities with debug logging on; `null` when none are.
+    pub enabled: Option<Vec<String>>,
+    /// Facility name to description.
+    pub facilities: HashMap<String, String>,
+}
+
+/// A device in `/rest/system/discovery`, keyed by device ID.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiscoveryEntry {
+    pub addresses: Vec<String>,
+}
+
+/// `/rest/system/version`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Version {
+    pub version: String,
+    pub long_version: String,
+    pub os: String,
+    pub arch: String,
+}
+
+/// `/rest/system/ping`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Ping {
+    pub ping: String,
+}
+
+/// `/rest/system/connections`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Connections {
+    pub total: ConnectionTotal,
+    pub connections: HashMap<String, Connection>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConnectionTotal {
+    pub at: String,
+    pub in_bytes_total: u64,
+    pub out_bytes_total: u64,
+}
+
+/// A single device entry in `/rest/system/connections`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Connection {
+    pub at: String,
+    pub connected: bool,
+    pub paused: bool,
+    pub address: String,
+    pub client_version: String,
+    #[serde(rename = "type")]
+    pub connection_type: String,
+    pub crypto: String,
+    pub is_local: bool,
+    /// When the current connection was established.
+    pub started_at: String,
+    pub in_bytes_total: u64,
+    pub out_bytes_total: u64,
+}
+
+/// A log line as returned by `/rest/system/error` and `/rest/system/log`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogEntry {
+    pub when: String,
+    pub message: String,
+    pub level: Option<u8>,
+}
+
+/// Wrapper for endpoints returning `{"errors": [...]}` or `{"messages": [...]}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct LogList {
+    pub errors: Option<Vec<LogEntry>>,
+    pub messages: Option<Vec<LogEntry>>,
+}
+
+/// A folder as configured in `/rest/config/folders`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderConfig {
+    pub id: String,
+    pub label: String,
+    pub path: String,
+    #[serde(rename = "type")]
+    pub folder_type: String,
+    pub devices: Vec<FolderDevice>,
+    pub paused: bool,
+    pub rescan_interval_s: u64,
+    pub fs_watcher_enabled: bool,
+    pub versioning: Versioning,
+    /// Syncthing stops pulling into the folder when less is free.
+    pub min_disk_free: DiskSize,
+}
+
+/// A folder's file versioning settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Versioning {
+    /// `simple`, `trashcan`, `staggered`, `external`, or empty for none.
+    #[serde(rename = "type")]
+    pub versioning_type: String,
+    /// Type-specific settings such as `keep` or `maxAge`; values are strings.
+    pub params: HashMap<String, String>,
+    pub cleanup_interval_s: u64,
+    /// Where versions are stored; `.stversions` in the folder when empty.
+    pub fs_path: String,
+}
+
+/// A size setting such as `minDiskFree`: a percentage of the volume or an
+/// absolute amount.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskSize {
+    pub value: f64,
+    /// `%`, `kB`, `MB`, `GB` or `TB` (decimal, as Syncthing counts them).
+    pub unit: String,
+}
+
+impl DiskSize {
+    /// The size in bytes on a volume of `total` bytes.
+    pub fn bytes(&self, total: u64) -> u64 {
+        let factor = match self.unit.as_str() {
+            "%" => return (total as f64 * self.value / 100.0) as u64,
+            "kB" => 1e3,
+            "MB" => 1e6,
+            "GB" => 1e9,
+            "TB" => 1e12,
+            _ => 1.0,
+        };
+        (self.value * factor) as u64
+    }
+}
+
+impl FolderConfig {
+    /// The label, or the ID when no label is set.
+    pub fn display_name(&self) -> &str {
+        if self.label.is_empty() {
+            &self.id
+        } else {
+            &self.label
+        }
+    }
+
+    /// The folder's path with a leading `~` expanded, as it would be on
+    /// this machine.
+    pub fn local_path(&self) -> PathBuf {
+        expand_tilde(Path::new(&self.path))
+    }
+}
+
+/// A folder, device or other named thing the user asked for doesn't exist.
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// Find a folder by exact ID, falling back to a unique label match. A local
+/// path such as `.` or `~/Sync/photos` finds the folder containing it, so
+/// commands can be run from inside a synced directory.
+pub fn find_folder<'a>(folders: &'a [FolderConfig], query: &str) -> Result<&'a FolderConfig> {
+    if let Some(folder) = folders.iter().find(|f| f.id == query) {
+        return Ok(folder);
+    }
+
+    let matches: Vec<_> = folders.iter().filter(|f| f.label == query).collect();
+    match matches.as_slice() {
+        [folder] => Ok(folder),
+        [] if is_path_like(query) => folder_containing(folders, Path::new(query)),
+        [] => Err(NotFound(format!("No folder with ID or label '{}'", query)).into()),
+        _ => bail!(
+            "Label '{}' matches {} folders; use the folder ID instead",
+            query,
+            matches.len()
+        ),
+    }
+}
+
+fn is_path_like(query: &str) -> bool {
+    query == "." || query == ".." || ["./", "../", "/", "~"].iter().any(|p| query.starts_with(p))
+}
+
+/// The folder whose path contains `path`, the innermost if folders nest.
+/// Folder paths are those on the Syncthing host, so this only finds
+/// anything when it runs on the same machine.
+fn folder_containing<'a>(folders: &'a [FolderConfig], path: &Path) -> Result<&'a FolderConfig> {
+    let target = resolve(path);
+    folders
+        .iter()
+        .map(|folder| (folder, resolve(&folder.local_path())))
+        .filter(|(_, root)| target.starts_with(root))
+        .max_by_key(|(_, root)| root.components().count())
+        .map(|(folder, _)| folder)
+        .ok_or_else(|| {
+            NotFound(format!(
+                "No configured folder contains {}",
+                target.display()
+            ))
+            .into()
+        })
+}
+
+fn expand_tilde(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// `path` with `~` expanded, made absolute and, where it exists, with
+/// symlinks resolved.
+fn resolve(path: &Path) -> PathBuf {
+    let path = expand_tilde(path);
+    std::fs::canonicalize(&path).unwrap_or_else(|_| std::path::absolute(&path).unwrap_or(path))
+}
+
+/// A device a folder is shared with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderDevice {
+    #[serde(rename = "deviceID")]
+    pub device_id: String,
+    #[serde(rename = "introducedBy")]
+    pub introduced_by: String,
+    pub encryption_password: String,
+}
+
+/// A device as configured in `/rest/config/devices`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeviceConfig {
+    #[serde(rename = "deviceID")]
+    pub device_id: String,
+    pub name: String,
+    pub addresses: Vec<String>,
+    pub compression: String,
+    pub introducer: bool,
+    pub paused: bool,
+    pub auto_accept_folders: bool,
+    pub max_send_kbps: u64,
+    pub max_recv_kbps: u64,
+    pub untrusted: bool,
+}
+
+impl DeviceConfig {
+    /// The configured name, or the device ID when unnamed.
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            &self.device_id
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// `/rest/config/restart-required`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RestartRequired {
+    pub requires_restart: bool,
+}
+
+/// GUI and REST API settings from `/rest/config/gui`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GuiConfig {
+    pub enabled: bool,
+    pub address: String,
+    pub user: String,
+    /// Bcrypt hash of the GUI password; empty when none is set.
+    pub password: String,
+    #[serde(rename = "useTLS")]
+    pub use_tls: bool,
+    pub api_key: String,
+    pub theme: String,
+    /// `static` for the user and password above, or `ldap`.
+    pub auth_mode: String,
+}
+
+/// Directory server the GUI can authenticate against, from `/rest/config/ldap`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LdapConfig {
+    /// `host:port` of the server.
+    pub address: String,
+    /// DN to bind as, with `%s` standing for the user name.
+    #[serde(rename = "bindDN")]
+    pub bind_dn: String,
+    /// `plain`, `tls` or `starttls`.
+    pub transport: String,
+    pub insecure_skip_verify: bool,
+    #[serde(rename = "searchBaseDN")]
+    pub search_base_dn: String,
+    pub search_filter: String,
+}
+
+/// Global settings from `/rest/config/options`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Options {
+    pub listen_addresses: Vec<String>,
+    pub global_announce_enabled: bool,
+    pub local_announce_enabled: bool,
+    pub relays_enabled: bool,
+    pub nat_enabled: bool,
+    /// Upload limit in KiB/s; 0 means unlimited.
+    pub max_send_kbps: u64,
+    /// Download limit in KiB/s; 0 means unlimited.
+    pub max_recv_kbps: u64,
+    pub limit_bandwidth_in_lan: bool,
+    /// Usage reporting consent: -1 declined, 0 undecided, else the accepted version.
+    pub ur_accepted: i64,
+}
+
+/// Find a device by full ID, unique ID prefix (e.g. the 7-character short
+/// ID), or unique name.
+pub fn find_device<'a>(devices: &'a [DeviceConfig], query: &str) -> Result<&'a DeviceConfig> {
+    if let Some(device) = devices.iter().find(|d| d.device_id == query) {
+        return Ok(device);
+    }
+
+    let by_name: Vec<_> = devices.iter().filter(|d| d.name == query).collect();
+    if let [device] = by_name.as_slice() {
+        return Ok(device);
+    }
+
+    let by_prefix: Vec<_> = devices
+        .iter()
+        .filter(|d| d.device_id.starts_with(query))
+        .collect();
+    match (by_name.len(), by_prefix.as_slice()) {
+        (0, [device]) => Ok(device),
+        (0, []) => Err(NotFound(format!("No device with ID or name '{}'", query)).into()),
+        _ => bail!(
+            "'{}' matches more than one device; use the full device ID instead",
+            query
+        ),
+    }
+}
+
+/// `/rest/db/status`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DbStatus {
+    pub state: String,
+    pub state_changed: String,
+    pub error: String,
+    pub errors: u64,
+    pub pull_errors: u64,
+    pub global_bytes: u64,
+    pub global_files: u64,
+    pub global_deleted: u64,
+    pub local_bytes: u64,
+    pub local_files: u64,
+    pub local_deleted: u64,
+    pub in_sync_bytes: u64,
+    pub in_sync_files: u64,
+    pub need_bytes: u64,
+    pub need_files: u64,
+    pub need_deletes: u64,
+    pub receive_only_changed_bytes: u64,
+    pub receive_only_changed_files: u64,
+    pub sequence: u64,
+}
+
+/// `/rest/db/completion`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Completion {
+    pub completion: f64,
+    pub global_bytes: u64,
+    pub global_items: u64,
+    pub need_bytes: u64,
+    pub need_items: u64,
+    pub need_deletes: u64,
+    pub remote_state: String,
+    pub sequence: u64,
+}
+
+impl Default for Completion {
+    fn default() -> Self {
+        Self {
+            completion: 100.0,
+            global_bytes: 0,
+            global_items: 0,
+            need_bytes: 0,
+            need_items: 0,
+            need_deletes: 0,
+            remote_state: String::new(),
+            sequence: 0,
+        }
+    }
+}
+
+/// A file entry in `/rest/db/need`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NeedFile {
+    pub name: String,
+    pub size: u64,
+    pub modified: String,
+    pub deleted: bool,
+    pub flags: String,
+    pub sequence: u64,
+}
+
+/// `/rest/db/need`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Need {
+    pub progress: Vec<NeedFile>,
+    pub queued: Vec<NeedFile>,
+    pub rest: Vec<NeedFile>,
+    pub page: u64,
+    pub perpage: u64,
+}
+
+impl Need {
+    /// All needed files: in progress, queued, and the rest.
+    pub fn files(&self) -> impl Iterator<Item = &NeedFile> {
+        self.progress.iter().chain(&self.queued).chain(&self.rest)
+    }
+}
+
+/// `/rest/stats/device` entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeviceStats {
+    pub last_seen: String,
+    pub last_connection_duration_s: f64,
+}
+
+/// `/rest/stats/folder` entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderStats {
+    pub last_scan: String,
+    pub last_file: LastFile,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LastFile {
+    pub at: String,
+    pub filename: String,
+    pub deleted: bool,
+}
+
+/// `/rest/cluster/pending/devices` entry.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PendingDevice {
+    pub time: String,
+    pub name: String,
+    pub address: String,
+}
+
+/// `/rest/cluster/pending/folders` entry, keyed by folder ID.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PendingFolder {
+    /// Offers for this folder, keyed by the offering device ID.
+    pub offered_by: HashMap<String, PendingFolderOffer>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PendingFolderOffer {
+    pub time: String,
+    pub label: String,
+    pub receive_encrypted: bool,
+    pub remote_encrypted: bool,
+}
+
+/// A per-file error from `/rest/folder/errors`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileError {
+    pub path: String,
+    pub error: String,
+}
+
+/// `/rest/folder/errors`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FolderErrors {
+    pub folder: String,
+    pub errors: Option<Vec<FileError>>,
+    pub page: u64,
+    pub perpage: u64,
+}
+
+/// `/rest/db/ignores`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Ignores {
+    /// Lines of `.stignore` as written.
+    pub ignore: Option<Vec<String>>,
+    /// Patterns after `#include` expansion, as used for matching.
+    pub expanded: Option<Vec<String>>,
+    pub error: Option<String>,
+}
+
+/// A file or directory from `/rest/db/browse`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BrowseEntry {
+    pub name: String,
+    pub mod_time: String,
+    pub size: u64,
+    /// `FILE_INFO_TYPE_FILE`, `FILE_INFO_TYPE_DIRECTORY`, ...
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    pub children: Vec<BrowseEntry>,
+}
+
+impl BrowseEntry {
+    pub fn is_dir(&self) -> bool {
+        self.entry_type.ends_with("DIRECTORY")
+    }
+}
+
+/// `/rest/db/file`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DbFile {
+    /// Devices that have the global version of the file.
+    pub availability: Option<Vec<Availability>>,
+    pub global: FileInfo,
+    pub local: FileInfo,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Availability {
+    pub id: String,
+    pub from_temporary: bool,
+}
+
+/// A file's metadata as stored in the database.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileInfo {
+    pub name: String,
+    pub size: u64,
+    pub modified: String,
+    /// Short ID of the device that last modified the file.
+    pub modified_by: String,
+    pub deleted: bool,
+    pub ignored: bool,
+    pub invalid: bool,
+    pub sequence: u64,
+    /// Version vector as `SHORTID:counter` entries.
+    pub version: Vec<String>,
+    #[serde(rename = "type")]
+    pub file_type: String,
+}
+
+/// `/rest/db/localchanged`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalChanged {
+    pub files: Vec<FileInfo>,
+    pub page: u64,
+    pub perpage: u64,
+}
+
+/// An archived version from `/rest/folder/versions`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileVersion {
+    /// When the version was archived.
+    pub version_time: String,
+    /// Modification time of the archived file.
+    pub mod_time: String,
+    pub size: u64,
+}
+
+/// An entry from `/rest/events`. `data` depends on the event type.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Event {
+    pub id: u64,
+    #[serde(rename = "globalID")]
+    pub global_id: u64,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub time: String,
+    pub data: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folder_display_name_falls_back_to_id() {
+        let folder = FolderConfig {
+            id: "abcd-1234".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(folder.display_name(), "abcd-1234");
+    }
+
+    #[test]
+    fn test_find_folder_by_id_or_label() {
+        let folders = vec![
+            FolderConfig {
+                id: "abcd-1234".to_string(),
+                label: "Documents".to_string(),
+                ..Default::default()
+            },
+            FolderConfig {
+                id: "efgh-5678".to_string(),
+                label: "Photos".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(find_folder(&folders, "efgh-5678").unwrap().label, "Photos");
+        assert_eq!(find_folder(&folders, "Documents").unwrap().id, "abcd-1234");
+        assert!(find_folder(&folders, "Music").is_err());
+    }
+
+    #[test]
+    fn test_disk_size_bytes() {
+        let size = |value, unit: &str| DiskSize {
+            value,
+            unit: unit.to_string(),
+        };
+        assert_eq!(size(1.0, "%").bytes(500_000), 5_000);
+        assert_eq!(size(2.5, "GB").bytes(0), 2_500_000_000);
+        assert_eq!(size(0.0, "%").bytes(500_000), 0);
+    }
+
+    #[test]
+    fn test_find_folder_by_local_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let photos = dir.path().join("photos");
+        std::fs::create_dir_all(photos.join("2024")).unwrap();
+        let folders = vec![
+            FolderConfig {
+                id: "outer".to_string(),
+                path: dir.path().display().to_string(),
+                ..Default::default()
+            },
+            FolderConfig {
+                id: "photos".to_string(),
+                path: format!("{}/", photos.display()),
+                ..Default::default()
+            },
+        ];
+        let find = |path: &Path| find_folder(&folders, &path.display().to_string()).map(|f| &f.id);
+        assert_eq!(find(&photos.join("2024")).unwrap(), "photos");
+        assert_eq!(find(&dir.path().join("other")).unwrap(), "outer");
+        let err = find(Path::new("/nonexistent/elsewhere")).unwrap_err();
+        assert!(err.downcast_ref::<NotFound>().is_some());
+    }
+
+    #[test]
+    fn test_find_device_by_id_prefix_or_name() {
+        let devices = vec![
+            DeviceConfig {
+                device_id: "ABCDEFG-1111111".to_string(),
+                name: "laptop".to_string(),
+                ..Default::default()
+            },
+            DeviceConfig {
+                device_id: "ABCDXYZ-2222222".to_string(),
+                name: "nas".to_string(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(find_device(&devices, "nas").unwrap().name, "nas");
+        assert_eq!(find_device(&devices, "ABCDEFG").unwrap().name, "laptop");
+        assert!(find_device(&devices, "ABCD").is_err());
+        assert!(find_device(&devices, "phone").is_err());
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let status: DbStatus = serde_json::from_str(r#"{"state": "idle"}"#).unwrap();
+        assert_eq!(status.state, "idle");
+        assert_eq!(status.need_bytes, 0);
+
+        let completion: Completion = serde_json::from_str("{}").unwrap();
+        assert_eq!(completion.completion, 100.0);
+    }
+
+    #[test]
+    fn test_pending_folders_shape() {
+        let json = r#"{
+            "docs": {
+                "offeredBy": {
+                    "DEV1": {"time": "2024-01-01T00:00:00Z", "label": "Docs", "receiveEncrypted": false}
+                }
+            }
+        }"#;
+        let pending: HashMap<String, PendingFolder> = serde_json::from_str(json).unwrap();
+        assert_eq!(pending["docs"].offered_by["DEV1"].label, "Docs");
+    }
+}
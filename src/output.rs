@@ -0,0 +1,2238 @@
+//! Output structs shared by the human and `--json` renderers.
+//!
+//! Every command builds one of these and hands it to [`Output::emit`]. The JSON shape
+//! is part of the CLI's interface: fields are camelCase (matching the
+//! Syncthing REST API) and are only ever added, never renamed.
+
+use crate::delimited::Delimiter;
+use crate::table::{Cell, Table};
+use crate::template::Template;
+use chrono::{DateTime, Utc};
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use syncthing::models::{BrowseEntry, DbStatus, FileInfo, FileVersion, NeedFile};
+
+/// Something that can be printed either as JSON or as human-readable text.
+pub trait Render: Serialize {
+    fn render(&self);
+
+    /// Text for `--quiet`: just the values a script would want, one per
+    /// line. Defaults to the normal text.
+    fn render_quiet(&self) {
+        self.render();
+    }
+
+    /// For reports that wrap a list in an object: the field holding that
+    /// list, whose items `--output` and `--format` print one row each.
+    fn rows_field(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// The JSON form of `value` that `--output` and `--format` print rows of.
+fn rows<T: Render>(value: &T) -> serde_json::Result<serde_json::Value> {
+    let mut json = serde_json::to_value(value)?;
+    Ok(match value.rows_field() {
+        Some(field) => json[field].take(),
+        None => json,
+    })
+}
+
+/// How command results are printed, derived from the global flags.
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    pub json: bool,
+    /// Only essential values on stdout, no progress or notes on stderr.
+    pub quiet: bool,
+    /// `--format` template applied to the JSON form instead of the text.
+    pub format: Option<Template>,
+    /// `--output csv|tsv`: the JSON form as delimited rows.
+    pub delimited: Option<Delimiter>,
+    /// Whether the delimited header went out already, so streams print it
+    /// only once.
+    pub header_written: Arc<AtomicBool>,
+}
+
+impl Output {
+    /// Print `value` as pretty JSON when `--json` is set, otherwise as text.
+    pub fn emit<T: Render>(&self, value: &T) -> anyhow::Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(value)?);
+        } else if let Some(delimiter) = self.delimited {
+            self.header_written.store(true, Ordering::Relaxed);
+            for line in delimiter.lines(&rows(value)?, true) {
+                println!("{}", line);
+            }
+        } else {
+            self.render(value)?;
+        }
+        Ok(())
+    }
+
+    /// Like `emit`, but JSON is written compactly on a single line so
+    /// streams come out as JSON Lines.
+    pub fn emit_line<T: Render>(&self, value: &T) -> anyhow::Result<()> {
+        if self.json {
+            println!("{}", serde_json::to_string(value)?);
+        } else if let Some(delimiter) = self.delimited {
+            let header = !self.header_written.swap(true, Ordering::Relaxed);
+            for line in delimiter.lines(&rows(value)?, header) {
+                println!("{}", line);
+            }
+        } else {
+            self.render(value)?;
+        }
+        Ok(())
+    }
+
+    fn render<T: Render>(&self, value: &T) -> anyhow::Result<()> {
+        if let Some(template) = &self.format {
+            template.print(&rows(value)?);
+        } else if self.quiet {
+            value.render_quiet();
+        } else {
+            value.render();
+        }
+        Ok(())
+    }
+
+    /// Print an informational note on stderr, unless `--quiet`.
+    pub fn info(&self, message: impl std::fmt::Display) {
+        if !self.quiet {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// Whether to draw progress on stderr: only for humans at a terminal.
+    pub fn show_progress(&self) -> bool {
+        !self.json && !self.quiet && std::io::stderr().is_terminal()
+    }
+}
+
+/// Whether text output is colored; set once from `--color` at startup.
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+}
+
+/// Wrap `text` in the ANSI codes for `color` when color is enabled. Pad
+/// before painting: the escape codes would count towards the width.
+pub fn paint(text: &str, color: Color) -> String {
+    if !COLOR.load(Ordering::Relaxed) {
+        return text.to_string();
+    }
+    let code = match color {
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+/// The color a folder state is shown in: idle is good, anything in
+/// progress is yellow, errors are red.
+pub fn state_color(state: &str) -> Color {
+    match state {
+        "idle" => Color::Green,
+        "error" => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+    const TB: u64 = GB * 1024;
+
+    if bytes >= TB {
+        format!("{:.1} TB", bytes as f64 / TB as f64)
+    } else if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+pub fn format_duration_since(timestamp: &str) -> String {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(timestamp) {
+        let now = Utc::now();
+        let duration = now.signed_duration_since(dt.with_timezone(&Utc));
+
+        if duration.num_days() > 0 {
+            format!("{}d ago", duration.num_days())
+        } else if duration.num_hours() > 0 {
+            format!("{}h ago", duration.num_hours())
+        } else if duration.num_minutes() > 0 {
+            format!("{}m ago", duration.num_minutes())
+        } else {
+            "just now".to_string()
+        }
+    } else {
+        timestamp.to_string()
+    }
+}
+
+pub fn short_id(id: &str) -> &str {
+    &id[..7.min(id.len())]
+}
+
+/// Result of a command that performs an action rather than reporting state.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionResult {
+    /// Human-readable description of what was done.
+    pub message: String,
+}
+
+impl ActionResult {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl Render for ActionResult {
+    fn render(&self) {
+        println!("{}", self.message);
+    }
+
+    fn render_quiet(&self) {}
+}
+
+/// Output of `config` when showing the current settings.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReport {
+    /// API key stored in the CLI config, or `None` when read from syncthing's config.xml.
+    pub api_key: Option<String>,
+    pub host: String,
+    /// Name of the profile in use, if any.
+    pub profile: Option<String>,
+    /// Whether the API key is kept in the OS keyring.
+    pub keyring: bool,
+}
+
+impl Render for ConfigReport {
+    fn render(&self) {
+        if let Some(profile) = &self.profile {
+            println!("Profile: {}", profile);
+        }
+        let source = if self.keyring {
+            "(in OS keyring)"
+        } else {
+            "(from syncthing config)"
+        };
+        println!("API Key: {}", self.api_key.as_deref().unwrap_or(source));
+        println!("Host: {}", self.host);
+    }
+}
+
+/// One row of `config profile list`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+    pub name: String,
+    pub host: String,
+    pub default: bool,
+}
+
+impl Render for Vec<ProfileSummary> {
+    fn render(&self) {
+        if self.is_empty() {
+            println!("No profiles configured");
+        }
+        for profile in self {
+            let marker = if profile.default { "*" } else { " " };
+            println!("{} {:<16} {}", marker, profile.name, profile.host);
+        }
+    }
+
+    fn render_quiet(&self) {
+        for profile in self {
+            println!("{}", profile.name);
+        }
+    }
+}
+
+/// Output of `status`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusReport {
+    pub version: String,
+    /// Uptime in seconds.
+    pub uptime: u64,
+    /// Bytes allocated by the Go runtime.
+    pub memory_alloc: u64,
+    /// Bytes obtained from the OS by the Go runtime.
+    pub memory_sys: u64,
+    /// Overall completion percentage across all folders and devices.
+    pub completion: f64,
+    pub global_bytes: u64,
+    pub need_bytes: u64,
+    /// Remote devices currently connected.
+    pub devices_connected: usize,
+    /// Remote devices configured.
+    pub devices_total: usize,
+    pub pending_devices: usize,
+    pub pending_folders: usize,
+    /// Active system errors (see `errors`).
+    pub errors: usize,
+}
+
+impl Render for StatusReport {
+    fn render(&self) {
+        println!("Syncthing {}", self.version);
+        println!();
+
+        let hours = self.uptime / 3600;
+        let mins = (self.uptime % 3600) / 60;
+        println!("Uptime: {}h {}m", hours, mins);
+        println!(
+            "Memory: {} / {}",
+            format_bytes(self.memory_alloc),
+            format_bytes(self.memory_sys)
+        );
+
+        println!();
+        println!("Sync: {:.1}% complete", self.completion);
+        println!("Total: {}", format_bytes(self.global_bytes));
+        if self.need_bytes > 0 {
+            println!("Need: {}", format_bytes(self.need_bytes));
+        }
+
+        println!();
+        println!(
+            "Devices: {}/{} connected",
+            self.devices_connected, self.devices_total
+        );
+        if self.pending_devices > 0 || self.pending_folders > 0 {
+            println!(
+                "Pending: {} devices, {} folders",
+                self.pending_devices, self.pending_folders
+            );
+        }
+        if self.errors > 0 {
+            println!("Errors: {}", paint(&self.errors.to_string(), Color::Red));
+        }
+    }
+}
+
+/// One row of `folders`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderSummary {
+    pub id: String,
+    pub label: String,
+    pub paused: bool,
+    /// Folder state (`idle`, `syncing`, `scanning`, ...); `None` when paused
+    /// or when the status could not be fetched.
+    pub state: Option<String>,
+    pub need_files: u64,
+    pub need_bytes: u64,
+    /// Size of the folder's global (newest) version.
+    pub global_bytes: u64,
+    pub errors: u64,
+    /// Set when the folder's volume is at or near its `minDiskFree`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_warning: Option<String>,
+}
+
+impl Render for Vec<FolderSummary> {
+    fn render(&self) {
+        let mut table = Table::new(&["Folder", "State", "Need", "Errors"]).shrink(0);
+        for folder in self {
+            let state = match (&folder.state, folder.paused) {
+                (_, true) => Cell::from("paused"),
+                (Some(state), false) => Cell::colored(state.as_str(), state_color(state)),
+                (None, false) => Cell::from("unknown"),
+            };
+            let need = if folder.need_files > 0 {
+                format!(
+                    "{} files ({})",
+                    folder.need_files,
+                    format_bytes(folder.need_bytes)
+                )
+            } else {
+                String::new()
+            };
+            let errors = if folder.errors > 0 {
+                Cell::colored(folder.errors.to_string(), Color::Red)
+            } else {
+                Cell::from("")
+            };
+            table.row(vec![
+                folder.label.as_str().into(),
+                state,
+                need.into(),
+                errors,
+            ]);
+        }
+        table.print();
+        // On stderr, to keep the table easy to parse
+        for folder in self {
+            if let Some(warning) = &folder.disk_warning {
+                eprintln!(
+                    "{} {}: {}",
+                    paint("Low disk space:", Color::Yellow),
+                    folder.label,
+                    warning
+                );
+            }
+        }
+    }
+
+    fn render_quiet(&self) {
+        for folder in self {
+            println!("{}", folder.id);
+        }
+    }
+}
+
+/// One row of `devices`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceSummary {
+    pub id: String,
+    pub name: String,
+    pub connected: bool,
+    /// RFC 3339 timestamp of when the device was last seen, if ever.
+    pub last_seen: Option<String>,
+    /// Current download rate in bytes per second.
+    pub in_rate: u64,
+    /// Current upload rate in bytes per second.
+    pub out_rate: u64,
+    /// Bytes received over the current connection.
+    pub in_bytes_total: u64,
+    /// Bytes sent over the current connection.
+    pub out_bytes_total: u64,
+}
+
+impl Render for Vec<DeviceSummary> {
+    fn render(&self) {
+        let mut table = Table::new(&[
+            "Device",
+            "ID",
+            "Status",
+            "Down",
+            "Up",
+            "Received",
+            "Sent",
+            "Last seen",
+        ])
+        .shrink(0);
+        for device in self {
+            let mut row = vec![
+                Cell::from(device.name.as_str()),
+                Cell::from(short_id(&device.id)),
+            ];
+            if device.connected {
+                row.extend([
+                    Cell::colored("connected", Color::Green),
+                    format!("{}/s", format_bytes(device.in_rate)).into(),
+                    format!("{}/s", format_bytes(device.out_rate)).into(),
+                    format_bytes(device.in_bytes_total).into(),
+                    format_bytes(device.out_bytes_total).into(),
+                    "now".into(),
+                ]);
+            } else {
+                let last_seen = device
+                    .last_seen
+                    .as_deref()
+                    .map(format_duration_since)
+                    .unwrap_or_else(|| "never".to_string());
+                row.extend([
+                    Cell::colored("offline", Color::Red),
+                    "".into(),
+                    "".into(),
+                    "".into(),
+                    "".into(),
+                    last_seen.into(),
+                ]);
+            }
+            table.row(row);
+        }
+        table.print();
+    }
+
+    fn render_quiet(&self) {
+        for device in self {
+            println!("{}", device.id);
+        }
+    }
+}
+
+/// One row of `--offline folders`: a folder as configured in config.xml.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfiguredFolder {
+    pub id: String,
+    pub label: String,
+    pub path: String,
+    #[serde(rename = "type")]
+    pub folder_type: String,
+    pub paused: bool,
+    /// Names of the devices the folder is shared with (IDs when unnamed).
+    pub devices: Vec<String>,
+}
+
+impl Render for Vec<ConfiguredFolder> {
+    fn render(&self) {
+        let mut table = Table::new(&["Folder", "Type", "Path", "Shared with"]).shrink(2);
+        for folder in self {
+            let folder_type = if folder.paused {
+                Cell::colored("paused", Color::Yellow)
+            } else {
+                Cell::from(folder.folder_type.as_str())
+            };
+            table.row(vec![
+                folder.label.as_str().into(),
+                folder_type,
+                folder.path.as_str().into(),
+                folder.devices.join(", ").into(),
+            ]);
+        }
+        table.print();
+    }
+
+    fn render_quiet(&self) {
+        for folder in self {
+            println!("{}", folder.id);
+        }
+    }
+}
+
+/// One row of `--offline devices`: a device as configured in config.xml.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfiguredDevice {
+    pub id: String,
+    pub name: String,
+    pub addresses: Vec<String>,
+    pub paused: bool,
+}
+
+impl Render for Vec<ConfiguredDevice> {
+    fn render(&self) {
+        let mut table = Table::new(&["Device", "ID", "Addresses"]).shrink(0);
+        for device in self {
+            let addresses = if device.paused {
+                Cell::colored("paused", Color::Yellow)
+            } else {
+                Cell::from(device.addresses.join(", "))
+            };
+            table.row(vec![
+                device.name.as_str().into(),
+                short_id(&device.id).into(),
+                addresses,
+            ]);
+        }
+        table.print();
+    }
+
+    fn render_quiet(&self) {
+        for device in self {
+            println!("{}", device.id);
+        }
+    }
+}
+
+/// One device in `connections`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionInfo {
+    pub id: String,
+    pub name: String,
+    pub connected: bool,
+    pub address: String,
+    /// Transport, e.g. `tcp-client`, `quic-server` or `relay-client`.
+    #[serde(rename = "type")]
+    pub connection_type: String,
+    pub crypto: String,
+    /// Whether the peer is on the local network.
+    pub is_local: bool,
+    /// Compression setting from the device config (`metadata`, `always`, `never`).
+    pub compression: String,
+    pub client_version: String,
+    /// RFC 3339 timestamp of when the connection was established.
+    pub started_at: Option<String>,
+}
+
+impl Render for Vec<ConnectionInfo> {
+    fn render(&self) {
+        for conn in self {
+            if !conn.connected {
+                println!(
+                    "{:<20} ({}) {}",
+                    conn.name,
+                    short_id(&conn.id),
+                    paint("offline", Color::Red)
+                );
+                continue;
+            }
+            let since = conn
+                .started_at
+                .as_deref()
+                .map(format_duration_since)
+                .unwrap_or_else(|| "unknown".to_string());
+            println!(
+                "{:<20} ({}) {} {}",
+                conn.name,
+                short_id(&conn.id),
+                paint("connected", Color::Green),
+                since
+            );
+            println!("  Address:     {}", conn.address);
+            println!(
+                "  Type:        {} ({})",
+                conn.connection_type,
+                if conn.is_local { "LAN" } else { "WAN" }
+            );
+            println!("  Crypto:      {}", conn.crypto);
+            println!("  Compression: {}", conn.compression);
+            println!("  Version:     {}", conn.client_version);
+        }
+    }
+}
+
+/// A per-file error reported for a folder.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderError {
+    pub path: String,
+    pub error: String,
+}
+
+/// Output of `errors --folder`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderErrorsReport {
+    pub folder: String,
+    pub errors: Vec<FolderError>,
+}
+
+impl Render for FolderErrorsReport {
+    fn render(&self) {
+        if self.errors.is_empty() {
+            println!("No errors for folder '{}'", self.folder);
+        }
+        for err in &self.errors {
+            println!("{}: {}", err.path, paint(&err.error, Color::Red));
+        }
+    }
+}
+
+/// A system-level error.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemError {
+    /// RFC 3339 timestamp of when the error occurred.
+    pub when: String,
+    pub message: String,
+}
+
+impl Render for Vec<SystemError> {
+    fn render(&self) {
+        if self.is_empty() {
+            println!("No errors");
+        }
+        for err in self {
+            println!(
+                "[{}] {}",
+                format_duration_since(&err.when),
+                paint(&err.message, Color::Red)
+            );
+        }
+    }
+}
+
+/// Output of `paths`: runtime file locations on the Syncthing host, by name.
+#[derive(Serialize)]
+pub struct PathsReport(pub BTreeMap<String, String>);
+
+impl Render for PathsReport {
+    fn render(&self) {
+        let width = self.0.keys().map(|name| name.len()).max().unwrap_or(0);
+        for (name, path) in &self.0 {
+            println!("{:<width$}  {}", name, path, width = width);
+        }
+    }
+}
+
+/// Output of `defaults folder` and `defaults device`: the template's
+/// settings, by config key.
+#[derive(Serialize)]
+pub struct DefaultsReport(pub serde_json::Map<String, serde_json::Value>);
+
+impl Render for DefaultsReport {
+    fn render(&self) {
+        use serde_json::Value;
+        let width = self.0.keys().map(|key| key.len()).max().unwrap_or(0);
+        for (key, value) in &self.0 {
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_str().map_or_else(|| item.to_string(), String::from))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                // versioning
+                Value::Object(fields) if fields.contains_key("type") => {
+                    match fields["type"].as_str().unwrap_or_default() {
+                        "" => "none".to_string(),
+                        versioning_type => versioning_type.to_string(),
+                    }
+                }
+                // minDiskFree
+                Value::Object(fields) if fields.contains_key("unit") => {
+                    format!(
+                        "{}{}",
+                        fields["value"],
+                        fields["unit"].as_str().unwrap_or_default()
+                    )
+                }
+                other => other.to_string(),
+            };
+            println!(
+                "{:<width$}  {}",
+                format!("{}:", key),
+                text,
+                width = width + 1
+            );
+        }
+    }
+}
+
+/// A line of `log`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    /// RFC 3339 timestamp of the line.
+    pub when: String,
+    /// Syncthing's level: 0 debug, 1 verbose, 2 info, 3 warning.
+    pub level: Option<u8>,
+    pub message: String,
+}
+
+impl Render for LogLine {
+    fn render(&self) {
+        let time = DateTime::parse_from_rfc3339(&self.when)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.when.clone());
+        let level = match self.level {
+            Some(0) => "DEBUG",
+            Some(1) => "VERBOSE",
+            Some(3) => "WARNING",
+            _ => "INFO",
+        };
+        println!("{} {:<7} {}", time, level, self.message);
+    }
+}
+
+impl Render for Vec<LogLine> {
+    fn render(&self) {
+        for line in self {
+            line.render();
+        }
+    }
+}
+
+/// A device asking to connect.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingDevice {
+    pub id: String,
+    pub name: String,
+}
+
+/// A folder offered by a remote device.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingFolder {
+    pub id: String,
+    pub label: String,
+    /// Device ID of the offering device.
+    pub device: String,
+    /// Name of the offering device, if it is configured.
+    pub device_name: Option<String>,
+    /// RFC 3339 timestamp of when the offer was made.
+    pub time: Option<String>,
+    /// The offering device wants it as an encrypted folder (we'd be untrusted).
+    pub receive_encrypted: bool,
+    /// A folder with this ID is already configured here; `folder accept`
+    /// only adds the device to it, keeping its path and settings.
+    pub exists: bool,
+}
+
+/// Output of `pending`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingReport {
+    pub devices: Vec<PendingDevice>,
+    pub folders: Vec<PendingFolder>,
+}
+
+impl Render for PendingReport {
+    fn render(&self) {
+        println!("Pending Devices:");
+        if self.devices.is_empty() {
+            println!("  (none)");
+        } else {
+            let mut table = Table::new(&["Device", "ID"]).shrink(0);
+            for device in &self.devices {
+                table.row(vec![device.name.as_str().into(), device.id.as_str().into()]);
+            }
+            table.print();
+        }
+
+        println!("\nPending Folders:");
+        if self.folders.is_empty() {
+            println!("  (none)");
+        } else {
+            let mut table = Table::new(&["Folder", "ID", "Offered by", "When", "Notes"]).shrink(0);
+            for folder in &self.folders {
+                let device = match &folder.device_name {
+                    Some(name) => format!("{} ({})", name, short_id(&folder.device)),
+                    None => short_id(&folder.device).to_string(),
+                };
+                let when = folder
+                    .time
+                    .as_deref()
+                    .map(format_duration_since)
+                    .unwrap_or_default();
+                let mut notes = Vec::new();
+                if folder.exists {
+                    notes.push("exists here");
+                }
+                if folder.receive_encrypted {
+                    notes.push("encrypted");
+                }
+                table.row(vec![
+                    folder.label.as_str().into(),
+                    folder.id.as_str().into(),
+                    device.into(),
+                    when.into(),
+                    notes.join(", ").into(),
+                ]);
+            }
+            table.print();
+
+            println!("\nAccept with:");
+            for folder in &self.folders {
+                let command = format!(
+                    "syncthing folder accept {} --from {}",
+                    folder.id,
+                    short_id(&folder.device)
+                );
+                if folder.exists {
+                    println!("  {}  # exists here, only shares it", command);
+                } else {
+                    println!("  {} --path <dir>", command);
+                }
+            }
+        }
+    }
+}
+
+/// Output of `ignores get`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoresReport {
+    pub folder: String,
+    /// Lines of `.stignore` as written.
+    pub patterns: Vec<String>,
+    /// Patterns after `#include` expansion.
+    pub expanded: Vec<String>,
+    #[serde(skip)]
+    pub show_expanded: bool,
+}
+
+impl Render for IgnoresReport {
+    fn render(&self) {
+        let lines = if self.show_expanded {
+            &self.expanded
+        } else {
+            &self.patterns
+        };
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Output of `ignores test`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreTestReport {
+    pub folder: String,
+    pub paths: Vec<IgnoreVerdict>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreVerdict {
+    pub path: String,
+    pub ignored: bool,
+    /// Syncthing's own files, which are never synced.
+    pub internal: bool,
+    /// The expanded pattern that decided, if any.
+    pub pattern: Option<String>,
+    /// Ignored but deletable (`(?d)`).
+    pub deletable: bool,
+}
+
+impl Render for IgnoreTestReport {
+    fn render(&self) {
+        let mut table = Table::new(&["Path", "Result", "Reason"]).shrink(0);
+        for verdict in &self.paths {
+            let result = if verdict.ignored {
+                Cell::colored("ignored", Color::Yellow)
+            } else {
+                Cell::colored("synced", Color::Green)
+            };
+            let reason = match (&verdict.pattern, verdict.internal) {
+                (_, true) => "Syncthing internal file".to_string(),
+                (Some(pattern), _) if verdict.ignored => format!("matches {}", pattern),
+                (Some(pattern), _) => format!("kept by {}", pattern),
+                (None, _) => "no pattern matches".to_string(),
+            };
+            table.row(vec![verdict.path.as_str().into(), result, reason.into()]);
+        }
+        table.print();
+    }
+
+    fn render_quiet(&self) {
+        for verdict in self.paths.iter().filter(|v| v.ignored) {
+            println!("{}", verdict.path);
+        }
+    }
+}
+
+/// Output of `browse`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseReport {
+    pub folder: String,
+    pub prefix: Option<String>,
+    /// Entries as returned by `/rest/db/browse`, directories with `children`.
+    pub entries: Vec<BrowseEntry>,
+}
+
+impl Render for BrowseReport {
+    fn render(&self) {
+        println!("{}", self.prefix.as_deref().unwrap_or(&self.folder));
+        render_tree(&self.entries, "");
+    }
+}
+
+fn render_tree(entries: &[BrowseEntry], indent: &str) {
+    for (i, entry) in entries.iter().enumerate() {
+        let last = i == entries.len() - 1;
+        let branch = if last { "└── " } else { "├── " };
+        if entry.is_dir() {
+            println!("{}{}{}/", indent, branch, entry.name);
+            let child_indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+            render_tree(&entry.children, &child_indent);
+        } else {
+            println!(
+                "{}{}{} ({})",
+                indent,
+                branch,
+                entry.name,
+                format_bytes(entry.size)
+            );
+        }
+    }
+}
+
+/// Output of `file`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    pub folder: String,
+    pub path: String,
+    /// What this device has.
+    pub local: FileInfo,
+    /// The newest version known in the cluster.
+    pub global: FileInfo,
+    /// Names (or IDs) of devices that have the global version.
+    pub available_on: Vec<String>,
+}
+
+impl Render for FileReport {
+    fn render(&self) {
+        println!("{} in {}", self.path, self.folder);
+        println!();
+        let row = |name: &str, local: String, global: String| {
+            println!("{:<14} {:<32} {}", name, local, global);
+        };
+        row("", "Local".to_string(), "Global".to_string());
+        row(
+            "Size",
+            format_bytes(self.local.size),
+            format_bytes(self.global.size),
+        );
+        row(
+            "Modified",
+            self.local.modified.clone(),
+            self.global.modified.clone(),
+        );
+        row(
+            "Modified by",
+            self.local.modified_by.clone(),
+            self.global.modified_by.clone(),
+        );
+        row(
+            "Version",
+            self.local.version.join(","),
+            self.global.version.join(","),
+        );
+        row(
+            "Sequence",
+            self.local.sequence.to_string(),
+            self.global.sequence.to_string(),
+        );
+        if self.local.deleted || self.global.deleted {
+            row(
+                "Deleted",
+                self.local.deleted.to_string(),
+                self.global.deleted.to_string(),
+            );
+        }
+
+        println!();
+        if self.available_on.is_empty() {
+            println!("Available on: (no devices)");
+        } else {
+            println!("Available on: {}", self.available_on.join(", "));
+        }
+    }
+}
+
+/// Output of `localchanged`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalChangedReport {
+    pub folder: String,
+    pub files: Vec<FileInfo>,
+}
+
+impl Render for LocalChangedReport {
+    fn render(&self) {
+        if self.files.is_empty() {
+            println!("No local changes in folder '{}'", self.folder);
+            return;
+        }
+        for file in &self.files {
+            let change = if file.deleted {
+                "deleted".to_string()
+            } else {
+                format_bytes(file.size)
+            };
+            println!("{:<10} {}", change, file.name);
+        }
+        println!();
+        println!("{} locally changed items", self.files.len());
+    }
+}
+
+/// Output of `folders --id`: one folder's sync status.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderStatusReport {
+    pub id: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub status: DbStatus,
+}
+
+impl Render for FolderStatusReport {
+    fn render(&self) {
+        let status = &self.status;
+        println!("{} ({}): {}", self.label, self.id, status.state);
+        let counts = [
+            ("Global", status.global_files, status.global_bytes),
+            ("Local", status.local_files, status.local_bytes),
+            ("Needed", status.need_files, status.need_bytes),
+        ];
+        for (name, files, bytes) in counts {
+            println!("  {:<8} {} files, {}", name, files, format_bytes(bytes));
+        }
+        if status.receive_only_changed_files > 0 {
+            println!(
+                "  {:<8} {} files, {}",
+                "Changed",
+                status.receive_only_changed_files,
+                format_bytes(status.receive_only_changed_bytes)
+            );
+        }
+        if status.errors > 0 {
+            println!("  {:<8} {}", "Errors", status.errors);
+        }
+        if !status.error.is_empty() {
+            println!("  {:<8} {}", "Error", status.error);
+        }
+    }
+
+    fn render_quiet(&self) {
+        println!("{}", self.status.state);
+    }
+}
+
+/// Output of `open`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderPathReport {
+    pub folder: String,
+    pub path: String,
+}
+
+impl Render for FolderPathReport {
+    fn render(&self) {
+        println!("Opened {}", self.path);
+    }
+
+    fn render_quiet(&self) {
+        println!("{}", self.path);
+    }
+}
+
+/// A match in `search`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub folder: String,
+    pub label: String,
+    pub path: String,
+    pub size: u64,
+    pub dir: bool,
+    /// In the global view but not yet pulled to this device.
+    pub needed: bool,
+}
+
+impl Render for Vec<SearchHit> {
+    fn render(&self) {
+        if self.is_empty() {
+            println!("No matches");
+            return;
+        }
+        let mut table = Table::new(&["Folder", "Path", "Size", "State"]).shrink(1);
+        for hit in self {
+            let size = if hit.dir {
+                String::new()
+            } else {
+                format_bytes(hit.size)
+            };
+            let state = if hit.needed {
+                Cell::colored("needed", Color::Yellow)
+            } else {
+                Cell::colored("synced", Color::Green)
+            };
+            table.row(vec![
+                hit.label.as_str().into(),
+                hit.path.as_str().into(),
+                size.into(),
+                state,
+            ]);
+        }
+        table.print();
+    }
+
+    fn render_quiet(&self) {
+        for hit in self {
+            println!("{}", hit.path);
+        }
+    }
+}
+
+/// Output of `need`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NeedReport {
+    pub folder: String,
+    pub files: Vec<NeededFile>,
+    /// Counted before `--top` cut the list short.
+    pub total_files: usize,
+    pub total_bytes: u64,
+}
+
+/// A file in `need`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NeededFile {
+    /// `downloading`, `queued`, or empty when not yet queued.
+    pub queue: &'static str,
+    #[serde(flatten)]
+    pub file: NeedFile,
+}
+
+impl Render for NeedReport {
+    fn render(&self) {
+        if self.files.is_empty() {
+            println!("Folder '{}' has nothing left to pull", self.folder);
+            return;
+        }
+        for needed in &self.files {
+            let size = if needed.file.deleted {
+                "deleted".to_string()
+            } else {
+                format_bytes(needed.file.size)
+            };
+            println!("{:<10} {:<11} {}", size, needed.queue, needed.file.name);
+        }
+        println!();
+        if self.files.len() < self.total_files {
+            println!(
+                "Largest {} of {} needed items ({})",
+                self.files.len(),
+                self.total_files,
+                format_bytes(self.total_bytes)
+            );
+        } else {
+            println!(
+                "{} needed items ({})",
+                self.total_files,
+                format_bytes(self.total_bytes)
+            );
+        }
+    }
+
+    fn render_quiet(&self) {
+        for needed in &self.files {
+            println!("{}", needed.file.name);
+        }
+    }
+
+    fn rows_field(&self) -> Option<&'static str> {
+        Some("files")
+    }
+}
+
+/// Output of `folder lag`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LagReport {
+    pub folder: String,
+    /// Devices sharing the folder, the furthest behind first.
+    pub devices: Vec<DeviceLag>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceLag {
+    pub id: String,
+    pub name: String,
+    pub connected: bool,
+    /// Percentage of the folder the device has, as far as we know.
+    pub completion: f64,
+    pub need_bytes: u64,
+    pub need_items: u64,
+}
+
+impl Render for LagReport {
+    fn render(&self) {
+        if self.devices.is_empty() {
+            println!("Folder '{}' is not shared with any device", self.folder);
+            return;
+        }
+        let mut table = Table::new(&["Device", "Completion", "Need", "Items", "Status"]).shrink(0);
+        for device in &self.devices {
+            let completion = format!("{:.1}%", device.completion);
+            let completion = if device.need_bytes > 0 || device.need_items > 0 {
+                Cell::colored(completion, Color::Yellow)
+            } else {
+                Cell::colored(completion, Color::Green)
+            };
+            let status = if device.connected {
+                Cell::colored("connected", Color::Green)
+            } else {
+                Cell::colored("offline", Color::Red)
+            };
+            table.row(vec![
+                device.name.as_str().into(),
+                completion,
+                format_bytes(device.need_bytes).into(),
+                device.need_items.to_string().into(),
+                status,
+            ]);
+        }
+        table.print();
+    }
+
+    fn render_quiet(&self) {
+        for device in &self.devices {
+            if device.need_bytes > 0 || device.need_items > 0 {
+                println!("{}", device.id);
+            }
+        }
+    }
+}
+
+/// A file with archived versions.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionedFile {
+    pub path: String,
+    /// Archived versions, newest first.
+    pub versions: Vec<FileVersion>,
+}
+
+/// Output of `versions list`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionsReport {
+    pub folder: String,
+    pub files: Vec<VersionedFile>,
+}
+
+impl Render for VersionsReport {
+    fn render(&self) {
+        if self.files.is_empty() {
+            println!("No archived versions in folder '{}'", self.folder);
+        }
+        for file in &self.files {
+            println!("{}", file.path);
+            for version in &file.versions {
+                println!(
+                    "  {:<28} {:>10}  (archived {})",
+                    version.mod_time,
+                    format_bytes(version.size),
+                    format_duration_since(&version.version_time)
+                );
+            }
+        }
+    }
+}
+
+/// A single conflict copy.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictFile {
+    pub path: String,
+    /// RFC 3339 time the conflict was created, from the file name.
+    pub time: Option<String>,
+    /// Short ID of the device whose change lost.
+    pub device: String,
+    pub size: u64,
+}
+
+/// Conflict copies of one original file.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictGroup {
+    pub original: String,
+    /// Newest first.
+    pub conflicts: Vec<ConflictFile>,
+}
+
+/// Output of `conflicts`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictsReport {
+    pub folder: String,
+    pub groups: Vec<ConflictGroup>,
+}
+
+impl Render for ConflictsReport {
+    fn render(&self) {
+        if self.groups.is_empty() {
+            println!("No conflicts in folder '{}'", self.folder);
+            return;
+        }
+        for group in &self.groups {
+            println!("{}", group.original);
+            for conflict in &group.conflicts {
+                let age = conflict
+                    .time
+                    .as_deref()
+                    .map(format_duration_since)
+                    .unwrap_or_else(|| "?".to_string());
+                println!(
+                    "  {:<10} {:>10}  from {}  {}",
+                    age,
+                    format_bytes(conflict.size),
+                    conflict.device,
+                    conflict.path
+                );
+            }
+        }
+        let total: usize = self.groups.iter().map(|g| g.conflicts.len()).sum();
+        println!();
+        println!("{} conflicts in {} files", total, self.groups.len());
+    }
+}
+
+/// One entry of `events`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSummary {
+    pub id: u64,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// RFC 3339 timestamp of the event.
+    pub time: String,
+    /// Event-specific payload, as sent by Syncthing.
+    pub data: serde_json::Value,
+}
+
+impl EventSummary {
+    /// The scalar top-level fields of `data` as `key=value` pairs.
+    pub fn describe(&self) -> String {
+        let Some(fields) = self.data.as_object() else {
+            return String::new();
+        };
+        fields
+            .iter()
+            .filter_map(|(key, value)| match value {
+                serde_json::Value::String(s) => Some(format!("{}={}", key, s)),
+                serde_json::Value::Number(n) => Some(format!("{}={}", key, n)),
+                serde_json::Value::Bool(b) => Some(format!("{}={}", key, b)),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A single event as printed by `events --follow`.
+impl Render for EventSummary {
+    fn render(&self) {
+        let time = DateTime::parse_from_rfc3339(&self.time)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| self.time.clone());
+        println!(
+            "[{}] {} {:<22} {}",
+            self.id,
+            time,
+            self.event_type,
+            self.describe()
+        );
+    }
+}
+
+impl Render for Vec<EventSummary> {
+    fn render(&self) {
+        for event in self {
+            println!(
+                "[{}] {} - {}",
+                event.id,
+                format_duration_since(&event.time),
+                event.event_type
+            );
+        }
+    }
+}
+
+/// One folder in `folders stats`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderStatsRow {
+    pub id: String,
+    pub label: String,
+    /// RFC 3339 timestamp of the last completed scan, if any.
+    pub last_scan: Option<String>,
+    /// The last file synced into this folder from another device.
+    pub last_file: Option<LastFileInfo>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastFileInfo {
+    pub path: String,
+    /// RFC 3339 timestamp of when it arrived.
+    pub at: String,
+    /// Whether the change was a deletion.
+    pub deleted: bool,
+}
+
+impl Render for Vec<FolderStatsRow> {
+    fn render(&self) {
+        for folder in self {
+            println!("{} ({})", folder.label, folder.id);
+            match &folder.last_scan {
+                Some(at) => println!("  Last scan: {}", format_duration_since(at)),
+                None => println!("  Last scan: never"),
+            }
+            match &folder.last_file {
+                Some(file) => println!(
+                    "  Last file: {}{} ({})",
+                    file.path,
+                    if file.deleted { " (deleted)" } else { "" },
+                    format_duration_since(&file.at)
+                ),
+                None => println!("  Last file: none"),
+            }
+        }
+    }
+}
+
+/// Output of `matrix`: completion of every folder on every device.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixReport {
+    /// Column headers.
+    pub devices: Vec<MatrixDevice>,
+    pub folders: Vec<MatrixRow>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixDevice {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixRow {
+    pub id: String,
+    pub label: String,
+    /// Completion percentage per device, in the order of `devices`; `None`
+    /// where the folder isn't shared with the device or is paused.
+    pub completion: Vec<Option<f64>>,
+}
+
+impl Render for MatrixReport {
+    fn render(&self) {
+        const CELL: usize = 10;
+        let width = self
+            .folders
+            .iter()
+            .map(|f| f.label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Folder".len());
+
+        print!("{:<width$}", "Folder", width = width);
+        for device in &self.devices {
+            let name: String = device.name.chars().take(CELL).collect();
+            print!("  {:>CELL$}", name);
+        }
+        println!();
+
+        for folder in &self.folders {
+            print!("{:<width$}", folder.label, width = width);
+            for cell in &folder.completion {
+                let text = match cell {
+                    Some(pct) => format!("{:.0}%", pct.floor()),
+                    None => "-".to_string(),
+                };
+                print!("  {:>CELL$}", text);
+            }
+            println!();
+        }
+    }
+}
+
+/// Output of `folder versioning`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersioningReport {
+    pub folder: String,
+    /// `simple`, `trashcan`, `staggered` or `external`; `None` when off.
+    #[serde(rename = "type")]
+    pub versioning_type: Option<String>,
+    pub params: BTreeMap<String, String>,
+    pub cleanup_interval_s: u64,
+    pub path: Option<String>,
+}
+
+impl Render for VersioningReport {
+    fn render(&self) {
+        let Some(versioning_type) = &self.versioning_type else {
+            println!("Folder {}: no versioning", self.folder);
+            return;
+        };
+        println!("Folder {}: {} versioning", self.folder, versioning_type);
+        for (key, value) in &self.params {
+            println!("  {:<16} {}", key, value);
+        }
+        println!("  {:<16} {}s", "cleanupInterval", self.cleanup_interval_s);
+        if let Some(path) = &self.path {
+            println!("  {:<16} {}", "path", path);
+        }
+    }
+}
+
+/// Output of `id`: this node's device ID.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdReport {
+    pub id: String,
+    /// Draw the ID as a QR code for scanning with a phone.
+    #[serde(skip)]
+    pub qr: bool,
+}
+
+impl Render for IdReport {
+    fn render(&self) {
+        println!("{}", self.id);
+        if self.qr
+            && let Ok(code) = QrCode::new(self.id.as_bytes())
+        {
+            // Inverted so it scans on the usual dark terminal background
+            let qr = code
+                .render::<Dense1x2>()
+                .dark_color(Dense1x2::Light)
+                .light_color(Dense1x2::Dark)
+                .build();
+            println!();
+            println!("{}", qr);
+        }
+    }
+
+    fn render_quiet(&self) {
+        println!("{}", self.id);
+    }
+}
+
+/// Output of `device-id check`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceIdReport {
+    /// The ID in canonical form.
+    pub id: String,
+    /// Name of the configured device with this ID, if any.
+    pub known_as: Option<String>,
+}
+
+impl Render for DeviceIdReport {
+    fn render(&self) {
+        println!("{}", self.id);
+        if let Some(name) = &self.known_as {
+            println!("Already configured as {}", name);
+        }
+    }
+
+    fn render_quiet(&self) {
+        println!("{}", self.id);
+    }
+}
+
+/// One facility in `debug list`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugFacility {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+impl Render for Vec<DebugFacility> {
+    fn render(&self) {
+        for facility in self {
+            println!(
+                "{} {:<14} {}",
+                if facility.enabled { "*" } else { " " },
+                facility.name,
+                facility.description
+            );
+        }
+    }
+}
+
+/// Output of `discovery`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryReport {
+    /// Discovery mechanisms and their last error, if any.
+    pub mechanisms: Vec<DiscoveryMechanism>,
+    pub devices: Vec<DiscoveredDevice>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryMechanism {
+    pub name: String,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredDevice {
+    pub id: String,
+    pub name: String,
+    /// Addresses from the device config (`dynamic` means use discovery).
+    pub configured: Vec<String>,
+    /// Addresses found by local or global discovery.
+    pub discovered: Vec<String>,
+    /// Address of the current connection, if connected.
+    pub connected_address: Option<String>,
+}
+
+impl Render for DiscoveryReport {
+    fn render(&self) {
+        println!("Mechanisms:");
+        for mechanism in &self.mechanisms {
+            match &mechanism.error {
+                Some(error) => println!("  {:<40} error: {}", mechanism.name, error),
+                None => println!("  {:<40} ok", mechanism.name),
+            }
+        }
+        for device in &self.devices {
+            println!();
+            println!("{} ({})", device.name, short_id(&device.id));
+            println!("  Configured: {}", device.configured.join(", "));
+            if device.discovered.is_empty() {
+                println!("  Discovered: none");
+            } else {
+                println!("  Discovered: {}", device.discovered.join(", "));
+            }
+            if let Some(address) = &device.connected_address {
+                println!("  Connected:  {}", address);
+            }
+        }
+    }
+}
+
+/// Output of `limits`: bandwidth limits in KiB/s, 0 meaning unlimited.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitsReport {
+    pub max_send_kbps: u64,
+    pub max_recv_kbps: u64,
+    /// Devices with a limit of their own.
+    pub devices: Vec<DeviceLimits>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceLimits {
+    pub id: String,
+    pub name: String,
+    pub max_send_kbps: u64,
+    pub max_recv_kbps: u64,
+}
+
+impl Render for LimitsReport {
+    fn render(&self) {
+        println!("Download: {}", format_rate_limit(self.max_recv_kbps));
+        println!("Upload:   {}", format_rate_limit(self.max_send_kbps));
+        if !self.devices.is_empty() {
+            println!();
+            for device in &self.devices {
+                println!(
+                    "{:<20} down {}, up {}",
+                    device.name,
+                    format_rate_limit(device.max_recv_kbps),
+                    format_rate_limit(device.max_send_kbps)
+                );
+            }
+        }
+    }
+}
+
+fn format_rate_limit(kbps: u64) -> String {
+    if kbps == 0 {
+        "unlimited".to_string()
+    } else {
+        format!("{} KiB/s", kbps)
+    }
+}
+
+/// One instance in `cluster status`.
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterMember {
+    pub profile: String,
+    pub host: String,
+    /// Why the instance could not be queried; the other fields are then empty.
+    pub error: Option<String>,
+    pub version: String,
+    /// Uptime in seconds.
+    pub uptime: u64,
+    pub completion: f64,
+    pub need_bytes: u64,
+    /// Active system errors.
+    pub errors: usize,
+}
+
+impl Render for Vec<ClusterMember> {
+    fn render(&self) {
+        println!(
+            "{:<12} {:<28} {:<10} {:>8} {:>7} {:>10} {:>6}",
+            "PROFILE", "HOST", "VERSION", "UPTIME", "SYNC", "NEED", "ERRORS"
+        );
+        for member in self {
+            if let Some(error) = &member.error {
+                println!(
+                    "{:<12} {:<28} unreachable: {}",
+                    member.profile, member.host, error
+                );
+                continue;
+            }
+            println!(
+                "{:<12} {:<28} {:<10} {:>8} {:>6.1}% {:>10} {:>6}",
+                member.profile,
+                member.host,
+                member.version,
+                format!("{}h {}m", member.uptime / 3600, (member.uptime % 3600) / 60),
+                member.completion,
+                format_bytes(member.need_bytes),
+                member.errors
+            );
+        }
+    }
+}
+
+/// Output of `du`: folders by synced size, largest first.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuReport {
+    pub folders: Vec<FolderUsage>,
+    pub total: DuTotal,
+}
+
+/// One folder in `du`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderUsage {
+    /// Set with `du --all-profiles`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub id: String,
+    pub label: String,
+    /// Size of the newest version of everything in the folder.
+    pub global_bytes: u64,
+    /// What this instance has of it on disk.
+    pub local_bytes: u64,
+    /// What is still to be pulled.
+    pub need_bytes: u64,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DuTotal {
+    pub global_bytes: u64,
+    pub local_bytes: u64,
+    pub need_bytes: u64,
+}
+
+impl DuReport {
+    pub fn new(mut folders: Vec<FolderUsage>) -> Self {
+        folders.sort_by(|a, b| {
+            b.global_bytes
+                .cmp(&a.global_bytes)
+                .then_with(|| a.label.cmp(&b.label))
+        });
+        let mut total = DuTotal::default();
+        for folder in &folders {
+            total.global_bytes += folder.global_bytes;
+            total.local_bytes += folder.local_bytes;
+            total.need_bytes += folder.need_bytes;
+        }
+        Self { folders, total }
+    }
+}
+
+impl Render for DuReport {
+    fn render(&self) {
+        let profiles = self.folders.iter().any(|f| f.profile.is_some());
+        let mut headers = vec!["Folder", "Global", "Local", "Need"];
+        if profiles {
+            headers.insert(0, "Profile");
+        }
+        let label = usize::from(profiles);
+        let mut table = Table::new(&headers).shrink(label);
+        let sizes = |global, local, need| -> Vec<Cell> {
+            let need = if need > 0 {
+                Cell::colored(format_bytes(need), Color::Yellow)
+            } else {
+                Cell::from("")
+            };
+            vec![
+                format_bytes(global).into(),
+                format_bytes(local).into(),
+                need,
+            ]
+        };
+        for folder in &self.folders {
+            let mut row = Vec::new();
+            if profiles {
+                row.push(folder.profile.clone().unwrap_or_default().into());
+            }
+            row.push(folder.label.as_str().into());
+            row.extend(sizes(
+                folder.global_bytes,
+                folder.local_bytes,
+                folder.need_bytes,
+            ));
+            table.row(row);
+        }
+        let mut footer: Vec<Cell> = (0..label).map(|_| Cell::from("")).collect();
+        footer.push("Total".into());
+        footer.extend(sizes(
+            self.total.global_bytes,
+            self.total.local_bytes,
+            self.total.need_bytes,
+        ));
+        table.footer(footer);
+        table.print();
+    }
+}
+
+/// Output of `options get`: the commonly tuned global settings.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionsReport {
+    pub relays_enabled: bool,
+    pub nat_enabled: bool,
+    pub local_announce_enabled: bool,
+    pub global_announce_enabled: bool,
+    pub ur_accepted: i64,
+}
+
+impl Render for OptionsReport {
+    fn render(&self) {
+        println!("relaysEnabled:         {}", self.relays_enabled);
+        println!("natEnabled:            {}", self.nat_enabled);
+        println!("localAnnounceEnabled:  {}", self.local_announce_enabled);
+        println!("globalAnnounceEnabled: {}", self.global_announce_enabled);
+        println!("urAccepted:            {}", self.ur_accepted);
+    }
+}
+
+/// Output of `config ldap`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdapReport {
+    pub address: String,
+    #[serde(rename = "bindDN")]
+    pub bind_dn: String,
+    pub transport: String,
+    pub insecure_skip_verify: bool,
+    #[serde(rename = "searchBaseDN")]
+    pub search_base_dn: String,
+    pub search_filter: String,
+    /// Whether the GUI authenticates against this server.
+    pub gui_auth: bool,
+}
+
+impl Render for LdapReport {
+    fn render(&self) {
+        let or_none = |value: &str| {
+            if value.is_empty() {
+                "(none)".to_string()
+            } else {
+                value.to_string()
+            }
+        };
+        println!("Address:        {}", or_none(&self.address));
+        println!("Bind DN:        {}", or_none(&self.bind_dn));
+        println!("Transport:      {}", or_none(&self.transport));
+        println!("Skip verify:    {}", self.insecure_skip_verify);
+        println!("Search base DN: {}", or_none(&self.search_base_dn));
+        println!("Search filter:  {}", or_none(&self.search_filter));
+        println!(
+            "GUI login:      {}",
+            if self.gui_auth {
+                "LDAP"
+            } else {
+                "GUI user and password"
+            }
+        );
+    }
+}
+
+/// Output of `gui`. The password hash and API key are never shown.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuiReport {
+    pub enabled: bool,
+    pub address: String,
+    pub tls: bool,
+    pub user: String,
+    pub password_set: bool,
+}
+
+impl Render for GuiReport {
+    fn render(&self) {
+        println!("Enabled:  {}", self.enabled);
+        println!("Address:  {}", self.address);
+        println!("TLS:      {}", self.tls);
+        println!(
+            "User:     {}",
+            if self.user.is_empty() {
+                "(none)"
+            } else {
+                &self.user
+            }
+        );
+        println!(
+            "Password: {}",
+            if self.password_set { "set" } else { "not set" }
+        );
+    }
+}
+
+/// Output of `ping`: round-trip times of `/rest/system/ping`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PingReport {
+    pub host: String,
+    pub count: u32,
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Render for PingReport {
+    fn render(&self) {
+        if self.count == 1 {
+            println!("pong from {}: {:.1} ms", self.host, self.avg_ms);
+        } else {
+            println!(
+                "pong from {}: {} pings, min/avg/max = {:.1}/{:.1}/{:.1} ms",
+                self.host, self.count, self.min_ms, self.avg_ms, self.max_ms
+            );
+        }
+    }
+
+    fn render_quiet(&self) {
+        println!("{:.1}", self.avg_ms);
+    }
+}
+
+/// Outcome of `check`, with the process exit code it maps to.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckReport {
+    /// `ok`, `out-of-sync`, `unreachable`, or `error` when Syncthing answered
+    /// a request with an error.
+    pub status: String,
+    pub exit_code: i32,
+    /// One line per folder that failed a threshold.
+    pub problems: Vec<String>,
+    /// Folders running low on disk space; these don't change the status.
+    pub warnings: Vec<String>,
+    /// Number of folders that were checked (paused folders are skipped).
+    pub folders: usize,
+}
+
+impl Render for CheckReport {
+    fn render(&self) {
+        match self.status.as_str() {
+            "ok" => println!(
+                "{} {} folders in sync",
+                paint("OK:", Color::Green),
+                self.folders
+            ),
+            "unreachable" => println!(
+                "{} {}",
+                paint("UNREACHABLE:", Color::Red),
+                self.problems.join("; ")
+            ),
+            "error" => println!(
+                "{} {}",
+                paint("ERROR:", Color::Red),
+                self.problems.join("; ")
+            ),
+            _ => println!(
+                "{} {}",
+                paint("OUT OF SYNC:", Color::Red),
+                self.problems.join("; ")
+            ),
+        }
+        if !self.warnings.is_empty() {
+            println!(
+                "{} {}",
+                paint("WARNING:", Color::Yellow),
+                self.warnings.join("; ")
+            );
+        }
+    }
+
+    /// The exit code says it all.
+    fn render_quiet(&self) {}
+}
+
+/// Output of `apply`: what it takes to make the config match a manifest,
+/// and whether it was done.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyReport {
+    pub changes: Vec<PlannedChange>,
+    pub applied: bool,
+}
+
+/// One device, folder or the options, as `apply` would change them.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedChange {
+    /// `add`, `update` or `remove`.
+    pub action: &'static str,
+    /// `device`, `folder` or `options`.
+    pub kind: &'static str,
+    /// Device or folder ID; empty for the options.
+    pub id: String,
+    /// Device name or folder label.
+    pub name: String,
+    /// The settings being set; none for removals.
+    pub fields: Vec<FieldChange>,
+}
+
+/// A setting as configured now and as the manifest wants it. Nested keys
+/// are dotted, like `versioning.type`.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+impl ApplyReport {
+    /// The plan, one line per change followed by its fields, and a count.
+    pub fn plan_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for change in &self.changes {
+            let (sign, color) = match change.action {
+                "add" => ("+", Color::Green),
+                "remove" => ("-", Color::Red),
+                _ => ("~", Color::Yellow),
+            };
+            let subject = match (change.kind, change.name.as_str()) {
+                ("options", _) => "options".to_string(),
+                (kind, name) if name.is_empty() || name == change.id => {
+                    format!("{} {}", kind, change.id)
+                }
+                ("device", name) => format!("device {} ({})", name, short_id(&change.id)),
+                (kind, name) => format!("{} {} ({})", kind, change.id, name),
+            };
+            lines.push(paint(&format!("{} {}", sign, subject), color));
+            for field in &change.fields {
+                lines.push(if field.from.is_null() {
+                    format!("    {}: {}", field.field, field.to)
+                } else {
+                    format!("    {}: {} -> {}", field.field, field.from, field.to)
+                });
+            }
+        }
+        let (add, update, remove) = self.counts();
+        lines.push(format!(
+            "Plan: {} to add, {} to change, {} to remove",
+            add, update, remove
+        ));
+        lines
+    }
+
+    fn counts(&self) -> (usize, usize, usize) {
+        let count = |action| self.changes.iter().filter(|c| c.action == action).count();
+        (count("add"), count("update"), count("remove"))
+    }
+}
+
+impl Render for ApplyReport {
+    fn render(&self) {
+        if self.changes.is_empty() {
+            println!("No changes: the config already matches the manifest");
+        } else if self.applied {
+            let (add, update, remove) = self.counts();
+            println!(
+                "Applied: {} added, {} changed, {} removed",
+                add, update, remove
+            );
+        } else {
+            for line in self.plan_lines() {
+                println!("{}", line);
+            }
+        }
+    }
+
+    fn render_quiet(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+
+    #[test]
+    fn test_state_color() {
+        assert_eq!(state_color("idle"), Color::Green);
+        assert_eq!(state_color("syncing"), Color::Yellow);
+        assert_eq!(state_color("scan-waiting"), Color::Yellow);
+        assert_eq!(state_color("error"), Color::Red);
+    }
+
+    #[test]
+    fn test_short_id() {
+        assert_eq!(short_id("ABCDEFG-HIJKLMN"), "ABCDEFG");
+        assert_eq!(short_id("ABC"), "ABC");
+    }
+
+    #[test]
+    fn test_folder_summary_json_shape() {
+        let summary = FolderSummary {
+            id: "docs".to_string(),
+            label: "Documents".to_string(),
+            paused: false,
+            state: Some("idle".to_string()),
+            need_files: 0,
+            need_bytes: 0,
+            global_bytes: 0,
+            errors: 0,
+            disk_warning: None,
+        };
+        let value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(value["needBytes"], 0);
+        assert_eq!(value["state"], "idle");
+    }
+
+    #[test]
+    fn test_need_report_rows_are_files() {
+        let report = NeedReport {
+            folder: "docs".to_string(),
+            files: vec![NeededFile {
+                queue: "queued",
+                file: NeedFile {
+                    name: "a.txt".to_string(),
+                    size: 3,
+                    ..Default::default()
+                },
+            }],
+            total_files: 1,
+            total_bytes: 3,
+        };
+        assert_eq!(
+            Delimiter::Comma.lines(&rows(&report).unwrap(), true),
+            [
+                "queue,name,size,modified,deleted,flags,sequence",
+                "queued,a.txt,3,,false,,0"
+            ]
+        );
+    }
+}
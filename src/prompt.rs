@@ -0,0 +1,68 @@
+//! Interactive prompts.
+
+use anyhow::{Result, bail};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Ask a yes/no question on stderr; anything but `y`/`yes` is a no.
+pub fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Go on with a destructive action only if `yes` is set or the user agrees.
+/// Without a terminal to ask on, refuse instead of reading an answer from
+/// whatever stdin is, so scripts have to opt in with `--yes`.
+pub fn confirm_or_abort(question: &str, yes: bool) -> Result<()> {
+    if yes {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        bail!(
+            "Confirmation needed but stdin is not a terminal (pass --yes to go ahead): {}",
+            question
+        );
+    }
+    if !confirm(question)? {
+        bail!("Aborted");
+    }
+    Ok(())
+}
+
+/// Let the user pick one of `options` by number. Returns the chosen index.
+pub fn choose(question: &str, options: &[String]) -> Result<usize> {
+    eprintln!("{}", question);
+    for (i, option) in options.iter().enumerate() {
+        eprintln!("  {}) {}", i + 1, option);
+    }
+
+    loop {
+        eprint!("Choice [1-{}]: ", options.len());
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            bail!("No choice made");
+        }
+        match answer.trim().parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => return Ok(n - 1),
+            _ => eprintln!("Please enter a number between 1 and {}", options.len()),
+        }
+    }
+}
+
+/// Read a new secret from the terminal without echoing it, asking twice so
+/// a typo doesn't go unnoticed.
+pub fn new_password(question: &str) -> Result<String> {
+    let password = rpassword::prompt_password(format!("{}: ", question))?;
+    if password.is_empty() {
+        bail!("Empty password");
+    }
+    if rpassword::prompt_password("Repeat: ")? != password {
+        bail!("Passwords don't match");
+    }
+    Ok(password)
+}
@@ -0,0 +1,212 @@
+//! Column-aligned tables for list output.
+//!
+//! At a terminal, columns are padded to their widest cell under a header
+//! row, and one column may be truncated to fit the window. When stdout is
+//! piped the header and padding are dropped and cells are tab-separated, so
+//! the output stays easy to `cut` or `awk`.
+
+use crate::output::{Color, paint};
+use std::io::IsTerminal;
+
+/// Narrowest a shrinkable column is truncated to.
+const MIN_SHRUNK_WIDTH: usize = 8;
+
+pub struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<Cell>>,
+    /// Summary row (e.g. totals) shown under the rows at a terminal only.
+    footer: Option<Vec<Cell>>,
+    /// Column that gets truncated when the table is wider than the terminal.
+    shrink: Option<usize>,
+}
+
+pub struct Cell {
+    text: String,
+    color: Option<Color>,
+}
+
+impl Cell {
+    pub fn colored(text: impl Into<String>, color: Color) -> Self {
+        Self {
+            text: text.into(),
+            color: Some(color),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self { text, color: None }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+impl Table {
+    pub fn new(headers: &[&'static str]) -> Self {
+        Self {
+            headers: headers.to_vec(),
+            rows: Vec::new(),
+            footer: None,
+            shrink: None,
+        }
+    }
+
+    /// Let `column` (usually a free-form label) be truncated to fit.
+    pub fn shrink(mut self, column: usize) -> Self {
+        self.shrink = Some(column);
+        self
+    }
+
+    pub fn row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(cells);
+    }
+
+    pub fn footer(&mut self, cells: Vec<Cell>) {
+        self.footer = Some(cells);
+    }
+
+    pub fn print(&self) {
+        let lines = if std::io::stdout().is_terminal() {
+            let width = ratatui::crossterm::terminal::size()
+                .ok()
+                .map(|(columns, _)| usize::from(columns))
+                .filter(|&columns| columns > 0);
+            self.aligned(width)
+        } else {
+            self.plain()
+        };
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+
+    /// Tab-separated rows without the header or footer.
+    fn plain(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\t")
+            })
+            .collect()
+    }
+
+    /// Header and rows padded into columns, fitting in `width` if given.
+    fn aligned(&self, width: Option<usize>) -> Vec<String> {
+        const GAP: usize = 2;
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+        for row in self.rows.iter().chain(&self.footer) {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.text.chars().count());
+            }
+        }
+        if let (Some(column), Some(width)) = (self.shrink, width) {
+            let total = widths.iter().sum::<usize>() + GAP * widths.len().saturating_sub(1);
+            if total > width {
+                let excess = total - width;
+                widths[column] = widths[column]
+                    .saturating_sub(excess)
+                    .max(MIN_SHRUNK_WIDTH.min(widths[column]));
+            }
+        }
+
+        let header: Vec<Cell> = self.headers.iter().map(|&h| Cell::from(h)).collect();
+        std::iter::once(&header)
+            .chain(&self.rows)
+            .chain(&self.footer)
+            .map(|row| {
+                let last = row.len().saturating_sub(1);
+                let cells: Vec<String> = row
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| {
+                        let text = truncate(&cell.text, widths[i]);
+                        let padded = if i == last {
+                            text
+                        } else {
+                            format!("{:<width$}", text, width = widths[i])
+                        };
+                        match cell.color {
+                            Some(color) => paint(&padded, color),
+                            None => padded,
+                        }
+                    })
+                    .collect();
+                // No trailing spaces when the last columns are empty
+                cells.join(&" ".repeat(GAP)).trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+/// Cut `text` to `width` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new(&["Folder", "State"]).shrink(0);
+        table.row(vec!["Documents and other things".into(), "idle".into()]);
+        table.row(vec!["Photos".into(), "syncing".into()]);
+        table
+    }
+
+    #[test]
+    fn test_aligned() {
+        assert_eq!(
+            table().aligned(None),
+            [
+                "Folder                      State",
+                "Documents and other things  idle",
+                "Photos                      syncing",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aligned_shrinks_to_width() {
+        assert_eq!(
+            table().aligned(Some(23)),
+            [
+                "Folder          State",
+                "Documents and…  idle",
+                "Photos          syncing",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_footer_only_aligned() {
+        let mut table = table();
+        table.footer(vec!["Total".into(), "".into()]);
+        assert_eq!(
+            table.aligned(None).last().map(String::as_str),
+            Some("Total")
+        );
+        assert_eq!(table.plain().len(), 2);
+    }
+
+    #[test]
+    fn test_plain() {
+        assert_eq!(
+            table().plain(),
+            ["Documents and other things\tidle", "Photos\tsyncing"]
+        );
+    }
+}
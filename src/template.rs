@@ -0,0 +1,114 @@
+//! `--format` templates: `{{.field}}` placeholders filled from the JSON
+//! form of a command's output, one line per item for lists.
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+/// A parsed `--format` template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    /// Path into the item, e.g. `["versioning", "type"]` for `{{.versioning.type}}`.
+    Field(Vec<String>),
+}
+
+impl Template {
+    /// Parse `template`, unescaping `\t`, `\n` and `\\` in the literal text
+    /// since shells pass them through as typed.
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            push_text(&mut parts, &rest[..start]);
+            let Some(end) = rest[start..].find("}}") else {
+                bail!("Unclosed '{{{{' in --format template");
+            };
+            let field = rest[start + 2..start + end].trim();
+            let Some(path) = field.strip_prefix('.') else {
+                bail!("Template fields start with a dot, like {{{{.{}}}}}", field);
+            };
+            parts.push(Part::Field(
+                path.split('.')
+                    .filter(|key| !key.is_empty())
+                    .map(String::from)
+                    .collect(),
+            ));
+            rest = &rest[start + end + 2..];
+        }
+        push_text(&mut parts, rest);
+        Ok(Self { parts })
+    }
+
+    /// Print one line per element of a JSON array, or a single line for
+    /// anything else.
+    pub fn print(&self, value: &Value) {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    println!("{}", self.fill(item));
+                }
+            }
+            value => println!("{}", self.fill(value)),
+        }
+    }
+
+    fn fill(&self, item: &Value) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(path) => {
+                    let field = path.iter().try_fold(item, |value, key| value.get(key));
+                    match field {
+                        None | Some(Value::Null) => String::new(),
+                        Some(Value::String(s)) => s.clone(),
+                        Some(other) => other.to_string(),
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+fn push_text(parts: &mut Vec<Part>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let text = text
+        .replace("\\\\", "\u{0}")
+        .replace("\\t", "\t")
+        .replace("\\n", "\n")
+        .replace('\u{0}', "\\");
+    parts.push(Part::Text(text));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fill() {
+        let template = Template::parse(r"{{.label}}\t{{ .state }}\t{{.needBytes}}").unwrap();
+        let item = json!({"label": "Docs", "state": "idle", "needBytes": 42});
+        assert_eq!(template.fill(&item), "Docs\tidle\t42");
+    }
+
+    #[test]
+    fn test_fill_nested_and_missing() {
+        let template = Template::parse("{{.versioning.type}}|{{.nope}}|{{.paused}}").unwrap();
+        let item = json!({"versioning": {"type": "simple"}, "paused": false});
+        assert_eq!(template.fill(&item), "simple||false");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Template::parse("{{.label").is_err());
+        assert!(Template::parse("{{label}}").is_err());
+    }
+}